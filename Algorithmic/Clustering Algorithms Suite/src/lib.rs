@@ -1,4 +1,34 @@
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Errors reported by the clustering algorithms.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ClusterError {
+    /// A point's dimensionality differs from the rest of the input.
+    DimensionMismatch { expected: usize, found: usize },
+    /// `k` is zero or exceeds the number of points, so `k` distinct clusters
+    /// can't be seeded (centroids, medoids or mixture means).
+    InvalidClusterCount { k: usize, points: usize },
+}
+
+impl fmt::Display for ClusterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClusterError::DimensionMismatch { expected, found } => write!(
+                f,
+                "dimension mismatch: expected {}-D point, found {}-D",
+                expected, found
+            ),
+            ClusterError::InvalidClusterCount { k, points } => {
+                write!(f, "cannot form {} clusters from {} points", k, points)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ClusterError {}
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct Point {
@@ -10,7 +40,30 @@ impl Point {
         Point { coords }
     }
 
+    /// Number of coordinates.
+    pub fn dim(&self) -> usize {
+        self.coords.len()
+    }
+
+    /// Euclidean distance, or an error if the points have different dimensions.
+    pub fn try_distance(&self, other: &Point) -> Result<f64, ClusterError> {
+        if self.dim() != other.dim() {
+            return Err(ClusterError::DimensionMismatch {
+                expected: self.dim(),
+                found: other.dim(),
+            });
+        }
+        Ok(self.distance(other))
+    }
+
+    /// Euclidean distance. Both points must have the same dimension; use
+    /// `try_distance` when that isn't already guaranteed.
     pub fn distance(&self, other: &Point) -> f64 {
+        debug_assert_eq!(
+            self.dim(),
+            other.dim(),
+            "distance between points of different dimensions"
+        );
         self.coords
             .iter()
             .zip(other.coords.iter())
@@ -18,63 +71,308 @@ impl Point {
             .sum::<f64>()
             .sqrt()
     }
+
+    /// Manhattan (taxicab) distance: the sum of absolute coordinate
+    /// differences. Same dimension requirement as `distance`.
+    pub fn manhattan_distance(&self, other: &Point) -> f64 {
+        debug_assert_eq!(
+            self.dim(),
+            other.dim(),
+            "distance between points of different dimensions"
+        );
+        self.coords
+            .iter()
+            .zip(other.coords.iter())
+            .map(|(a, b)| (a - b).abs())
+            .sum()
+    }
+
+    /// Cosine distance `1 - cos(angle)`, in `[0, 2]`: it compares directions
+    /// and ignores magnitudes. A zero vector has no direction, so it is at
+    /// the maximum distance 2 from every other point (and 0 from itself)
+    /// instead of producing NaN. Same dimension requirement as `distance`.
+    pub fn cosine_distance(&self, other: &Point) -> f64 {
+        debug_assert_eq!(
+            self.dim(),
+            other.dim(),
+            "distance between points of different dimensions"
+        );
+        let norm = |p: &Point| p.coords.iter().map(|x| x * x).sum::<f64>().sqrt();
+        let (a, b) = (norm(self), norm(other));
+        if a == 0.0 || b == 0.0 {
+            return if a == b { 0.0 } else { 2.0 };
+        }
+        let dot: f64 = self
+            .coords
+            .iter()
+            .zip(&other.coords)
+            .map(|(x, y)| x * y)
+            .sum();
+        (1.0 - dot / (a * b)).clamp(0.0, 2.0)
+    }
+
+    /// True if the points have the same dimension and every coordinate
+    /// differs by at most `eps`.
+    pub fn approx_eq(&self, other: &Point, eps: f64) -> bool {
+        self.dim() == other.dim()
+            && self
+                .coords
+                .iter()
+                .zip(other.coords.iter())
+                .all(|(a, b)| (a - b).abs() <= eps)
+    }
+}
+
+/// True if two label vectors describe the same partition, i.e. they are equal
+/// up to a renaming of the labels. Useful for comparing runs, since cluster
+/// ids depend on initialization.
+pub fn clusterings_equivalent(a: &[usize], b: &[usize]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut forward = HashMap::new();
+    let mut backward = HashMap::new();
+    a.iter()
+        .zip(b)
+        .all(|(&x, &y)| *forward.entry(x).or_insert(y) == y && *backward.entry(y).or_insert(x) == x)
+}
+
+/// Mean silhouette coefficient of a clustering, in `[-1, 1]`; higher means
+/// tighter, better separated clusters.
+///
+/// For a point with mean distance `a` to the rest of its cluster and mean
+/// distance `b` to the nearest other cluster, the coefficient is
+/// `(b - a) / max(a, b)`. Points alone in their cluster count as 0.
+///
+/// Labels that don't convert to `usize` (DBSCAN's `-1` noise label) are
+/// left out entirely, so both `KMeans` and `DBSCAN` output can be passed
+/// directly. Returns 0 when fewer than two clusters remain. Takes
+/// `O(n^2)` distance computations.
+///
+/// # Panics
+///
+/// Panics if `labels` and `points` differ in length.
+pub fn silhouette_score<L: Copy + TryInto<usize>>(points: &[Point], labels: &[L]) -> f64 {
+    assert_eq!(points.len(), labels.len(), "one label per point required");
+    let labelled: Vec<(&Point, usize)> = points
+        .iter()
+        .zip(labels)
+        .filter_map(|(p, &l)| l.try_into().ok().map(|l| (p, l)))
+        .collect();
+    let mut sizes: HashMap<usize, usize> = HashMap::new();
+    for &(_, l) in &labelled {
+        *sizes.entry(l).or_insert(0) += 1;
+    }
+    if sizes.len() < 2 {
+        return 0.0;
+    }
+
+    let total: f64 = labelled
+        .iter()
+        .map(|&(p, own)| {
+            if sizes[&own] == 1 {
+                return 0.0;
+            }
+            let mut sums: HashMap<usize, f64> = HashMap::new();
+            for &(q, l) in &labelled {
+                *sums.entry(l).or_insert(0.0) += p.distance(q);
+            }
+            let a = sums[&own] / (sizes[&own] - 1) as f64;
+            let b = sums
+                .iter()
+                .filter(|&(&l, _)| l != own)
+                .map(|(l, sum)| sum / sizes[l] as f64)
+                .fold(f64::INFINITY, f64::min);
+            if a.max(b) == 0.0 {
+                0.0
+            } else {
+                (b - a) / a.max(b)
+            }
+        })
+        .sum();
+    total / labelled.len() as f64
+}
+
+/// Random source for one `fit`: seeded when the caller asked for
+/// reproducible runs, fresh entropy otherwise.
+fn fit_rng(seed: Option<u64>) -> StdRng {
+    match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_rng(&mut rand::rng()),
+    }
+}
+
+/// Checks that every point has the same dimension as the first.
+fn validate_dimensions(points: &[Point]) -> Result<(), ClusterError> {
+    if let Some(first) = points.first() {
+        for p in points {
+            if p.dim() != first.dim() {
+                return Err(ClusterError::DimensionMismatch {
+                    expected: first.dim(),
+                    found: p.dim(),
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// How `KMeans` picks its starting centroids.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum KMeansInit {
+    /// k-means++: spread the seeds out, favouring points far from the
+    /// centroids chosen so far.
+    PlusPlus,
+    /// `k` distinct points chosen uniformly at random.
+    Random,
+}
+
+/// Result of fitting `KMeans`.
+#[derive(Clone, Debug)]
+pub struct KMeansModel {
+    /// Cluster of each point, as an index into `centroids`.
+    pub assignments: Vec<usize>,
+    pub centroids: Vec<Point>,
+    /// Within-cluster sum of squares: the summed squared distance from each
+    /// point to its centroid, under the metric the model was fitted with.
+    pub inertia: f64,
+    /// The metric the model was fitted with, reused by `predict`.
+    metric: fn(&Point, &Point) -> f64,
+}
+
+/// Models compare by their fitted results; function pointers have no
+/// reliable equality, so the metric is left out.
+impl PartialEq for KMeansModel {
+    fn eq(&self, other: &Self) -> bool {
+        self.assignments == other.assignments
+            && self.centroids == other.centroids
+            && self.inertia == other.inertia
+    }
+}
+
+impl KMeansModel {
+    /// Index of the centroid nearest to `point` under the metric the model
+    /// was fitted with, the cluster `fit` would put it in. Ties go to the
+    /// lower index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the model has no centroids (it was fitted on no points).
+    pub fn predict(&self, point: &Point) -> usize {
+        assert!(!self.centroids.is_empty(), "model has no centroids");
+        nearest_centroid(point, &self.centroids, self.metric)
+    }
+}
+
+/// Index of the centroid closest to `point`, preferring the lowest index on
+/// ties.
+fn nearest_centroid(
+    point: &Point,
+    centroids: &[Point],
+    metric: fn(&Point, &Point) -> f64,
+) -> usize {
+    let mut min_dist = f64::MAX;
+    let mut best_cluster = 0;
+    for (j, centroid) in centroids.iter().enumerate() {
+        let dist = metric(point, centroid);
+        if dist < min_dist {
+            min_dist = dist;
+            best_cluster = j;
+        }
+    }
+    best_cluster
 }
 
 /// K-Means clustering algorithm.
+///
+/// Euclidean by default, where each centroid moves to the mean of its
+/// cluster. The mean only minimizes squared Euclidean distance, so with
+/// another metric (`with_metric`) each centroid instead moves to its
+/// cluster's medoid: the member with the smallest total distance to the
+/// others.
 pub struct KMeans {
     k: usize,
     max_iters: usize,
+    init: KMeansInit,
+    seed: Option<u64>,
+    metric: fn(&Point, &Point) -> f64,
+    /// Whether `metric` is `Point::distance`, for which the mean is optimal.
+    euclidean: bool,
 }
 
 impl KMeans {
+    /// K-Means seeded with k-means++.
     pub fn new(k: usize, max_iters: usize) -> Self {
-        KMeans { k, max_iters }
+        KMeans {
+            euclidean: true,
+            ..Self::with_metric(k, max_iters, Point::distance)
+        }
     }
 
-    pub fn fit(&self, points: &[Point]) -> Vec<usize> {
-        if points.is_empty() {
-            return vec![];
+    /// K-Means with a custom (symmetric) distance function, such as
+    /// `Point::manhattan_distance` or `Point::cosine_distance`, used for
+    /// seeding, assignment and inertia. Centroids are updated to cluster
+    /// medoids, which costs `O(m^2)` distances per cluster of `m` points
+    /// and iteration, and keeps every centroid an input point.
+    pub fn with_metric(k: usize, max_iters: usize, metric: fn(&Point, &Point) -> f64) -> Self {
+        KMeans {
+            k,
+            max_iters,
+            init: KMeansInit::PlusPlus,
+            seed: None,
+            metric,
+            euclidean: false,
         }
+    }
 
-        let mut rng = rand::rng();
-        let mut centroids = Vec::with_capacity(self.k);
-
-        // K-Means++ Initialization
-        if !points.is_empty() {
-            // 1. Choose first centroid randomly
-            centroids.push(points[rng.random_range(0..points.len())].clone());
+    /// K-Means seeded with uniformly random points. Usually worse than the
+    /// k-means++ seeding of `new`; kept for comparison.
+    pub fn with_random_init(k: usize, max_iters: usize) -> Self {
+        KMeans {
+            init: KMeansInit::Random,
+            ..Self::new(k, max_iters)
+        }
+    }
 
-            // 2. Choose remaining k-1 centroids
-            for _ in 1..self.k {
-                let mut dists = Vec::with_capacity(points.len());
-                let mut sum_sq_dist = 0.0;
+    /// Seeds the random initialization, so the same input always yields the
+    /// same clustering. Unseeded runs draw fresh entropy each time.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
 
-                for p in points {
-                    let mut min_dist_sq = f64::MAX;
-                    for c in &centroids {
-                        let d = p.distance(c);
-                        let d_sq = d * d;
-                        if d_sq < min_dist_sq {
-                            min_dist_sq = d_sq;
-                        }
-                    }
-                    dists.push(min_dist_sq);
-                    sum_sq_dist += min_dist_sq;
-                }
+    /// Cluster assignment of each point; see `fit_model` for the centroids.
+    pub fn fit(&self, points: &[Point]) -> Result<Vec<usize>, ClusterError> {
+        Ok(self.fit_model(points)?.assignments)
+    }
 
-                // Roulette wheel selection
-                let mut r = rng.random_range(0.0..sum_sq_dist);
-                let mut next_centroid_idx = 0;
-                for (i, &d_sq) in dists.iter().enumerate() {
-                    r -= d_sq;
-                    if r <= 0.0 {
-                        next_centroid_idx = i;
-                        break;
-                    }
-                }
-                centroids.push(points[next_centroid_idx].clone());
-            }
+    /// Fails if the points differ in dimension, or if `k` is 0 or larger
+    /// than the number of points. An empty input yields an empty model.
+    pub fn fit_model(&self, points: &[Point]) -> Result<KMeansModel, ClusterError> {
+        validate_dimensions(points)?;
+        if points.is_empty() {
+            return Ok(KMeansModel {
+                assignments: vec![],
+                centroids: vec![],
+                inertia: 0.0,
+                metric: self.metric,
+            });
         }
+        if self.k == 0 || self.k > points.len() {
+            return Err(ClusterError::InvalidClusterCount {
+                k: self.k,
+                points: points.len(),
+            });
+        }
+
+        let mut rng = fit_rng(self.seed);
+        let mut centroids = match self.init {
+            KMeansInit::PlusPlus => kmeans_plus_plus(points, self.k, self.metric, &mut rng),
+            KMeansInit::Random => rand::seq::index::sample(&mut rng, points.len(), self.k)
+                .into_iter()
+                .map(|i| points[i].clone())
+                .collect(),
+        };
 
         let mut assignments = vec![0; points.len()];
 
@@ -83,15 +381,7 @@ impl KMeans {
 
             // Assign points to nearest centroid
             for (i, point) in points.iter().enumerate() {
-                let mut min_dist = f64::MAX;
-                let mut best_cluster = 0;
-                for (j, centroid) in centroids.iter().enumerate() {
-                    let dist = point.distance(centroid);
-                    if dist < min_dist {
-                        min_dist = dist;
-                        best_cluster = j;
-                    }
-                }
+                let best_cluster = nearest_centroid(point, &centroids, self.metric);
                 if assignments[i] != best_cluster {
                     assignments[i] = best_cluster;
                     changed = true;
@@ -102,67 +392,817 @@ impl KMeans {
                 break;
             }
 
-            // Update centroids
-            let mut new_centroids = vec![vec![0.0; points[0].coords.len()]; self.k];
-            let mut counts = vec![0; self.k];
+            if self.euclidean {
+                update_centroids(points, &assignments, &mut centroids, &mut rng);
+            } else {
+                update_medoids(points, &assignments, &mut centroids, self.metric, &mut rng);
+            }
+        }
 
-            for (i, point) in points.iter().enumerate() {
-                let cluster = assignments[i];
-                for (d, val) in point.coords.iter().enumerate() {
-                    new_centroids[cluster][d] += val;
+        let inertia = points
+            .iter()
+            .zip(&assignments)
+            .map(|(p, &c)| (self.metric)(p, &centroids[c]).powi(2))
+            .sum();
+        Ok(KMeansModel {
+            assignments,
+            centroids,
+            inertia,
+            metric: self.metric,
+        })
+    }
+}
+
+/// Mini-batch K-Means (Sculley, 2010): instead of reassigning every point
+/// each iteration, it samples `batch_size` points and nudges each one's
+/// nearest centroid toward it with a per-centroid learning rate of
+/// `1 / (points seen so far)`, so every centroid tracks the running mean of
+/// the samples it has absorbed.
+///
+/// Much cheaper per iteration than `KMeans` on large inputs, at the cost of
+/// slightly noisier centroids. Seeded with k-means++; the final assignment
+/// of every point to its nearest centroid is one full pass.
+pub struct MiniBatchKMeans {
+    k: usize,
+    batch_size: usize,
+    max_iters: usize,
+    seed: Option<u64>,
+}
+
+impl MiniBatchKMeans {
+    /// # Panics
+    ///
+    /// Panics if `batch_size` is 0.
+    pub fn new(k: usize, batch_size: usize, max_iters: usize) -> Self {
+        assert!(batch_size > 0, "batch size must be positive");
+        MiniBatchKMeans {
+            k,
+            batch_size,
+            max_iters,
+            seed: None,
+        }
+    }
+
+    /// Seeds the initialization and batch sampling, for reproducible fits.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Cluster assignment of each point; see `fit_model` for the centroids.
+    pub fn fit(&self, points: &[Point]) -> Result<Vec<usize>, ClusterError> {
+        Ok(self.fit_model(points)?.assignments)
+    }
+
+    /// Same validation as `KMeans::fit_model`.
+    pub fn fit_model(&self, points: &[Point]) -> Result<KMeansModel, ClusterError> {
+        validate_dimensions(points)?;
+        if points.is_empty() {
+            return Ok(KMeansModel {
+                assignments: vec![],
+                centroids: vec![],
+                inertia: 0.0,
+                metric: Point::distance,
+            });
+        }
+        if self.k == 0 || self.k > points.len() {
+            return Err(ClusterError::InvalidClusterCount {
+                k: self.k,
+                points: points.len(),
+            });
+        }
+
+        let mut rng = fit_rng(self.seed);
+        let mut centroids = kmeans_plus_plus(points, self.k, Point::distance, &mut rng);
+        let mut seen = vec![0usize; self.k];
+        let mut batch = Vec::with_capacity(self.batch_size);
+
+        for _ in 0..self.max_iters {
+            // Assign the whole batch against the same centroids, then step.
+            batch.clear();
+            batch.extend((0..self.batch_size).map(|_| {
+                let point = &points[rng.random_range(0..points.len())];
+                (point, nearest_centroid(point, &centroids, Point::distance))
+            }));
+            for &(point, cluster) in &batch {
+                seen[cluster] += 1;
+                let rate = 1.0 / seen[cluster] as f64;
+                for (c, x) in centroids[cluster].coords.iter_mut().zip(&point.coords) {
+                    *c += rate * (x - *c);
                 }
-                counts[cluster] += 1;
             }
+        }
+
+        let assignments: Vec<usize> = points
+            .iter()
+            .map(|p| nearest_centroid(p, &centroids, Point::distance))
+            .collect();
+        let inertia = points
+            .iter()
+            .zip(&assignments)
+            .map(|(p, &c)| p.distance(&centroids[c]).powi(2))
+            .sum();
+        Ok(KMeansModel {
+            assignments,
+            centroids,
+            inertia,
+            metric: Point::distance,
+        })
+    }
+}
 
-            for (j, centroid) in centroids.iter_mut().enumerate() {
-                if counts[j] > 0 {
-                    for (d, coord) in centroid.coords.iter_mut().enumerate() {
-                        *coord = new_centroids[j][d] / counts[j] as f64;
+/// Moves each centroid to the mean of its assigned points. A centroid left
+/// with no points is re-initialized to a random point instead.
+fn update_centroids(
+    points: &[Point],
+    assignments: &[usize],
+    centroids: &mut [Point],
+    rng: &mut impl Rng,
+) {
+    let mut sums = vec![vec![0.0; points[0].dim()]; centroids.len()];
+    let mut counts = vec![0; centroids.len()];
+
+    for (point, &cluster) in points.iter().zip(assignments) {
+        for (d, val) in point.coords.iter().enumerate() {
+            sums[cluster][d] += val;
+        }
+        counts[cluster] += 1;
+    }
+
+    for (j, centroid) in centroids.iter_mut().enumerate() {
+        if counts[j] > 0 {
+            for (d, coord) in centroid.coords.iter_mut().enumerate() {
+                *coord = sums[j][d] / counts[j] as f64;
+            }
+        } else {
+            *centroid = points[rng.random_range(0..points.len())].clone();
+        }
+    }
+}
+
+/// Moves each centroid to its cluster's medoid under `metric`: the member
+/// with the smallest summed distance to the other members (the first on
+/// ties). An empty cluster is reseeded from a random point, as in
+/// `update_centroids`.
+fn update_medoids(
+    points: &[Point],
+    assignments: &[usize],
+    centroids: &mut [Point],
+    metric: fn(&Point, &Point) -> f64,
+    rng: &mut impl Rng,
+) {
+    for (j, centroid) in centroids.iter_mut().enumerate() {
+        let members: Vec<&Point> = points
+            .iter()
+            .zip(assignments)
+            .filter(|&(_, &c)| c == j)
+            .map(|(p, _)| p)
+            .collect();
+        let total = |a: &Point| members.iter().map(|b| metric(a, b)).sum::<f64>();
+        *centroid = match members.iter().min_by(|a, b| total(a).total_cmp(&total(b))) {
+            Some(medoid) => (*medoid).clone(),
+            None => points[rng.random_range(0..points.len())].clone(),
+        };
+    }
+}
+
+/// k-means++ seeding: the first centroid is a random point, each further
+/// one is drawn with probability proportional to its squared distance from
+/// the nearest centroid chosen so far.
+fn kmeans_plus_plus(
+    points: &[Point],
+    k: usize,
+    metric: fn(&Point, &Point) -> f64,
+    rng: &mut impl Rng,
+) -> Vec<Point> {
+    let mut centroids = Vec::with_capacity(k);
+    centroids.push(points[rng.random_range(0..points.len())].clone());
+
+    let mut dists: Vec<f64> = points
+        .iter()
+        .map(|p| metric(p, &centroids[0]).powi(2))
+        .collect();
+    for _ in 1..k {
+        let sum_sq_dist: f64 = dists.iter().sum();
+        let next = if sum_sq_dist > 0.0 {
+            // Roulette wheel selection
+            let mut r = rng.random_range(0.0..sum_sq_dist);
+            let mut next = dists.len() - 1;
+            for (i, &d_sq) in dists.iter().enumerate() {
+                r -= d_sq;
+                if r <= 0.0 {
+                    next = i;
+                    break;
+                }
+            }
+            next
+        } else {
+            // Every point sits on a centroid already.
+            rng.random_range(0..points.len())
+        };
+        let centroid = points[next].clone();
+        for (d_sq, p) in dists.iter_mut().zip(points) {
+            *d_sq = d_sq.min(metric(p, &centroid).powi(2));
+        }
+        centroids.push(centroid);
+    }
+    centroids
+}
+
+/// Result of fitting `GaussianMixture`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GaussianMixtureModel {
+    /// Mixing weight of each component; they sum to 1.
+    pub weights: Vec<f64>,
+    pub means: Vec<Point>,
+    /// Per-dimension variance of each component (diagonal covariance),
+    /// including the regularization term.
+    pub variances: Vec<Vec<f64>>,
+    /// Posterior probability of each component for each point.
+    pub responsibilities: Vec<Vec<f64>>,
+    /// Log-likelihood of the data under the final parameters.
+    pub log_likelihood: f64,
+    /// Whether the log-likelihood settled within the tolerance before
+    /// `max_iters` ran out. If not, the parameters are the best effort so far.
+    pub converged: bool,
+    pub iterations: usize,
+    /// Whether some component's variance fell below the regularization term
+    /// at any point, i.e. it collapsed (or nearly) onto identical points and
+    /// only the regularization kept its covariance invertible.
+    pub regularized: bool,
+}
+
+impl GaussianMixtureModel {
+    /// Most likely component of each point.
+    pub fn assignments(&self) -> Vec<usize> {
+        self.responsibilities
+            .iter()
+            .map(|row| {
+                (0..row.len())
+                    .max_by(|&a, &b| row[a].total_cmp(&row[b]))
+                    .unwrap_or(0)
+            })
+            .collect()
+    }
+}
+
+/// Gaussian Mixture Model with diagonal covariances, fitted by
+/// Expectation-Maximization.
+///
+/// A small `regularization` is added to every variance so a component that
+/// collapses onto a single point (zero variance, infinite density) stays
+/// finite instead of producing NaNs.
+pub struct GaussianMixture {
+    k: usize,
+    max_iters: usize,
+    tolerance: f64,
+    regularization: f64,
+    seed: Option<u64>,
+}
+
+impl GaussianMixture {
+    pub fn new(k: usize, max_iters: usize) -> Self {
+        GaussianMixture {
+            k,
+            max_iters,
+            tolerance: 1e-6,
+            regularization: 1e-6,
+            seed: None,
+        }
+    }
+
+    /// Stop once the log-likelihood improves by less than `tolerance`.
+    /// Defaults to `1e-6`.
+    pub fn with_tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Variance added to every diagonal entry. Defaults to `1e-6`.
+    pub fn with_regularization(mut self, regularization: f64) -> Self {
+        self.regularization = regularization;
+        self
+    }
+
+    /// Seeds the choice of the first mean, for reproducible fits.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Fails if the points differ in dimension, or if `k` is 0 or larger
+    /// than the number of points. An empty input yields an empty model.
+    pub fn fit(&self, points: &[Point]) -> Result<GaussianMixtureModel, ClusterError> {
+        validate_dimensions(points)?;
+        let n = points.len();
+        let k = self.k;
+        if n == 0 {
+            return Ok(GaussianMixtureModel {
+                weights: vec![],
+                means: vec![],
+                variances: vec![],
+                responsibilities: vec![vec![]; n],
+                log_likelihood: 0.0,
+                converged: true,
+                iterations: 0,
+                regularized: false,
+            });
+        }
+        if k == 0 || k > n {
+            return Err(ClusterError::InvalidClusterCount { k, points: n });
+        }
+        let dim = points[0].dim();
+
+        // Seed the means far apart: a random point, then repeatedly the
+        // point farthest from all chosen means.
+        let mut rng = fit_rng(self.seed);
+        let mut means = vec![points[rng.random_range(0..n)].clone()];
+        while means.len() < k {
+            let farthest = points
+                .iter()
+                .max_by(|a, b| {
+                    let nearest =
+                        |p: &Point| means.iter().map(|m| p.distance(m)).fold(f64::MAX, f64::min);
+                    nearest(a).total_cmp(&nearest(b))
+                })
+                .unwrap();
+            means.push(farthest.clone());
+        }
+        // Every component starts with the variance of the whole data set.
+        let overall: Vec<f64> = (0..dim)
+            .map(|d| {
+                let mean = points.iter().map(|p| p.coords[d]).sum::<f64>() / n as f64;
+                let var = points
+                    .iter()
+                    .map(|p| (p.coords[d] - mean).powi(2))
+                    .sum::<f64>()
+                    / n as f64;
+                var + self.regularization
+            })
+            .collect();
+        let mut variances = vec![overall; k];
+        let mut weights = vec![1.0 / k as f64; k];
+
+        let mut responsibilities = vec![vec![0.0; k]; n];
+        let mut log_likelihood = f64::NEG_INFINITY;
+        let mut converged = false;
+        let mut regularized = false;
+        let mut iterations = 0;
+
+        while iterations < self.max_iters {
+            iterations += 1;
+
+            let total = e_step(points, &weights, &means, &variances, &mut responsibilities);
+            let improvement = total - log_likelihood;
+            log_likelihood = total;
+            if improvement.abs() < self.tolerance {
+                converged = true;
+                break;
+            }
+
+            // M-step.
+            for j in 0..k {
+                let nk: f64 = responsibilities.iter().map(|row| row[j]).sum();
+                if nk <= f64::MIN_POSITIVE {
+                    // A component that explains nothing keeps its parameters.
+                    weights[j] = 0.0;
+                    continue;
+                }
+                weights[j] = nk / n as f64;
+                let mean: Vec<f64> = (0..dim)
+                    .map(|d| {
+                        points
+                            .iter()
+                            .zip(&responsibilities)
+                            .map(|(p, row)| row[j] * p.coords[d])
+                            .sum::<f64>()
+                            / nk
+                    })
+                    .collect();
+                for d in 0..dim {
+                    let raw = points
+                        .iter()
+                        .zip(&responsibilities)
+                        .map(|(p, row)| row[j] * (p.coords[d] - mean[d]).powi(2))
+                        .sum::<f64>()
+                        / nk;
+                    if raw < self.regularization {
+                        regularized = true;
                     }
-                } else {
-                    // If a cluster is empty, re-initialize it to a random point
-                    *centroid = points[rng.random_range(0..points.len())].clone();
+                    variances[j][d] = raw + self.regularization;
                 }
+                means[j] = Point::new(mean);
             }
         }
+        if !converged {
+            // The loop ended on an M-step: score the parameters it produced,
+            // so the log-likelihood and responsibilities describe them.
+            log_likelihood = e_step(points, &weights, &means, &variances, &mut responsibilities);
+        }
 
-        assignments
+        Ok(GaussianMixtureModel {
+            weights,
+            means,
+            variances,
+            responsibilities,
+            log_likelihood,
+            converged,
+            iterations,
+            regularized,
+        })
+    }
+}
+
+/// EM expectation step: fills in each point's posterior over the components
+/// and returns the log-likelihood of the data. Works in log space
+/// (log-sum-exp) to avoid underflow.
+fn e_step(
+    points: &[Point],
+    weights: &[f64],
+    means: &[Point],
+    variances: &[Vec<f64>],
+    responsibilities: &mut [Vec<f64>],
+) -> f64 {
+    let mut total = 0.0;
+    for (point, row) in points.iter().zip(responsibilities.iter_mut()) {
+        for (j, r) in row.iter_mut().enumerate() {
+            *r = weights[j].ln() + log_density(point, &means[j], &variances[j]);
+        }
+        let max = row.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let log_sum = max + row.iter().map(|l| (l - max).exp()).sum::<f64>().ln();
+        for r in row.iter_mut() {
+            *r = (*r - log_sum).exp();
+        }
+        total += log_sum;
+    }
+    total
+}
+
+/// Log density of a diagonal-covariance Gaussian at `point`.
+fn log_density(point: &Point, mean: &Point, variances: &[f64]) -> f64 {
+    point
+        .coords
+        .iter()
+        .zip(&mean.coords)
+        .zip(variances)
+        .map(|((x, mu), var)| {
+            -0.5 * ((2.0 * std::f64::consts::PI * var).ln() + (x - mu).powi(2) / var)
+        })
+        .sum()
+}
+
+/// Result of fitting `FuzzyCMeans`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FuzzyCMeansModel {
+    pub centroids: Vec<Point>,
+    /// `memberships[i][j]` is how strongly point `i` belongs to cluster `j`;
+    /// each row sums to 1.
+    pub memberships: Vec<Vec<f64>>,
+}
+
+/// Fuzzy C-Means: a soft K-Means where every point belongs to every cluster
+/// with a weight, `u_ij = 1 / sum_k (d_ij / d_ik)^(2 / (m - 1))`.
+///
+/// The fuzziness `m > 1` controls how soft the split is: values near 1
+/// approach hard K-Means assignments, larger values spread membership out.
+pub struct FuzzyCMeans {
+    c: usize,
+    m: f64,
+    max_iters: usize,
+    seed: Option<u64>,
+}
+
+impl FuzzyCMeans {
+    /// # Panics
+    ///
+    /// Panics if `m <= 1`.
+    pub fn new(c: usize, m: f64, max_iters: usize) -> Self {
+        assert!(m > 1.0, "fuzziness m must be greater than 1");
+        FuzzyCMeans {
+            c,
+            m,
+            max_iters,
+            seed: None,
+        }
+    }
+
+    /// Seeds the random initial memberships, for reproducible fits.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Fails if the points differ in dimension, or if `c` is 0 or larger
+    /// than the number of points. An empty input yields an empty model.
+    pub fn fit(&self, points: &[Point]) -> Result<FuzzyCMeansModel, ClusterError> {
+        validate_dimensions(points)?;
+        let c = self.c;
+        if points.is_empty() {
+            return Ok(FuzzyCMeansModel {
+                centroids: vec![],
+                memberships: vec![],
+            });
+        }
+        if c == 0 || c > points.len() {
+            return Err(ClusterError::InvalidClusterCount {
+                k: c,
+                points: points.len(),
+            });
+        }
+
+        // Start from random memberships, normalised per point.
+        let mut rng = fit_rng(self.seed);
+        let mut memberships: Vec<Vec<f64>> = points
+            .iter()
+            .map(|_| {
+                let row: Vec<f64> = (0..c).map(|_| rng.random_range(0.01..1.0)).collect();
+                let sum: f64 = row.iter().sum();
+                row.into_iter().map(|u| u / sum).collect()
+            })
+            .collect();
+        let mut centroids = Vec::new();
+
+        for _ in 0..self.max_iters {
+            // Each centroid is the mean of all points weighted by u^m.
+            centroids = (0..c)
+                .map(|j| {
+                    let mut coords = vec![0.0; points[0].dim()];
+                    let mut total = 0.0;
+                    for (point, row) in points.iter().zip(&memberships) {
+                        let w = row[j].powf(self.m);
+                        for (acc, x) in coords.iter_mut().zip(&point.coords) {
+                            *acc += w * x;
+                        }
+                        total += w;
+                    }
+                    Point::new(coords.into_iter().map(|x| x / total).collect())
+                })
+                .collect();
+
+            let updated = self.memberships(points, &centroids);
+            let shift = updated
+                .iter()
+                .flatten()
+                .zip(memberships.iter().flatten())
+                .map(|(a, b)| (a - b).abs())
+                .fold(0.0, f64::max);
+            memberships = updated;
+            if shift < 1e-9 {
+                break;
+            }
+        }
+
+        Ok(FuzzyCMeansModel {
+            centroids,
+            memberships,
+        })
+    }
+
+    /// Membership of each point in each of the given centroids.
+    ///
+    /// A point lying exactly on one or more centroids (distance zero, where
+    /// the formula divides by zero) belongs to them in equal shares and to
+    /// no other cluster.
+    pub fn memberships(&self, points: &[Point], centroids: &[Point]) -> Vec<Vec<f64>> {
+        let exponent = 2.0 / (self.m - 1.0);
+        points
+            .iter()
+            .map(|point| {
+                let dists: Vec<f64> = centroids.iter().map(|c| point.distance(c)).collect();
+                let on_centroid = dists.iter().filter(|&&d| d == 0.0).count();
+                if on_centroid > 0 {
+                    return dists
+                        .iter()
+                        .map(|&d| {
+                            if d == 0.0 {
+                                1.0 / on_centroid as f64
+                            } else {
+                                0.0
+                            }
+                        })
+                        .collect();
+                }
+                dists
+                    .iter()
+                    .map(|&d_ij| {
+                        1.0 / dists
+                            .iter()
+                            .map(|&d_ik| (d_ij / d_ik).powf(exponent))
+                            .sum::<f64>()
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// Result of fitting `KMedoids`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KMedoidsModel {
+    /// Indices (into the input points) of the chosen medoids.
+    pub medoids: Vec<usize>,
+    /// Cluster of each point, as an index into `medoids`.
+    pub assignments: Vec<usize>,
+    /// Sum of distances from each point to its medoid.
+    pub cost: f64,
+}
+
+/// K-Medoids clustering using the PAM (Partitioning Around Medoids) algorithm.
+///
+/// Cluster centers are actual data points, and only pairwise distances are
+/// ever used, so any metric works, including ones where a coordinate mean
+/// is meaningless (e.g. cosine distance).
+pub struct KMedoids {
+    k: usize,
+    max_iters: usize,
+    metric: fn(&Point, &Point) -> f64,
+}
+
+impl KMedoids {
+    /// K-Medoids with Euclidean distance.
+    pub fn new(k: usize, max_iters: usize) -> Self {
+        Self::with_metric(k, max_iters, Point::distance)
+    }
+
+    /// K-Medoids with a custom distance function.
+    pub fn with_metric(k: usize, max_iters: usize, metric: fn(&Point, &Point) -> f64) -> Self {
+        KMedoids {
+            k,
+            max_iters,
+            metric,
+        }
+    }
+
+    /// Fails if the points differ in dimension, or if `k` is 0 or larger
+    /// than the number of points. An empty input yields an empty model.
+    pub fn fit(&self, points: &[Point]) -> Result<KMedoidsModel, ClusterError> {
+        validate_dimensions(points)?;
+        let n = points.len();
+        let k = self.k;
+        if n == 0 {
+            return Ok(KMedoidsModel {
+                medoids: vec![],
+                assignments: vec![],
+                cost: 0.0,
+            });
+        }
+        if k == 0 || k > n {
+            return Err(ClusterError::InvalidClusterCount { k, points: n });
+        }
+
+        // PAM looks at every pair many times, so compute them once up front.
+        let dist: Vec<Vec<f64>> = points
+            .iter()
+            .map(|a| points.iter().map(|b| (self.metric)(a, b)).collect())
+            .collect();
+        let total_cost = |medoids: &[usize]| -> f64 {
+            (0..n)
+                .map(|i| medoids.iter().map(|&m| dist[i][m]).fold(f64::MAX, f64::min))
+                .sum()
+        };
+
+        // BUILD: greedily add the point that lowers the total cost the most.
+        let mut medoids: Vec<usize> = Vec::with_capacity(k);
+        while medoids.len() < k {
+            let mut best = (f64::MAX, 0);
+            for candidate in 0..n {
+                if medoids.contains(&candidate) {
+                    continue;
+                }
+                medoids.push(candidate);
+                let cost = total_cost(&medoids);
+                medoids.pop();
+                if cost < best.0 {
+                    best = (cost, candidate);
+                }
+            }
+            medoids.push(best.1);
+        }
+
+        // SWAP: apply the best medoid/non-medoid exchange until none helps.
+        let mut cost = total_cost(&medoids);
+        for _ in 0..self.max_iters {
+            let mut best_swap = None;
+            let mut best_cost = cost;
+            for slot in 0..k {
+                for candidate in 0..n {
+                    if medoids.contains(&candidate) {
+                        continue;
+                    }
+                    let previous = medoids[slot];
+                    medoids[slot] = candidate;
+                    let swapped = total_cost(&medoids);
+                    medoids[slot] = previous;
+                    if swapped < best_cost {
+                        best_cost = swapped;
+                        best_swap = Some((slot, candidate));
+                    }
+                }
+            }
+            match best_swap {
+                Some((slot, candidate)) => {
+                    medoids[slot] = candidate;
+                    cost = best_cost;
+                }
+                None => break,
+            }
+        }
+
+        let assignments = (0..n)
+            .map(|i| {
+                let mut best_cluster = 0;
+                for (j, &m) in medoids.iter().enumerate() {
+                    if dist[i][m] < dist[i][medoids[best_cluster]] {
+                        best_cluster = j;
+                    }
+                }
+                best_cluster
+            })
+            .collect();
+
+        Ok(KMedoidsModel {
+            medoids,
+            assignments,
+            cost,
+        })
     }
 }
 
 /// DBSCAN clustering algorithm.
+///
+/// With the default Euclidean metric, neighbourhoods are found with a k-d
+/// tree built once per `fit`, so typical inputs take about `O(n log n)`
+/// instead of `O(n^2)`. Custom metrics fall back to comparing every pair.
 pub struct DBSCAN {
     epsilon: f64,
     min_points: usize,
+    metric: fn(&Point, &Point) -> f64,
+    /// Whether `metric` is `Point::distance`, which the k-d tree relies on.
+    euclidean: bool,
+    cache_distances: bool,
 }
 
 impl DBSCAN {
+    /// DBSCAN with Euclidean distance.
     pub fn new(epsilon: f64, min_points: usize) -> Self {
+        DBSCAN {
+            euclidean: true,
+            ..Self::with_metric(epsilon, min_points, Point::distance)
+        }
+    }
+
+    /// DBSCAN with a custom (symmetric) distance function.
+    pub fn with_metric(epsilon: f64, min_points: usize, metric: fn(&Point, &Point) -> f64) -> Self {
         DBSCAN {
             epsilon,
             min_points,
+            metric,
+            euclidean: false,
+            cache_distances: false,
         }
     }
 
-    pub fn fit(&self, points: &[Point]) -> Vec<i32> {
+    /// Remembers every computed distance so each pair is evaluated at most
+    /// once (a point's distance to `q` is reused when `q`'s neighbourhood is
+    /// queried). Costs `n * (n + 1) / 2` cached slots; worth it for expensive
+    /// metrics. Labels are identical either way. Has no effect with the
+    /// default Euclidean metric, whose k-d tree skips most pairs anyway.
+    pub fn with_distance_cache(mut self, enabled: bool) -> Self {
+        self.cache_distances = enabled;
+        self
+    }
+
+    pub fn fit(&self, points: &[Point]) -> Result<Vec<i32>, ClusterError> {
+        self.fit_with_index(points, self.euclidean)
+    }
+
+    /// `fit`, using the k-d tree only if `use_index` is set (and the points
+    /// have at least one dimension).
+    fn fit_with_index(&self, points: &[Point], use_index: bool) -> Result<Vec<i32>, ClusterError> {
+        validate_dimensions(points)?;
         let n = points.len();
         let mut labels = vec![-2; n]; // -2 undefined
         let mut current_c = -1;
+        let mut search = if use_index && points.first().is_some_and(|p| p.dim() > 0) {
+            NeighborSearch::KdTree(KdTree::new(points))
+        } else {
+            NeighborSearch::BruteForce(PairDistances::new(n, self.cache_distances))
+        };
 
         for i in 0..n {
             if labels[i] != -2 {
                 continue;
             }
-            let neighbors = self.region_query(points, i);
+            let neighbors = self.region_query(points, i, &mut search);
             if neighbors.len() < self.min_points {
                 labels[i] = -1; // Noise
             } else {
                 current_c += 1;
-                self.expand(points, &mut labels, i, neighbors, current_c);
+                self.expand(points, &mut labels, i, neighbors, current_c, &mut search);
             }
         }
 
-        labels
+        Ok(labels)
     }
 
     fn expand(
@@ -172,6 +1212,7 @@ impl DBSCAN {
         root: usize,
         mut neighbors: Vec<usize>,
         c: i32,
+        search: &mut NeighborSearch,
     ) {
         labels[root] = c;
 
@@ -182,7 +1223,7 @@ impl DBSCAN {
                 labels[neighbor_idx] = c; // Change noise to border point
             } else if labels[neighbor_idx] == -2 {
                 labels[neighbor_idx] = c;
-                let new_neighbors = self.region_query(points, neighbor_idx);
+                let new_neighbors = self.region_query(points, neighbor_idx, search);
                 if new_neighbors.len() >= self.min_points {
                     neighbors.extend(new_neighbors);
                 }
@@ -191,13 +1232,135 @@ impl DBSCAN {
         }
     }
 
-    fn region_query(&self, points: &[Point], idx: usize) -> Vec<usize> {
-        points
-            .iter()
-            .enumerate()
-            .filter(|(_, p)| points[idx].distance(p) <= self.epsilon)
-            .map(|(i, _)| i)
-            .collect()
+    /// Indices of all points within `epsilon` of `points[idx]` (itself
+    /// included), in increasing order.
+    fn region_query(
+        &self,
+        points: &[Point],
+        idx: usize,
+        search: &mut NeighborSearch,
+    ) -> Vec<usize> {
+        match search {
+            NeighborSearch::BruteForce(distances) => (0..points.len())
+                .filter(|&j| distances.get(points, self.metric, idx, j) <= self.epsilon)
+                .collect(),
+            NeighborSearch::KdTree(tree) => {
+                let mut found = Vec::new();
+                tree.within(
+                    &points[idx],
+                    self.epsilon,
+                    0,
+                    tree.order.len(),
+                    0,
+                    &mut found,
+                );
+                // Same order as the brute-force scan, so labels match exactly.
+                found.sort_unstable();
+                found
+            }
+        }
+    }
+}
+
+/// How `DBSCAN` finds each point's neighbourhood during one `fit`.
+enum NeighborSearch<'a> {
+    BruteForce(PairDistances),
+    KdTree(KdTree<'a>),
+}
+
+/// A k-d tree over a point set, stored implicitly: `order[lo..hi]` is a
+/// subtree whose root is its middle element, split on axis `depth % dim`,
+/// with no larger coordinate on that axis to its left and no smaller one to
+/// its right.
+struct KdTree<'a> {
+    points: &'a [Point],
+    order: Vec<usize>,
+}
+
+impl<'a> KdTree<'a> {
+    /// Builds the tree; the points must share a dimension of at least 1.
+    fn new(points: &'a [Point]) -> Self {
+        let mut tree = KdTree {
+            points,
+            order: (0..points.len()).collect(),
+        };
+        tree.build(0, points.len(), 0);
+        tree
+    }
+
+    fn build(&mut self, lo: usize, hi: usize, depth: usize) {
+        if hi - lo <= 1 {
+            return;
+        }
+        let axis = depth % self.points[0].dim();
+        let mid = (lo + hi) / 2;
+        let points = self.points;
+        self.order[lo..hi].select_nth_unstable_by(mid - lo, |&a, &b| {
+            points[a].coords[axis].total_cmp(&points[b].coords[axis])
+        });
+        self.build(lo, mid, depth + 1);
+        self.build(mid + 1, hi, depth + 1);
+    }
+
+    /// Pushes every point of the subtree `order[lo..hi]` within Euclidean
+    /// distance `radius` of `query` onto `found`.
+    ///
+    /// A subtree is skipped only when the gap along the split axis alone
+    /// exceeds `radius`; the full distance is at least that gap, so exactly
+    /// the points a brute-force `distance <= radius` scan accepts are found.
+    fn within(
+        &self,
+        query: &Point,
+        radius: f64,
+        lo: usize,
+        hi: usize,
+        depth: usize,
+        found: &mut Vec<usize>,
+    ) {
+        if lo >= hi {
+            return;
+        }
+        let axis = depth % query.dim();
+        let mid = (lo + hi) / 2;
+        let split = &self.points[self.order[mid]];
+        if query.distance(split) <= radius {
+            found.push(self.order[mid]);
+        }
+        let gap = query.coords[axis] - split.coords[axis];
+        if gap <= radius {
+            self.within(query, radius, lo, mid, depth + 1, found);
+        }
+        if -gap <= radius {
+            self.within(query, radius, mid + 1, hi, depth + 1, found);
+        }
+    }
+}
+
+/// Pairwise distances for one `fit`, optionally memoised in a lazily filled
+/// lower-triangular table (the metric is assumed symmetric).
+struct PairDistances {
+    cache: Option<Vec<Option<f64>>>,
+}
+
+impl PairDistances {
+    fn new(n: usize, enabled: bool) -> Self {
+        PairDistances {
+            cache: enabled.then(|| vec![None; n * (n + 1) / 2]),
+        }
+    }
+
+    fn get(
+        &mut self,
+        points: &[Point],
+        metric: fn(&Point, &Point) -> f64,
+        i: usize,
+        j: usize,
+    ) -> f64 {
+        let Some(cache) = &mut self.cache else {
+            return metric(&points[i], &points[j]);
+        };
+        let (lo, hi) = if i <= j { (i, j) } else { (j, i) };
+        *cache[hi * (hi + 1) / 2 + lo].get_or_insert_with(|| metric(&points[lo], &points[hi]))
     }
 }
 
@@ -215,7 +1378,7 @@ mod tests {
         ];
 
         let kmeans = KMeans::new(2, 100);
-        let assignments = kmeans.fit(&points);
+        let assignments = kmeans.fit(&points).unwrap();
 
         assert_eq!(assignments.len(), 4);
         // Should be 2 clusters. Points 0,1 should be same, 2,3 same.
@@ -224,6 +1387,119 @@ mod tests {
         assert_ne!(assignments[0], assignments[2]);
     }
 
+    #[test]
+    fn test_kmeans_model_centroids_and_inertia() {
+        let points = vec![
+            Point::new(vec![0.0, 0.0]),
+            Point::new(vec![0.0, 0.002]),
+            Point::new(vec![0.002, 0.0]),
+            Point::new(vec![10.0, 10.0]),
+            Point::new(vec![10.002, 10.002]),
+        ];
+        let model = KMeans::new(2, 100).fit_model(&points).unwrap();
+
+        assert!(model.inertia < 1e-4, "inertia {}", model.inertia);
+        let a = &model.centroids[model.assignments[0]];
+        let b = &model.centroids[model.assignments[3]];
+        assert!(a.approx_eq(&Point::new(vec![0.002 / 3.0, 0.002 / 3.0]), 1e-9));
+        assert!(b.approx_eq(&Point::new(vec![10.001, 10.001]), 1e-9));
+    }
+
+    #[test]
+    fn test_kmeans_predict() {
+        let points = vec![
+            Point::new(vec![0.0, 0.0]),
+            Point::new(vec![0.0, 1.0]),
+            Point::new(vec![20.0, 0.0]),
+            Point::new(vec![20.0, 1.0]),
+        ];
+        let model = KMeans::new(2, 100).fit_model(&points).unwrap();
+
+        let cluster_b = model.assignments[2];
+        assert_eq!(model.predict(&Point::new(vec![19.0, 0.7])), cluster_b);
+        assert_eq!(
+            model.predict(&Point::new(vec![1.0, 0.2])),
+            model.assignments[0]
+        );
+        // Halfway between the two centroids.
+        assert_eq!(model.predict(&Point::new(vec![10.0, 0.5])), 0);
+    }
+
+    #[test]
+    fn test_seeded_fits_are_reproducible() {
+        let points: Vec<Point> = (0..60)
+            .map(|i| {
+                let t = i as f64;
+                Point::new(vec![(t * 0.37).sin() * 5.0, (t * 0.91).cos() * 5.0])
+            })
+            .collect();
+
+        for kmeans in [|| KMeans::new(4, 100), || KMeans::with_random_init(4, 100)] {
+            let first = kmeans().with_seed(11).fit_model(&points).unwrap();
+            let second = kmeans().with_seed(11).fit_model(&points).unwrap();
+            assert_eq!(first.assignments, second.assignments);
+            assert_eq!(first, second);
+        }
+
+        let fuzzy = || {
+            FuzzyCMeans::new(3, 2.0, 50)
+                .with_seed(5)
+                .fit(&points)
+                .unwrap()
+        };
+        assert_eq!(fuzzy(), fuzzy());
+        let gmm = || {
+            GaussianMixture::new(3, 50)
+                .with_seed(5)
+                .fit(&points)
+                .unwrap()
+        };
+        assert_eq!(gmm(), gmm());
+    }
+
+    /// Three tight 10-point clusters, far apart.
+    fn three_blobs() -> Vec<Point> {
+        let centers = [(0.0, 0.0), (100.0, 0.0), (0.0, 100.0)];
+        centers
+            .iter()
+            .flat_map(|&(x, y)| {
+                (0..10).map(move |i| {
+                    let t = i as f64;
+                    Point::new(vec![x + (t * 0.7).sin(), y + (t * 1.3).cos()])
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_kmeans_plus_plus_beats_random_init() {
+        let points = three_blobs();
+        let truth: Vec<usize> = (0..30).map(|i| i / 10).collect();
+        let successes = |kmeans: fn(usize, usize) -> KMeans| {
+            (0..200)
+                .filter(|&seed| {
+                    let labels = kmeans(3, 100).with_seed(seed).fit(&points).unwrap();
+                    clusterings_equivalent(&labels, &truth)
+                })
+                .count()
+        };
+
+        let plus_plus = successes(KMeans::new);
+        let random = successes(KMeans::with_random_init);
+        // Uniform seeds land one per cluster only a quarter of the time.
+        // Lloyd iterations rescue many of the rest, but not all: expect ~70%.
+        assert!(
+            plus_plus >= 195,
+            "k-means++ found the clusters {}/200 times",
+            plus_plus
+        );
+        assert!(
+            random < 180,
+            "random init found the clusters {}/200 times",
+            random
+        );
+    }
+
     #[test]
     fn test_dbscan_simple() {
         // Cluster 1: (0,0), (0,1), (1,0), (1,1) -> dense square
@@ -247,7 +1523,7 @@ mod tests {
         // (5,5) neighbors: self -> count 1. Noise.
 
         let dbscan = DBSCAN::new(1.5, 3);
-        let labels = dbscan.fit(&points);
+        let labels = dbscan.fit(&points).unwrap();
 
         assert_eq!(labels[0], labels[1]);
         assert_eq!(labels[0], labels[2]);
@@ -261,4 +1537,469 @@ mod tests {
 
         assert_ne!(labels[0], labels[5]);
     }
+
+    #[test]
+    fn test_dimension_mismatch() {
+        let a = Point::new(vec![0.0, 0.0]);
+        let b = Point::new(vec![3.0, 4.0, 12.0]);
+        let mismatch = ClusterError::DimensionMismatch {
+            expected: 2,
+            found: 3,
+        };
+
+        assert_eq!(a.try_distance(&b), Err(mismatch.clone()));
+        assert_eq!(a.try_distance(&Point::new(vec![3.0, 4.0])), Ok(5.0));
+
+        let points = vec![a, b];
+        assert_eq!(KMeans::new(1, 10).fit(&points), Err(mismatch.clone()));
+        assert_eq!(DBSCAN::new(1.0, 1).fit(&points), Err(mismatch));
+    }
+
+    #[test]
+    fn test_kmeans_invalid_cluster_count() {
+        let points = vec![Point::new(vec![0.0]), Point::new(vec![1.0])];
+        for k in [0, 3] {
+            let expected = Err(ClusterError::InvalidClusterCount { k, points: 2 });
+            assert_eq!(KMeans::new(k, 10).fit(&points), expected);
+            assert_eq!(KMeans::with_random_init(k, 10).fit(&points), expected);
+        }
+        assert_eq!(KMeans::new(2, 10).fit(&points).map(|a| a.len()), Ok(2));
+        assert_eq!(KMeans::new(3, 10).fit(&[]), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_gmm_invalid_cluster_count() {
+        let points = vec![Point::new(vec![0.0]), Point::new(vec![1.0])];
+        for k in [0, 3] {
+            let expected = ClusterError::InvalidClusterCount { k, points: 2 };
+            assert_eq!(
+                GaussianMixture::new(k, 10).fit(&points).err(),
+                Some(expected)
+            );
+        }
+        assert!(GaussianMixture::new(2, 10).fit(&points).is_ok());
+        let empty = GaussianMixture::new(3, 10).fit(&[]).unwrap();
+        assert!(empty.means.is_empty());
+    }
+
+    #[test]
+    fn test_fuzzy_cmeans_invalid_cluster_count() {
+        let points = vec![Point::new(vec![0.0]), Point::new(vec![1.0])];
+        for c in [0, 3] {
+            let expected = ClusterError::InvalidClusterCount { k: c, points: 2 };
+            assert_eq!(
+                FuzzyCMeans::new(c, 2.0, 10).fit(&points).err(),
+                Some(expected)
+            );
+        }
+        assert!(FuzzyCMeans::new(2, 2.0, 10).fit(&points).is_ok());
+        let empty = FuzzyCMeans::new(3, 2.0, 10).fit(&[]).unwrap();
+        assert!(empty.centroids.is_empty() && empty.memberships.is_empty());
+    }
+
+    #[test]
+    fn test_kmedoids_invalid_cluster_count() {
+        let points = vec![Point::new(vec![0.0]), Point::new(vec![1.0])];
+        for k in [0, 3] {
+            let expected = ClusterError::InvalidClusterCount { k, points: 2 };
+            assert_eq!(KMedoids::new(k, 10).fit(&points).err(), Some(expected));
+        }
+        assert!(KMedoids::new(2, 10).fit(&points).is_ok());
+        let empty = KMedoids::new(3, 10).fit(&[]).unwrap();
+        assert!(empty.medoids.is_empty() && empty.assignments.is_empty());
+    }
+
+    #[test]
+    fn test_kmeans_reinitializes_empty_clusters() {
+        let points = vec![
+            Point::new(vec![0.0, 0.0]),
+            Point::new(vec![2.0, 0.0]),
+            Point::new(vec![7.0, 7.0]),
+        ];
+        let mut centroids = vec![Point::new(vec![1.0, 1.0]), Point::new(vec![-50.0, -50.0])];
+        let mut rng = StdRng::seed_from_u64(1);
+
+        // Every point is in cluster 0, so cluster 1 is empty.
+        update_centroids(&points, &[0, 0, 0], &mut centroids, &mut rng);
+        assert!(centroids[0].approx_eq(&Point::new(vec![3.0, 7.0 / 3.0]), 1e-12));
+        assert!(points.contains(&centroids[1]), "{:?}", centroids[1]);
+    }
+
+    #[test]
+    fn test_mini_batch_kmeans_matches_full_kmeans() {
+        let mut rng = StdRng::seed_from_u64(8);
+        let centers = [(0.0, 0.0), (25.0, 0.0), (0.0, 25.0), (25.0, 25.0)];
+        let points: Vec<Point> = (0..10_000)
+            .map(|i| {
+                let (x, y) = centers[i % centers.len()];
+                Point::new(vec![
+                    x + rng.random_range(-4.0..4.0),
+                    y + rng.random_range(-4.0..4.0),
+                ])
+            })
+            .collect();
+
+        let full = KMeans::new(4, 100).with_seed(1).fit_model(&points).unwrap();
+        let mini = MiniBatchKMeans::new(4, 100, 50)
+            .with_seed(1)
+            .fit_model(&points)
+            .unwrap();
+
+        assert!(clusterings_equivalent(&mini.assignments, &full.assignments));
+        for centroid in &mini.centroids {
+            let nearest = &full.centroids[full.predict(centroid)];
+            assert!(
+                centroid.distance(nearest) < 0.5,
+                "{:?} vs {:?}",
+                centroid,
+                nearest
+            );
+        }
+        assert!(mini.inertia < full.inertia * 1.01);
+    }
+
+    #[test]
+    fn test_dbscan_with_manhattan_metric() {
+        // Diagonal neighbours are 1.41 apart in Euclidean distance but 2
+        // apart in Manhattan distance; axis neighbours are 1.5 apart in both.
+        let points = vec![
+            Point::new(vec![0.0, 0.0]),
+            Point::new(vec![1.0, 1.0]),
+            Point::new(vec![2.0, 2.0]),
+            Point::new(vec![10.0, 0.0]),
+            Point::new(vec![11.5, 0.0]),
+            Point::new(vec![13.0, 0.0]),
+        ];
+
+        let euclidean = DBSCAN::new(1.6, 2).fit(&points).unwrap();
+        assert_eq!(euclidean, vec![0, 0, 0, 1, 1, 1]);
+        let manhattan = DBSCAN::with_metric(1.6, 2, Point::manhattan_distance)
+            .fit(&points)
+            .unwrap();
+        assert_eq!(manhattan, vec![-1, -1, -1, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_dbscan_kd_tree_matches_brute_force() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let centers = [(0.0, 0.0), (30.0, 5.0), (10.0, 40.0), (45.0, 45.0)];
+        let mut points: Vec<Point> = (0..4600)
+            .map(|i| {
+                let (x, y) = centers[i % centers.len()];
+                Point::new(vec![
+                    x + rng.random_range(-6.0..6.0),
+                    y + rng.random_range(-6.0..6.0),
+                ])
+            })
+            .collect();
+        // Background noise, plus exact duplicates to exercise ties.
+        points.extend((0..300).map(|_| {
+            Point::new(vec![
+                rng.random_range(-20.0..70.0),
+                rng.random_range(-20.0..70.0),
+            ])
+        }));
+        points.extend(points[..100].to_vec());
+
+        // The brute-force path is slow in debug builds, so one setting only.
+        let dbscan = DBSCAN::new(0.6, 5);
+        let indexed = dbscan.fit(&points).unwrap();
+        let brute = dbscan.fit_with_index(&points, false).unwrap();
+        assert_eq!(indexed, brute);
+        assert!(indexed.contains(&-1));
+        assert!(indexed.iter().any(|&l| l >= 3));
+    }
+
+    #[test]
+    fn test_silhouette_score() {
+        let points = vec![
+            Point::new(vec![0.0, 0.0]),
+            Point::new(vec![0.0, 1.0]),
+            Point::new(vec![1.0, 0.0]),
+            Point::new(vec![50.0, 50.0]),
+            Point::new(vec![50.0, 51.0]),
+            Point::new(vec![51.0, 50.0]),
+        ];
+        let good = silhouette_score(&points, &[0usize, 0, 0, 1, 1, 1]);
+        let poor = silhouette_score(&points, &[0usize, 1, 0, 1, 0, 1]);
+        assert!(good > 0.95, "good clustering scored {}", good);
+        assert!(poor < 0.1, "poor clustering scored {}", poor);
+
+        // Noise is ignored; a singleton contributes 0.
+        let mut with_noise = points.clone();
+        with_noise.push(Point::new(vec![-100.0, 300.0]));
+        let noisy = silhouette_score(&with_noise, &[0, 0, 0, 1, 1, 1, -1]);
+        assert!((noisy - good).abs() < 1e-12);
+        let singleton = silhouette_score(&with_noise, &[0usize, 0, 0, 1, 1, 1, 2]);
+        assert!(singleton < good);
+        assert_eq!(silhouette_score(&points, &[0usize; 6]), 0.0);
+    }
+
+    #[test]
+    fn test_cosine_distance_zero_vector() {
+        let zero = Point::new(vec![0.0, 0.0]);
+        let x = Point::new(vec![3.0, 0.0]);
+        assert_eq!(zero.cosine_distance(&x), 2.0);
+        assert_eq!(x.cosine_distance(&zero), 2.0);
+        assert_eq!(zero.cosine_distance(&zero), 0.0);
+        assert_eq!(x.cosine_distance(&Point::new(vec![0.5, 0.0])), 0.0);
+        assert_eq!(x.cosine_distance(&Point::new(vec![-1.0, 0.0])), 2.0);
+    }
+
+    #[test]
+    fn test_kmeans_with_cosine_metric() {
+        // Two directions, each with points at very different magnitudes.
+        let points = vec![
+            Point::new(vec![1.0, 0.0]),
+            Point::new(vec![4.0, 0.1]),
+            Point::new(vec![9.0, 0.0]),
+            Point::new(vec![20.0, 0.3]),
+            Point::new(vec![0.0, 1.0]),
+            Point::new(vec![0.1, 5.0]),
+            Point::new(vec![0.0, 12.0]),
+            Point::new(vec![0.2, 25.0]),
+        ];
+
+        // Euclidean K-Means splits off the far points by magnitude...
+        let euclidean = KMeans::new(2, 100).with_seed(3).fit(&points).unwrap();
+        assert_eq!(euclidean[0], euclidean[4]);
+
+        // ...while under cosine distance only the direction matters.
+        let kmeans = KMeans::with_metric(2, 100, Point::cosine_distance).with_seed(3);
+        let model = kmeans.fit_model(&points).unwrap();
+        let a = model.assignments[0];
+        assert!(model.assignments[..4].iter().all(|&c| c == a));
+        assert!(model.assignments[4..].iter().all(|&c| c != a));
+        // Centroids are medoids: real points from their own group.
+        assert!(points[..4].contains(&model.centroids[a]));
+        assert!(points[4..].contains(&model.centroids[1 - a]));
+        let probe = Point::new(vec![3.0, 100.0]);
+        assert_eq!(model.predict(&probe), 1 - a);
+    }
+
+    #[test]
+    fn test_kmedoids_with_cosine_metric() {
+        // Two directions, each with points at very different magnitudes.
+        // Under cosine distance only the direction matters.
+        let points = vec![
+            Point::new(vec![1.0, 0.0]),
+            Point::new(vec![4.0, 0.1]),
+            Point::new(vec![9.0, 0.0]),
+            Point::new(vec![20.0, 0.3]),
+            Point::new(vec![0.0, 1.0]),
+            Point::new(vec![0.1, 5.0]),
+            Point::new(vec![0.0, 12.0]),
+            Point::new(vec![0.2, 25.0]),
+        ];
+
+        let model = KMedoids::with_metric(2, 100, Point::cosine_distance)
+            .fit(&points)
+            .unwrap();
+
+        assert_eq!(model.medoids.len(), 2);
+        let a = model.assignments[0];
+        assert!(model.assignments[..4].iter().all(|&c| c == a));
+        assert!(model.assignments[4..].iter().all(|&c| c != a));
+        // Each medoid is a real point from its own direction group.
+        assert!(model.medoids[a] < 4);
+        assert!(model.medoids[1 - a] >= 4);
+
+        // KMeans averages coordinates, so under cosine distance its clusters
+        // can be no better than the medoid clustering.
+        let kmeans_labels = KMeans::new(2, 100).fit(&points).unwrap();
+        let kmeans_cost: f64 = (0..2)
+            .map(|c| {
+                let members: Vec<&Point> = points
+                    .iter()
+                    .zip(&kmeans_labels)
+                    .filter(|(_, l)| **l == c)
+                    .map(|(p, _)| p)
+                    .collect();
+                members
+                    .iter()
+                    .map(|m| members.iter().map(|p| p.cosine_distance(m)).sum::<f64>())
+                    .fold(f64::MAX, f64::min)
+            })
+            .filter(|c| *c != f64::MAX)
+            .sum();
+        assert!(model.cost <= kmeans_cost + 1e-12);
+        assert!(model.cost < 0.01);
+    }
+
+    #[test]
+    fn test_kmedoids_euclidean_default() {
+        let points = vec![
+            Point::new(vec![0.0, 0.0]),
+            Point::new(vec![0.1, 0.1]),
+            Point::new(vec![0.2, 0.0]),
+            Point::new(vec![10.0, 10.0]),
+            Point::new(vec![10.1, 10.1]),
+        ];
+        let model = KMedoids::new(2, 100).fit(&points).unwrap();
+
+        assert_eq!(model.assignments[0], model.assignments[1]);
+        assert_eq!(model.assignments[0], model.assignments[2]);
+        assert_eq!(model.assignments[3], model.assignments[4]);
+        assert_ne!(model.assignments[0], model.assignments[3]);
+        // The middle point of the tight group is its most central member.
+        assert!(model.medoids.contains(&1));
+    }
+
+    thread_local! {
+        static DISTANCE_CALLS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+    }
+
+    fn counted_distance(a: &Point, b: &Point) -> f64 {
+        DISTANCE_CALLS.with(|c| c.set(c.get() + 1));
+        a.distance(b)
+    }
+
+    #[test]
+    fn test_dbscan_distance_cache() {
+        let mut points = Vec::new();
+        for i in 0..30 {
+            let x = (i % 10) as f64 * 0.4 + if i < 10 { 0.0 } else { 20.0 };
+            let y = (i / 10) as f64 * 0.3;
+            points.push(Point::new(vec![x, y]));
+        }
+        points.push(Point::new(vec![10.0, 10.0])); // Noise
+        let n = points.len();
+
+        let uncached = DBSCAN::with_metric(0.5, 3, counted_distance);
+        DISTANCE_CALLS.with(|c| c.set(0));
+        let expected = uncached.fit(&points).unwrap();
+        // Every point's neighbourhood is queried exactly once.
+        assert_eq!(DISTANCE_CALLS.with(|c| c.get()), n * n);
+
+        let cached = DBSCAN::with_metric(0.5, 3, counted_distance).with_distance_cache(true);
+        DISTANCE_CALLS.with(|c| c.set(0));
+        assert_eq!(cached.fit(&points).unwrap(), expected);
+        assert_eq!(DISTANCE_CALLS.with(|c| c.get()), n * (n + 1) / 2);
+        assert_eq!(DBSCAN::new(0.5, 3).fit(&points).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_fuzzy_cmeans_memberships() {
+        let fcm = FuzzyCMeans::new(2, 2.0, 100);
+        let centroids = vec![Point::new(vec![0.0, 0.0]), Point::new(vec![4.0, 0.0])];
+        let points = vec![
+            Point::new(vec![2.0, 1.0]), // Midway
+            Point::new(vec![1.0, 0.0]),
+            Point::new(vec![4.0, 0.0]), // On a centroid
+        ];
+
+        let u = fcm.memberships(&points, &centroids);
+        assert!((u[0][0] - 0.5).abs() < 1e-12 && (u[0][1] - 0.5).abs() < 1e-12);
+        // Distances 1 and 3: u = 1 / (1 + (1/3)^2) = 0.9.
+        assert!((u[1][0] - 0.9).abs() < 1e-12);
+        assert_eq!(u[2], vec![0.0, 1.0]);
+
+        let blobs = vec![
+            Point::new(vec![0.0, 0.0]),
+            Point::new(vec![0.2, 0.1]),
+            Point::new(vec![10.0, 10.0]),
+            Point::new(vec![10.1, 9.9]),
+        ];
+        let model = fcm.fit(&blobs).unwrap();
+        for row in &model.memberships {
+            assert!((row.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+            assert!(row.iter().any(|&u| u > 0.95));
+        }
+        let cluster = |i: usize| if model.memberships[i][0] > 0.5 { 0 } else { 1 };
+        assert_eq!(cluster(0), cluster(1));
+        assert_ne!(cluster(0), cluster(2));
+    }
+
+    #[test]
+    fn test_gmm_regularizes_collapsed_components() {
+        // Two stacks of identical points: each component collapses onto one.
+        let mut points = vec![Point::new(vec![0.0, 0.0]); 5];
+        points.extend(vec![Point::new(vec![10.0, 10.0]); 5]);
+
+        let model = GaussianMixture::new(2, 100).fit(&points).unwrap();
+
+        assert!(model.regularized);
+        assert!(model.log_likelihood.is_finite());
+        assert!(
+            model
+                .variances
+                .iter()
+                .flatten()
+                .all(|v| v.is_finite() && *v > 0.0)
+        );
+        assert!(model.responsibilities.iter().flatten().all(|r| !r.is_nan()));
+        let labels = model.assignments();
+        assert!(labels[..5].iter().all(|&l| l == labels[0]));
+        assert!(labels[5..].iter().all(|&l| l == labels[5]));
+        assert_ne!(labels[0], labels[5]);
+    }
+
+    #[test]
+    fn test_gmm_reports_convergence() {
+        let points: Vec<Point> = (0..40)
+            .map(|i| {
+                let jitter = ((i * 37) % 11) as f64 / 10.0;
+                let center = if i % 2 == 0 { 0.0 } else { 8.0 };
+                Point::new(vec![center + jitter, center - jitter])
+            })
+            .collect();
+
+        let model = GaussianMixture::new(2, 200).fit(&points).unwrap();
+        assert!(model.converged);
+        assert!(!model.regularized);
+        assert!((model.weights.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+
+        // One EM step can't converge; the partial result is still usable.
+        let partial = GaussianMixture::new(2, 1).fit(&points).unwrap();
+        assert!(!partial.converged);
+        assert_eq!(partial.iterations, 1);
+        assert!(partial.log_likelihood <= model.log_likelihood + 1e-9);
+    }
+
+    #[test]
+    fn test_gmm_partial_fit_is_self_consistent() {
+        let points: Vec<Point> = (0..30)
+            .map(|i| Point::new(vec![(i % 3) as f64 * 5.0 + (i as f64) / 30.0]))
+            .collect();
+        let model = GaussianMixture::new(3, 1)
+            .with_seed(7)
+            .fit(&points)
+            .unwrap();
+        assert!(!model.converged);
+
+        // Re-scoring the returned parameters reproduces the returned
+        // log-likelihood and responsibilities.
+        let mut responsibilities = vec![vec![0.0; 3]; points.len()];
+        let log_likelihood = e_step(
+            &points,
+            &model.weights,
+            &model.means,
+            &model.variances,
+            &mut responsibilities,
+        );
+        assert!((log_likelihood - model.log_likelihood).abs() < 1e-9);
+        for (expected, actual) in responsibilities.iter().zip(&model.responsibilities) {
+            for (e, a) in expected.iter().zip(actual) {
+                assert!((e - a).abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn test_clusterings_equivalent_up_to_relabeling() {
+        let a = [0, 0, 1, 1, 2, 2];
+        let relabeled = [2, 2, 0, 0, 1, 1];
+        assert!(clusterings_equivalent(&a, &relabeled));
+
+        // Merges two clusters: no bijection exists in either direction.
+        let merged = [0, 0, 0, 0, 1, 1];
+        assert!(!clusterings_equivalent(&a, &merged));
+        assert!(!clusterings_equivalent(&merged, &a));
+        assert!(!clusterings_equivalent(&a, &a[..5]));
+
+        let p = Point::new(vec![1.0, 2.0]);
+        assert!(p.approx_eq(&Point::new(vec![1.0005, 1.9995]), 1e-3));
+        assert!(!p.approx_eq(&Point::new(vec![1.01, 2.0]), 1e-3));
+        assert!(!p.approx_eq(&Point::new(vec![1.0]), 1e-3));
+    }
 }
@@ -1,4 +1,6 @@
 use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, Write};
 
 /// A literal is a variable ID and a boolean indicating if it's negated.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -29,12 +31,61 @@ pub struct SatSolver {
     num_vars: usize,
 }
 
+/// Errors from `SatSolver::from_dimacs`. Line numbers are 1-based.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParseError {
+    /// A clause appeared before any `p cnf` header, or there was no header.
+    MissingHeader,
+    /// The `p` line isn't `p cnf <variables> <clauses>`, or is repeated.
+    InvalidHeader { line: usize },
+    /// A clause token that isn't an integer.
+    InvalidLiteral { line: usize, token: String },
+    /// A literal whose variable exceeds the count declared in the header.
+    VariableOutOfRange { line: usize, var: usize },
+    /// The input ended inside a clause (no terminating `0`).
+    UnterminatedClause,
+    /// The number of clauses differs from the header's.
+    ClauseCountMismatch { expected: usize, found: usize },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::MissingHeader => write!(f, "missing `p cnf` header"),
+            ParseError::InvalidHeader { line } => write!(f, "line {}: invalid header", line),
+            ParseError::InvalidLiteral { line, token } => {
+                write!(f, "line {}: invalid literal `{}`", line, token)
+            }
+            ParseError::VariableOutOfRange { line, var } => {
+                write!(f, "line {}: variable {} out of range", line, var)
+            }
+            ParseError::UnterminatedClause => write!(f, "last clause is missing its `0`"),
+            ParseError::ClauseCountMismatch { expected, found } => {
+                write!(f, "header declares {} clauses, found {}", expected, found)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum Solution {
     Satisfiable(HashMap<usize, bool>),
     Unsatisfiable,
 }
 
+/// Counters collected during a search.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SolveStats {
+    /// Variables branched on.
+    pub decisions: u64,
+    /// Literals assigned because a clause became unit.
+    pub unit_propagations: u64,
+    /// Partial assignments found to falsify a clause.
+    pub conflicts: u64,
+}
+
 impl SatSolver {
     pub fn new(num_vars: usize) -> Self {
         SatSolver {
@@ -47,106 +98,697 @@ impl SatSolver {
         self.clauses.push(clause);
     }
 
+    /// Requires at most one of `lits` to be true, using the pairwise
+    /// encoding: a clause `!a OR !b` for every pair. That is quadratic in
+    /// `lits.len()` but needs no auxiliary variables.
+    pub fn add_at_most_one(&mut self, lits: &[Literal]) {
+        for (i, a) in lits.iter().enumerate() {
+            for b in &lits[i + 1..] {
+                self.add_clause(vec![a.not(), b.not()]);
+            }
+        }
+    }
+
+    /// Requires exactly one of `lits` to be true: one clause for "at least
+    /// one", plus `add_at_most_one`.
+    pub fn add_exactly_one(&mut self, lits: &[Literal]) {
+        self.add_clause(lits.to_vec());
+        self.add_at_most_one(lits);
+    }
+
+    /// Reads a formula in DIMACS CNF format: `c` comment lines, a
+    /// `p cnf <variables> <clauses>` header, then clauses as signed variable
+    /// numbers each terminated by `0` (`-3` is `Literal::new(3, true)`).
+    /// Clauses may span lines; a `%` line ends the input, as in the SATLIB
+    /// benchmark files.
+    pub fn from_dimacs(input: &str) -> Result<SatSolver, ParseError> {
+        let mut solver: Option<SatSolver> = None;
+        let mut expected_clauses = 0;
+        let mut clause = Vec::new();
+
+        for (i, line) in input.lines().enumerate() {
+            let line_no = i + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('c') {
+                continue;
+            }
+            if line.starts_with('%') {
+                break;
+            }
+            if line.starts_with('p') {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                let counts = match fields[..] {
+                    ["p", "cnf", vars, clauses] => vars.parse().ok().zip(clauses.parse().ok()),
+                    _ => None,
+                };
+                match (counts, &solver) {
+                    (Some((vars, clauses)), None) => {
+                        solver = Some(SatSolver::new(vars));
+                        expected_clauses = clauses;
+                    }
+                    _ => return Err(ParseError::InvalidHeader { line: line_no }),
+                }
+                continue;
+            }
+
+            let Some(solver) = solver.as_mut() else {
+                return Err(ParseError::MissingHeader);
+            };
+            for token in line.split_whitespace() {
+                let value: i64 = token.parse().map_err(|_| ParseError::InvalidLiteral {
+                    line: line_no,
+                    token: token.to_string(),
+                })?;
+                if value == 0 {
+                    solver.add_clause(std::mem::take(&mut clause));
+                    continue;
+                }
+                let var = value.unsigned_abs() as usize;
+                if var > solver.num_vars {
+                    return Err(ParseError::VariableOutOfRange { line: line_no, var });
+                }
+                clause.push(Literal::new(var, value < 0));
+            }
+        }
+
+        let solver = solver.ok_or(ParseError::MissingHeader)?;
+        if !clause.is_empty() {
+            return Err(ParseError::UnterminatedClause);
+        }
+        if solver.clauses.len() != expected_clauses {
+            return Err(ParseError::ClauseCountMismatch {
+                expected: expected_clauses,
+                found: solver.clauses.len(),
+            });
+        }
+        Ok(solver)
+    }
+
     pub fn solve(&self) -> Solution {
-        self.dpll_solve(self.clauses.clone(), HashMap::new())
+        self.solve_with_stats().0
     }
 
-    fn dpll_solve(
-        &self,
-        mut clauses: Vec<Clause>,
-        mut assignment: HashMap<usize, bool>,
-    ) -> Solution {
-        // 1. Unit Propagation
+    /// Like `solve`, also returning how much work the search did.
+    pub fn solve_with_stats(&self) -> (Solution, SolveStats) {
+        let mut log = ProofLog::new(None);
+        let solution = self.dpll_solve(&mut log, None).expect("no decision limit");
+        (solution, log.stats)
+    }
+
+    /// Like `solve`, but gives up once the search has made `max_decisions`
+    /// decisions and is about to make another, returning the statistics so
+    /// far instead.
+    pub fn solve_with_limit(&self, max_decisions: u64) -> Result<Solution, SolveStats> {
+        let mut log = ProofLog::new(None);
+        self.dpll_solve(&mut log, Some(max_decisions))
+            .ok_or(log.stats)
+    }
+
+    /// Like `solve`, but writes a DRAT proof to `proof` so an external
+    /// checker (e.g. `drat-trim`) can verify an `Unsatisfiable` answer.
+    ///
+    /// The learned clauses are the ones DPLL learns implicitly: whenever the
+    /// search refutes a set of decisions, the clause forbidding that set is
+    /// added (each is derivable by unit propagation from the formula and the
+    /// clauses already logged), and the two clauses it was derived from are
+    /// deleted. A refutation ends with the empty clause `0`. For satisfiable
+    /// formulas the log just records the refuted branches.
+    pub fn solve_with_proof(&self, proof: &mut impl Write) -> io::Result<Solution> {
+        let mut log = ProofLog::new(Some(proof));
+        let solution = self.dpll_solve(&mut log, None).expect("no decision limit");
+        match log.error {
+            Some(err) => Err(err),
+            None => Ok(solution),
+        }
+    }
+
+    /// Every satisfying assignment, each giving a value to all variables
+    /// from 1 to the highest in use.
+    ///
+    /// Solves repeatedly, each time adding a clause that blocks the model
+    /// just found, so it is only practical when there are few models.
+    pub fn solve_all(&self) -> Vec<HashMap<usize, bool>> {
+        let max_var = self.variable_count();
+        let mut blocked = SatSolver {
+            clauses: self.clauses.clone(),
+            num_vars: self.num_vars,
+        };
+        let mut models = Vec::new();
+        while let Solution::Satisfiable(mut model) = blocked.solve() {
+            // Variables left unassigned don't matter; pin them to false.
+            for id in 1..=max_var {
+                model.entry(id).or_insert(false);
+            }
+            blocked.add_clause(
+                (1..=max_var)
+                    .map(|id| Literal::new(id, model[&id]))
+                    .collect(),
+            );
+            models.push(model);
+        }
+        models
+    }
+
+    /// Number of satisfying assignments of the variables from 1 to
+    /// `num_vars` (or the highest id in use, if larger). Saturates at
+    /// `u64::MAX`.
+    ///
+    /// Runs the DPLL search without stopping at the first model: each
+    /// branch that satisfies every clause contributes `2^k` models for its
+    /// `k` unassigned variables, which includes variables absent from all
+    /// clauses. Pure literal elimination is skipped, as it discards models.
+    pub fn count_models(&self) -> u64 {
+        let max_var = self.variable_count();
+        let mut trail = Trail::new(max_var);
+        // (decided variable, trail length before the decision, false tried?)
+        let mut frames: Vec<(usize, usize, bool)> = Vec::new();
+        let mut count: u64 = 0;
+
         loop {
-            let mut unit_lit = None;
-            for clause in &clauses {
-                if clause.len() == 1 {
-                    unit_lit = Some(clause[0]);
-                    break;
+            match self.propagate(&mut trail, false, &mut SolveStats::default()) {
+                Propagation::Undecided => {
+                    let var = (1..=max_var)
+                        .find(|&id| trail.values[id].is_none())
+                        .expect("an open clause has an unassigned variable");
+                    frames.push((var, trail.assigned.len(), false));
+                    trail.assign(Literal::new(var, false));
+                    continue;
                 }
+                Propagation::Satisfied => {
+                    let free = (max_var - trail.assigned.len()) as u32;
+                    let models = 1u64.checked_shl(free).unwrap_or(u64::MAX);
+                    count = count.saturating_add(models);
+                }
+                Propagation::Conflict => {}
             }
 
-            if let Some(lit) = unit_lit {
-                let val = !lit.negated;
-                // Check for conflict
-                if let Some(&existing) = assignment.get(&lit.id)
-                    && existing != val
-                {
-                    return Solution::Unsatisfiable;
+            // Move on to the next untried branch.
+            loop {
+                let Some((var, trail_len, tried_false)) = frames.last_mut() else {
+                    return count;
+                };
+                trail.undo_to(*trail_len);
+                if !*tried_false {
+                    *tried_false = true;
+                    trail.assign(Literal::new(*var, true));
+                    break;
                 }
-                assignment.insert(lit.id, val);
+                frames.pop();
+            }
+        }
+    }
 
-                // Simplify clauses
-                if !self.simplify(&mut clauses, lit) {
-                    return Solution::Unsatisfiable; // Empty clause generated -> unsat
+    /// Conflict-driven clause learning: a complementary search to `solve`
+    /// that does much better on structured instances.
+    ///
+    /// Every conflict is analyzed back to its first unique implication point
+    /// (the single assignment of the current decision level through which all
+    /// paths to the conflict pass), yielding a learned clause that is added
+    /// to the clause database. The search then backjumps straight to the
+    /// second-highest decision level in that clause, where it becomes unit,
+    /// skipping any levels that played no part in the conflict.
+    pub fn solve_cdcl(&self) -> Solution {
+        self.cdcl_solve(&mut ProofLog::new(None))
+    }
+
+    /// Like `solve_cdcl`, but writes a DRAT proof to `proof`: every learned
+    /// clause, in the order learned (each follows from the formula and the
+    /// earlier ones by unit propagation), then the empty clause `0` if the
+    /// formula is refuted. Learned clauses are never deleted.
+    pub fn solve_cdcl_with_proof(&self, proof: &mut impl Write) -> io::Result<Solution> {
+        let mut log = ProofLog::new(Some(proof));
+        let solution = self.cdcl_solve(&mut log);
+        match log.error {
+            Some(err) => Err(err),
+            None => Ok(solution),
+        }
+    }
+
+    /// DPLL as an explicit loop over a trail of assignments. Backtracking
+    /// unassigns the trail down to the last decision instead of restoring
+    /// cloned clause sets, so neither memory nor stack depth grows with the
+    /// number of decisions.
+    ///
+    /// Each decision first tries `true`, then `false`; once both are refuted
+    /// the decisions above it are refuted too (logged to `proof`), and the
+    /// search backtracks further. Returns `None` if it would exceed
+    /// `max_decisions`.
+    fn dpll_solve(&self, proof: &mut ProofLog, max_decisions: Option<u64>) -> Option<Solution> {
+        let mut trail = Trail::new(self.variable_count());
+        // (decided variable, trail length before the decision, false tried?)
+        let mut frames: Vec<(usize, usize, bool)> = Vec::new();
+
+        loop {
+            match self.propagate(&mut trail, true, &mut proof.stats) {
+                Propagation::Satisfied => return Some(Solution::Satisfiable(trail.model())),
+                Propagation::Undecided => {
+                    if max_decisions.is_some_and(|max| proof.stats.decisions >= max) {
+                        return None;
+                    }
+                    // Pick the first unassigned variable if provided, otherwise
+                    // fall back to the first open literal.
+                    let var = (1..=self.num_vars)
+                        .find(|&id| trail.values[id].is_none())
+                        .unwrap_or_else(|| self.first_open_literal(&trail).id);
+                    proof.stats.decisions += 1;
+                    frames.push((var, trail.assigned.len(), false));
+                    let lit = Literal::new(var, false);
+                    proof.decisions.push(lit);
+                    trail.assign(lit);
                 }
-                if clauses.is_empty() {
-                    return Solution::Satisfiable(assignment);
+                Propagation::Conflict => {
+                    proof.stats.conflicts += 1;
+                    proof.refute();
+                    // Backtrack to the most recent decision with an untried
+                    // polarity, refuting every decision set exhausted on the way.
+                    loop {
+                        let Some((var, trail_len, tried_false)) = frames.last_mut() else {
+                            return Some(Solution::Unsatisfiable);
+                        };
+                        trail.undo_to(*trail_len);
+                        proof.decisions.pop();
+                        if !*tried_false {
+                            *tried_false = true;
+                            let lit = Literal::new(*var, true);
+                            proof.decisions.push(lit);
+                            trail.assign(lit);
+                            break;
+                        }
+                        let var = *var;
+                        frames.pop();
+                        proof.refute();
+                        if !proof.decisions.is_empty() {
+                            proof.delete_refutation_with(Literal::new(var, false));
+                            proof.delete_refutation_with(Literal::new(var, true));
+                        }
+                    }
                 }
-            } else {
-                break;
             }
         }
+    }
 
-        // 2. Pure Literal Elimination (Optional but good)
-        // Skip for simple DPLL or implement?
-        // Let's implement minimal branching first.
+    /// The CDCL search behind `solve_cdcl`. Learned clauses are logged to
+    /// `proof` (each follows from the formula and earlier ones by unit
+    /// propagation), ending with the empty clause when the formula is
+    /// refuted.
+    fn cdcl_solve(&self, proof: &mut ProofLog) -> Solution {
+        let mut search = Cdcl::new(self.variable_count(), &self.clauses);
+        if search.clauses.iter().any(|c| c.is_empty()) {
+            return proof.refute();
+        }
 
-        // Check if empty clause (unsat)
-        if clauses.iter().any(|c| c.is_empty()) {
-            return Solution::Unsatisfiable;
+        loop {
+            if let Some(conflict) = search.propagate(&mut proof.stats) {
+                proof.stats.conflicts += 1;
+                if search.decision_level() == 0 {
+                    return proof.refute();
+                }
+                let (learned, level) = search.analyze(conflict);
+                proof.write_clause("", &learned);
+                search.backjump(level);
+                search.clauses.push(learned);
+                continue;
+            }
+            if search
+                .clauses
+                .iter()
+                .all(|c| matches!(search.trail.status(c), ClauseStatus::Satisfied))
+            {
+                return Solution::Satisfiable(search.trail.model());
+            }
+            let var = (1..search.trail.values.len())
+                .find(|&id| search.trail.values[id].is_none())
+                .expect("an open clause has an unassigned variable");
+            proof.stats.decisions += 1;
+            search.decide(Literal::new(var, false));
         }
-        // Check if no clauses (sat)
-        if clauses.is_empty() {
-            return Solution::Satisfiable(assignment);
+    }
+
+    /// Highest variable id in use: the declared count, or more if a clause
+    /// mentions a larger id.
+    fn variable_count(&self) -> usize {
+        self.clauses
+            .iter()
+            .flatten()
+            .map(|lit| lit.id)
+            .max()
+            .unwrap_or(0)
+            .max(self.num_vars)
+    }
+
+    /// Unit propagation to a fixed point, then (if `pure_literals`) pure
+    /// literal elimination.
+    ///
+    /// Units are taken in clause order, first one first. A pure literal (its
+    /// variable occurs with one polarity only in the clauses not yet
+    /// satisfied) can be set to satisfy all of them. No open clause contains
+    /// the opposite literal, so this never causes a conflict, and any later
+    /// refutation holds without it (keeping the DRAT proof valid).
+    fn propagate(
+        &self,
+        trail: &mut Trail,
+        pure_literals: bool,
+        stats: &mut SolveStats,
+    ) -> Propagation {
+        loop {
+            let mut unit = None;
+            for clause in &self.clauses {
+                match trail.status(clause) {
+                    ClauseStatus::Falsified => return Propagation::Conflict,
+                    ClauseStatus::Unit(lit) => {
+                        unit.get_or_insert(lit);
+                    }
+                    ClauseStatus::Satisfied | ClauseStatus::Open => {}
+                }
+            }
+            match unit {
+                Some(lit) => {
+                    stats.unit_propagations += 1;
+                    trail.assign(lit);
+                }
+                None => break,
+            }
         }
 
-        // 3. Branching
-        // Pick the first unassigned variable if provided, otherwise fall back to first literal
-        let var = (1..=self.num_vars)
-            .find(|id| !assignment.contains_key(id))
-            .unwrap_or(clauses[0][0].id);
+        if pure_literals {
+            while let Some(lit) = self.find_pure_literal(trail) {
+                trail.assign(lit);
+            }
+        }
 
-        // Try true
-        let mut left_clauses = clauses.clone();
-        let mut left_assignment = assignment.clone();
-        left_assignment.insert(var, true);
-        let lit_true = Literal::new(var, false);
-        if self.simplify(&mut left_clauses, lit_true)
-            && let Solution::Satisfiable(res) = self.dpll_solve(left_clauses, left_assignment)
+        if self
+            .clauses
+            .iter()
+            .all(|c| matches!(trail.status(c), ClauseStatus::Satisfied))
         {
-            return Solution::Satisfiable(res);
+            Propagation::Satisfied
+        } else {
+            Propagation::Undecided
         }
+    }
 
-        // Try false
-        let mut right_clauses = clauses; // move clauses
-        let mut right_assignment = assignment;
-        right_assignment.insert(var, false);
-        let lit_false = Literal::new(var, true);
-        if self.simplify(&mut right_clauses, lit_false) {
-            return self.dpll_solve(right_clauses, right_assignment);
+    /// A pure literal among the unassigned literals of the open clauses:
+    /// its variable occurs there with this polarity only. Smallest variable
+    /// first, so the search stays deterministic.
+    fn find_pure_literal(&self, trail: &Trail) -> Option<Literal> {
+        let mut polarity: HashMap<usize, Option<bool>> = HashMap::new();
+        for clause in &self.clauses {
+            if matches!(trail.status(clause), ClauseStatus::Satisfied) {
+                continue;
+            }
+            for lit in clause.iter().filter(|lit| trail.value(**lit).is_none()) {
+                polarity
+                    .entry(lit.id)
+                    .and_modify(|p| {
+                        if *p != Some(lit.negated) {
+                            *p = None;
+                        }
+                    })
+                    .or_insert(Some(lit.negated));
+            }
         }
+        polarity
+            .into_iter()
+            .filter_map(|(id, p)| p.map(|negated| Literal::new(id, negated)))
+            .min_by_key(|lit| lit.id)
+    }
 
-        Solution::Unsatisfiable
+    /// First unassigned literal of the first clause not yet satisfied.
+    fn first_open_literal(&self, trail: &Trail) -> Literal {
+        self.clauses
+            .iter()
+            .filter(|c| !matches!(trail.status(c), ClauseStatus::Satisfied))
+            .flatten()
+            .copied()
+            .find(|lit| trail.value(*lit).is_none())
+            .expect("an undecided formula has an open literal")
     }
+}
 
-    /// Simplifies clauses given a literal assignment.
-    /// Returns false if an empty clause is generated (conflict).
-    fn simplify(&self, clauses: &mut Vec<Clause>, lit: Literal) -> bool {
-        // Remove clauses containing lit (they are satisfied)
-        clauses.retain(|c| !c.contains(&lit));
+/// Outcome of `SatSolver::propagate`.
+enum Propagation {
+    Conflict,
+    Satisfied,
+    /// No conflict yet, but some clauses are still open: time to branch.
+    Undecided,
+}
+
+/// A clause under a partial assignment.
+enum ClauseStatus {
+    Satisfied,
+    /// Every literal is false.
+    Falsified,
+    /// Only this literal is unassigned and the rest are false.
+    Unit(Literal),
+    /// At least two literals are unassigned.
+    Open,
+}
 
-        // Remove !lit from clauses (it cannot be true)
-        let not_lit = lit.not();
-        for clause in clauses.iter_mut() {
-            if let Some(pos) = clause.iter().position(|&l| l == not_lit) {
-                clause.remove(pos);
-                if clause.is_empty() {
-                    return false;
+/// The partial assignment of the iterative DPLL search, with the literals
+/// made true in assignment order so backtracking can undo them.
+struct Trail {
+    /// Value of each variable, indexed by id.
+    values: Vec<Option<bool>>,
+    assigned: Vec<Literal>,
+}
+
+impl Trail {
+    fn new(max_var: usize) -> Self {
+        Trail {
+            values: vec![None; max_var + 1],
+            assigned: Vec::new(),
+        }
+    }
+
+    /// Truth value of `lit`, if its variable is assigned.
+    fn value(&self, lit: Literal) -> Option<bool> {
+        self.values[lit.id].map(|v| v != lit.negated)
+    }
+
+    /// Makes `lit` true.
+    fn assign(&mut self, lit: Literal) {
+        self.values[lit.id] = Some(!lit.negated);
+        self.assigned.push(lit);
+    }
+
+    /// Unassigns everything after the first `len` assignments.
+    fn undo_to(&mut self, len: usize) {
+        for lit in self.assigned.drain(len..) {
+            self.values[lit.id] = None;
+        }
+    }
+
+    fn status(&self, clause: &[Literal]) -> ClauseStatus {
+        let mut unassigned = None;
+        let mut open = 0;
+        for &lit in clause {
+            match self.value(lit) {
+                Some(true) => return ClauseStatus::Satisfied,
+                Some(false) => {}
+                None => {
+                    open += 1;
+                    unassigned = Some(lit);
                 }
             }
         }
-        true
+        match (open, unassigned) {
+            (0, _) => ClauseStatus::Falsified,
+            (1, Some(lit)) => ClauseStatus::Unit(lit),
+            _ => ClauseStatus::Open,
+        }
+    }
+
+    fn model(&self) -> HashMap<usize, bool> {
+        self.assigned
+            .iter()
+            .map(|lit| (lit.id, !lit.negated))
+            .collect()
+    }
+}
+
+/// State of the CDCL search: the trail plus, for every assigned variable,
+/// the decision level it was assigned at and the clause that implied it.
+struct Cdcl {
+    /// The formula's clauses (with repeated literals removed), followed by
+    /// the learned ones.
+    clauses: Vec<Clause>,
+    trail: Trail,
+    level: Vec<usize>,
+    /// Index of the clause that forced each variable; `None` for decisions.
+    reason: Vec<Option<usize>>,
+    /// Trail length at the start of each decision level.
+    level_starts: Vec<usize>,
+}
+
+impl Cdcl {
+    fn new(max_var: usize, clauses: &[Clause]) -> Self {
+        let clauses = clauses
+            .iter()
+            .map(|clause| {
+                let mut clause = clause.clone();
+                clause.sort_by_key(|lit| (lit.id, lit.negated));
+                clause.dedup();
+                clause
+            })
+            .collect();
+        Cdcl {
+            clauses,
+            trail: Trail::new(max_var),
+            level: vec![0; max_var + 1],
+            reason: vec![None; max_var + 1],
+            level_starts: Vec::new(),
+        }
+    }
+
+    fn decision_level(&self) -> usize {
+        self.level_starts.len()
+    }
+
+    fn decide(&mut self, lit: Literal) {
+        self.level_starts.push(self.trail.assigned.len());
+        self.assign(lit, None);
+    }
+
+    fn assign(&mut self, lit: Literal, reason: Option<usize>) {
+        self.level[lit.id] = self.decision_level();
+        self.reason[lit.id] = reason;
+        self.trail.assign(lit);
+    }
+
+    /// Unit propagation to a fixed point. Returns the index of a falsified
+    /// clause on conflict.
+    fn propagate(&mut self, stats: &mut SolveStats) -> Option<usize> {
+        loop {
+            let mut unit = None;
+            for (i, clause) in self.clauses.iter().enumerate() {
+                match self.trail.status(clause) {
+                    ClauseStatus::Falsified => return Some(i),
+                    ClauseStatus::Unit(lit) => {
+                        unit = Some((lit, i));
+                        break;
+                    }
+                    ClauseStatus::Satisfied | ClauseStatus::Open => {}
+                }
+            }
+            let (lit, reason) = unit?;
+            stats.unit_propagations += 1;
+            self.assign(lit, Some(reason));
+        }
+    }
+
+    /// First-UIP conflict analysis. Resolves the conflicting clause with the
+    /// reasons of the current level's assignments, latest first, until a
+    /// single literal of this level remains. Returns the learned clause (the
+    /// asserting literal first) and the level to backjump to.
+    fn analyze(&self, conflict: usize) -> (Clause, usize) {
+        let current = self.decision_level();
+        let mut seen = vec![false; self.level.len()];
+        let mut learned = Vec::new();
+        let mut pending = 0;
+        let mut clause = conflict;
+        let mut index = self.trail.assigned.len();
+
+        let uip = loop {
+            for &lit in &self.clauses[clause] {
+                if seen[lit.id] || self.trail.value(lit) != Some(false) {
+                    continue;
+                }
+                seen[lit.id] = true;
+                match self.level[lit.id] {
+                    // Root-level assignments hold regardless of decisions.
+                    0 => {}
+                    level if level == current => pending += 1,
+                    _ => learned.push(lit),
+                }
+            }
+            // Latest assignment of this level that is involved in the conflict.
+            let lit = loop {
+                index -= 1;
+                let lit = self.trail.assigned[index];
+                if seen[lit.id] {
+                    break lit;
+                }
+            };
+            pending -= 1;
+            if pending == 0 {
+                break lit;
+            }
+            clause = self.reason[lit.id].expect("only the decision has no reason");
+        };
+
+        let backjump = learned
+            .iter()
+            .map(|lit| self.level[lit.id])
+            .max()
+            .unwrap_or(0);
+        learned.insert(0, uip.not());
+        (learned, backjump)
+    }
+
+    /// Undoes every decision level above `level`.
+    fn backjump(&mut self, level: usize) {
+        if let Some(&start) = self.level_starts.get(level) {
+            for lit in &self.trail.assigned[start..] {
+                self.reason[lit.id] = None;
+            }
+            self.trail.undo_to(start);
+            self.level_starts.truncate(level);
+        }
+    }
+}
+
+/// DRAT proof output for `solve_with_proof` and `solve_cdcl_with_proof`,
+/// tracking the decisions on the current search path, along with the
+/// search statistics.
+struct ProofLog<'w> {
+    writer: Option<&'w mut dyn Write>,
+    decisions: Vec<Literal>,
+    error: Option<io::Error>,
+    stats: SolveStats,
+}
+
+impl<'w> ProofLog<'w> {
+    fn new(writer: Option<&'w mut dyn Write>) -> Self {
+        ProofLog {
+            writer,
+            decisions: Vec::new(),
+            error: None,
+            stats: SolveStats::default(),
+        }
+    }
+
+    /// Logs that the current decisions lead to a conflict, i.e. learns the
+    /// clause of their negations (the empty clause at the root).
+    fn refute(&mut self) -> Solution {
+        let clause: Vec<Literal> = self.decisions.iter().map(Literal::not).collect();
+        self.write_clause("", &clause);
+        Solution::Unsatisfiable
+    }
+
+    /// Deletes the clause learned when the current decisions plus `lit` were
+    /// refuted.
+    fn delete_refutation_with(&mut self, lit: Literal) {
+        let mut clause: Vec<Literal> = self.decisions.iter().map(Literal::not).collect();
+        clause.push(lit.not());
+        self.write_clause("d ", &clause);
+    }
+
+    fn write_clause(&mut self, prefix: &str, clause: &[Literal]) {
+        let Some(writer) = self.writer.as_mut() else {
+            return;
+        };
+        if self.error.is_some() {
+            return;
+        }
+        let mut line = prefix.to_string();
+        for lit in clause {
+            let id = lit.id as i64;
+            line += &format!("{} ", if lit.negated { -id } else { id });
+        }
+        line += "0\n";
+        if let Err(err) = writer.write_all(line.as_bytes()) {
+            self.error = Some(err);
+        }
     }
 }
 
@@ -189,4 +831,414 @@ mod tests {
             Solution::Unsatisfiable => {}
         }
     }
+
+    #[test]
+    fn test_unsat_drat_proof() {
+        // Every combination of x1, x2 is forbidden.
+        let mut solver = SatSolver::new(2);
+        for (n1, n2) in [(false, false), (true, false), (false, true), (true, true)] {
+            solver.add_clause(vec![Literal::new(1, n1), Literal::new(2, n2)]);
+        }
+
+        let mut proof = Vec::new();
+        let solution = solver.solve_with_proof(&mut proof).unwrap();
+        assert_eq!(solution, Solution::Unsatisfiable);
+
+        // x1 = true is refuted, then x1 = false, then nothing is left.
+        let proof = String::from_utf8(proof).unwrap();
+        assert_eq!(proof, "-1 0\n1 0\n0\n");
+        assert!(proof.ends_with("\n0\n"));
+    }
+
+    #[test]
+    fn test_from_dimacs() {
+        let input = "c A small satisfiable instance\n\
+                     c with a clause split across lines\n\
+                     p cnf 4 4\n\
+                     1 -2 0\n\
+                     2 3 0\n\
+                     -1 -3\n\
+                     4 0\n\
+                     -4 -2 0\n";
+        let solver = SatSolver::from_dimacs(input).unwrap();
+        assert_eq!(solver.num_vars, 4);
+        assert_eq!(
+            solver.clauses[2],
+            vec![
+                Literal::new(1, true),
+                Literal::new(3, true),
+                Literal::new(4, false)
+            ]
+        );
+
+        let Solution::Satisfiable(model) = solver.solve() else {
+            panic!("should be satisfiable");
+        };
+        let satisfied = |lit: &Literal| model.get(&lit.id).is_some_and(|&v| v != lit.negated);
+        assert!(solver.clauses.iter().all(|c| c.iter().any(satisfied)));
+
+        let unsat = SatSolver::from_dimacs("p cnf 1 2\n1 0\n-1 0\n%\n0\n").unwrap();
+        assert_eq!(unsat.solve(), Solution::Unsatisfiable);
+    }
+
+    #[test]
+    fn test_from_dimacs_errors() {
+        let parse = |input: &str| SatSolver::from_dimacs(input).err();
+        assert_eq!(
+            parse("p cnf 3\n1 0\n"),
+            Some(ParseError::InvalidHeader { line: 1 })
+        );
+        assert_eq!(
+            parse("p dnf 3 1\n1 0\n"),
+            Some(ParseError::InvalidHeader { line: 1 })
+        );
+        assert_eq!(parse("1 2 0\n"), Some(ParseError::MissingHeader));
+        assert_eq!(
+            parse("p cnf 2 1\n1 -3 0\n"),
+            Some(ParseError::VariableOutOfRange { line: 2, var: 3 })
+        );
+        assert_eq!(
+            parse("p cnf 2 1\n1 x 0\n"),
+            Some(ParseError::InvalidLiteral {
+                line: 2,
+                token: "x".to_string()
+            })
+        );
+        assert_eq!(
+            parse("p cnf 2 1\n1 2\n"),
+            Some(ParseError::UnterminatedClause)
+        );
+        assert_eq!(
+            parse("p cnf 2 2\n1 2 0\n"),
+            Some(ParseError::ClauseCountMismatch {
+                expected: 2,
+                found: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_pure_literal_needs_no_branching() {
+        // x1 only occurs positively; setting it satisfies every clause.
+        let mut solver = SatSolver::new(3);
+        solver.add_clause(vec![Literal::new(1, false), Literal::new(2, false)]);
+        solver.add_clause(vec![
+            Literal::new(1, false),
+            Literal::new(2, true),
+            Literal::new(3, false),
+        ]);
+        solver.add_clause(vec![Literal::new(1, false), Literal::new(3, true)]);
+
+        let (solution, stats) = solver.solve_with_stats();
+        assert_eq!(solution, Solution::Satisfiable(HashMap::from([(1, true)])));
+        assert_eq!(stats.decisions, 0);
+
+        // Still unsatisfiable when nothing is pure.
+        let mut unsat = SatSolver::new(2);
+        for (n1, n2) in [(false, false), (true, false), (false, true), (true, true)] {
+            unsat.add_clause(vec![Literal::new(1, n1), Literal::new(2, n2)]);
+        }
+        assert_eq!(unsat.solve(), Solution::Unsatisfiable);
+    }
+
+    /// True if `model` makes every clause of `solver` true.
+    fn satisfies(solver: &SatSolver, model: &HashMap<usize, bool>) -> bool {
+        solver.clauses.iter().all(|clause| {
+            clause
+                .iter()
+                .any(|lit| model.get(&lit.id).is_some_and(|&v| v != lit.negated))
+        })
+    }
+
+    #[test]
+    fn test_long_chain_without_recursion() {
+        // Every window of three consecutive variables has a true and a false
+        // one. Nothing propagates from the root, so the search has to decide
+        // a long run of variables one after another.
+        let n = 500;
+        let mut solver = SatSolver::new(n);
+        for i in 1..=n - 2 {
+            solver.add_clause((i..i + 3).map(|id| Literal::new(id, false)).collect());
+            solver.add_clause((i..i + 3).map(|id| Literal::new(id, true)).collect());
+        }
+
+        let (Solution::Satisfiable(model), stats) = solver.solve_with_stats() else {
+            panic!("should be satisfiable");
+        };
+        assert!(satisfies(&solver, &model));
+        assert!(stats.decisions >= 100, "only {} decisions", stats.decisions);
+    }
+
+    #[test]
+    fn test_repeated_literals() {
+        // x2 repeats in the middle clause; x1 is forced false, so x2 must
+        // hold, and then x3 must be false.
+        let mut solver = SatSolver::new(3);
+        solver.add_clause(vec![Literal::new(1, true)]);
+        solver.add_clause(vec![
+            Literal::new(2, false),
+            Literal::new(1, false),
+            Literal::new(2, false),
+        ]);
+        solver.add_clause(vec![Literal::new(2, true), Literal::new(3, true)]);
+
+        let Solution::Satisfiable(model) = solver.solve() else {
+            panic!("should be satisfiable");
+        };
+        assert!(satisfies(&solver, &model));
+        assert_eq!(model.get(&3), Some(&false));
+    }
+
+    /// `pigeons` pigeons in `holes` holes, each hole holding at most one:
+    /// unsatisfiable whenever there are more pigeons than holes.
+    fn pigeonhole(pigeons: usize, holes: usize) -> SatSolver {
+        let var = |p: usize, h: usize| p * holes + h + 1;
+        let mut solver = SatSolver::new(pigeons * holes);
+        for p in 0..pigeons {
+            solver.add_clause((0..holes).map(|h| Literal::new(var(p, h), false)).collect());
+        }
+        for h in 0..holes {
+            for p in 0..pigeons {
+                for q in p + 1..pigeons {
+                    solver.add_clause(vec![
+                        Literal::new(var(p, h), true),
+                        Literal::new(var(q, h), true),
+                    ]);
+                }
+            }
+        }
+        solver
+    }
+
+    /// Whether `lemma` follows from `clauses` by reverse unit propagation:
+    /// assuming every literal of it false, unit propagation hits a conflict.
+    fn is_rup(clauses: &[Clause], lemma: &[Literal]) -> bool {
+        let mut value: HashMap<usize, bool> = lemma.iter().map(|l| (l.id, l.negated)).collect();
+        loop {
+            let mut changed = false;
+            for clause in clauses {
+                let falsified = |l: &&Literal| value.get(&l.id) == Some(&l.negated);
+                if clause.iter().any(|l| value.get(&l.id) == Some(&!l.negated)) {
+                    continue;
+                }
+                let open: Vec<&Literal> = clause.iter().filter(|l| !falsified(l)).collect();
+                match open.as_slice() {
+                    [] => return true,
+                    [unit] => {
+                        value.insert(unit.id, !unit.negated);
+                        changed = true;
+                    }
+                    _ => {}
+                }
+            }
+            if !changed {
+                return false;
+            }
+        }
+    }
+
+    #[test]
+    fn test_cdcl_drat_proof() {
+        let solver = pigeonhole(4, 3);
+        let mut proof = Vec::new();
+        assert_eq!(
+            solver.solve_cdcl_with_proof(&mut proof).unwrap(),
+            Solution::Unsatisfiable
+        );
+
+        // Replay the proof as a checker would: every lemma must follow from
+        // the formula and the lemmas before it, ending with the empty clause.
+        let proof = String::from_utf8(proof).unwrap();
+        assert!(proof.ends_with("\n0\n"));
+        let mut clauses = solver.clauses.clone();
+        for line in proof.lines() {
+            assert!(!line.starts_with('d'));
+            let lemma: Clause = line
+                .split_whitespace()
+                .map(|n| n.parse::<i64>().unwrap())
+                .take_while(|&n| n != 0)
+                .map(|n| Literal::new(n.unsigned_abs() as usize, n < 0))
+                .collect();
+            assert!(is_rup(&clauses, &lemma), "not RUP: {}", line);
+            clauses.push(lemma);
+        }
+        assert!(clauses.last().unwrap().is_empty());
+
+        // A satisfiable formula yields no refutation.
+        let mut proof = Vec::new();
+        let Solution::Satisfiable(_) = pigeonhole(3, 3).solve_cdcl_with_proof(&mut proof).unwrap()
+        else {
+            panic!("3 pigeons fit in 3 holes");
+        };
+        assert!(!String::from_utf8(proof).unwrap().ends_with("\n0\n"));
+    }
+
+    #[test]
+    fn test_cdcl_agrees_with_dpll() {
+        let mut formulas = Vec::new();
+
+        let mut simple = SatSolver::new(2);
+        simple.add_clause(vec![Literal::new(1, false), Literal::new(2, false)]);
+        simple.add_clause(vec![Literal::new(1, true), Literal::new(2, false)]);
+        formulas.push(simple);
+        formulas.push(SatSolver::from_dimacs("p cnf 1 2\n1 0\n-1 0\n").unwrap());
+        formulas
+            .push(SatSolver::from_dimacs("p cnf 2 4\n1 2 0\n-1 2 0\n1 -2 0\n-1 -2 0\n").unwrap());
+        formulas.push(SatSolver::from_dimacs("p cnf 3 3\n-1 0\n2 1 2 0\n-2 -3 0\n").unwrap());
+        formulas.push(pigeonhole(3, 3));
+
+        // Random 3-SAT around the satisfiability threshold, from a fixed LCG.
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        let mut next = |bound: u64| {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            (state >> 33) % bound
+        };
+        for _ in 0..200 {
+            let mut solver = SatSolver::new(12);
+            for _ in 0..51 {
+                let clause = (0..3)
+                    .map(|_| Literal::new(next(12) as usize + 1, next(2) == 1))
+                    .collect();
+                solver.add_clause(clause);
+            }
+            formulas.push(solver);
+        }
+
+        let mut satisfiable = 0;
+        for solver in &formulas {
+            match (solver.solve(), solver.solve_cdcl()) {
+                (Solution::Unsatisfiable, Solution::Unsatisfiable) => {}
+                (Solution::Satisfiable(_), Solution::Satisfiable(model)) => {
+                    assert!(satisfies(solver, &model));
+                    satisfiable += 1;
+                }
+                (dpll, cdcl) => panic!("DPLL found {:?}, CDCL {:?}", dpll, cdcl),
+            }
+        }
+        assert!(satisfiable > 20 && satisfiable < formulas.len() - 20);
+    }
+
+    #[test]
+    fn test_cdcl_pigeonhole_needs_fewer_decisions() {
+        // Four pigeons, three holes, behind eight independent pairs of
+        // variables that must differ. The variables are decided in order, so
+        // DPLL refutes the pigeonhole again under every choice for the pairs,
+        // while the clauses CDCL learns never mention them and it backjumps
+        // over the pairs instead.
+        let pairs = 8;
+        let core = pigeonhole(4, 3);
+        let mut solver = SatSolver::new(2 * pairs + core.num_vars);
+        for i in 0..pairs {
+            let (a, b) = (2 * i + 1, 2 * i + 2);
+            solver.add_clause(vec![Literal::new(a, false), Literal::new(b, false)]);
+            solver.add_clause(vec![Literal::new(a, true), Literal::new(b, true)]);
+        }
+        for clause in &core.clauses {
+            let shifted = clause
+                .iter()
+                .map(|lit| Literal::new(lit.id + 2 * pairs, lit.negated));
+            solver.add_clause(shifted.collect());
+        }
+
+        let (solution, dpll) = solver.solve_with_stats();
+        assert_eq!(solution, Solution::Unsatisfiable);
+        let mut cdcl = ProofLog::new(None);
+        assert_eq!(solver.cdcl_solve(&mut cdcl), Solution::Unsatisfiable);
+
+        assert!(
+            cdcl.stats.decisions * 20 < dpll.decisions,
+            "CDCL made {} decisions, DPLL {}",
+            cdcl.stats.decisions,
+            dpll.decisions
+        );
+    }
+
+    #[test]
+    fn test_exactly_one() {
+        let mut solver = SatSolver::new(3);
+        let lits: Vec<Literal> = (1..=3).map(|id| Literal::new(id, false)).collect();
+        solver.add_exactly_one(&lits);
+
+        let mut trues: Vec<usize> = solver
+            .solve_all()
+            .iter()
+            .map(|model| {
+                assert_eq!(model.values().filter(|&&v| v).count(), 1);
+                (1..=3).find(|id| model[id]).unwrap()
+            })
+            .collect();
+        trues.sort();
+        assert_eq!(trues, vec![1, 2, 3]);
+
+        // At most one also allows none of them.
+        let mut at_most = SatSolver::new(3);
+        at_most.add_at_most_one(&lits);
+        assert_eq!(at_most.solve_all().len(), 4);
+    }
+
+    #[test]
+    fn test_count_models() {
+        // (x1 OR x2) has three models over x1, x2, and x3 is free.
+        let mut solver = SatSolver::new(3);
+        solver.add_clause(vec![Literal::new(1, false), Literal::new(2, false)]);
+        assert_eq!(solver.count_models(), 6);
+
+        assert_eq!(SatSolver::new(4).count_models(), 16);
+        assert_eq!(pigeonhole(3, 2).count_models(), 0);
+
+        // Agrees with enumeration.
+        let mut exactly_one = SatSolver::new(5);
+        let lits: Vec<Literal> = (1..=4).map(|id| Literal::new(id, false)).collect();
+        exactly_one.add_exactly_one(&lits);
+        exactly_one.add_clause(vec![Literal::new(1, true), Literal::new(5, false)]);
+        assert_eq!(
+            exactly_one.count_models(),
+            exactly_one.solve_all().len() as u64
+        );
+    }
+
+    #[test]
+    fn test_solve_stats() {
+        // The formula of test_simple_sat: x2 is pure, so nothing is decided.
+        let mut solver = SatSolver::new(2);
+        solver.add_clause(vec![Literal::new(1, false), Literal::new(2, false)]);
+        solver.add_clause(vec![Literal::new(1, true), Literal::new(2, false)]);
+        let (_, stats) = solver.solve_with_stats();
+        assert_eq!(stats, SolveStats::default());
+
+        // x1 AND !x1: x1 is propagated, then !x1 is falsified.
+        let unsat = SatSolver::from_dimacs("p cnf 1 2\n1 0\n-1 0\n").unwrap();
+        let (solution, stats) = unsat.solve_with_stats();
+        assert_eq!(solution, Solution::Unsatisfiable);
+        assert_eq!(
+            stats,
+            SolveStats {
+                decisions: 0,
+                unit_propagations: 1,
+                conflicts: 1
+            }
+        );
+
+        // Every decision refutes the remaining x1, x2 combinations.
+        let mut four = SatSolver::new(2);
+        for (n1, n2) in [(false, false), (true, false), (false, true), (true, true)] {
+            four.add_clause(vec![Literal::new(1, n1), Literal::new(2, n2)]);
+        }
+        let (_, stats) = four.solve_with_stats();
+        assert_eq!(stats.decisions, 1);
+        assert_eq!(stats.conflicts, 2);
+    }
+
+    #[test]
+    fn test_solve_with_limit() {
+        let solver = pigeonhole(6, 5);
+        let stats = solver.solve_with_limit(10).unwrap_err();
+        assert_eq!(stats.decisions, 10);
+        assert!(stats.conflicts > 0 && stats.unit_propagations > 0);
+
+        let (solution, stats) = solver.solve_with_stats();
+        assert_eq!(solver.solve_with_limit(stats.decisions), Ok(solution));
+    }
 }
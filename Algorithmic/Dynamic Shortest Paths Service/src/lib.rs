@@ -1,55 +1,459 @@
 use std::cmp::Ordering;
-use std::collections::{BinaryHeap, HashMap};
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::fmt;
+use std::hash::Hash;
+use std::rc::Rc;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct NodeId(pub usize);
 
+/// Interns arbitrary labels (names, coordinates, ...) as dense `NodeId`s,
+/// so a graph can be built from the caller's own identifiers without
+/// hand-assigning numbers, and results can be mapped back to labels.
+#[derive(Clone, Debug)]
+pub struct NodeRegistry<L> {
+    ids: HashMap<L, NodeId>,
+    labels: Vec<L>,
+}
+
+impl<L> Default for NodeRegistry<L> {
+    fn default() -> Self {
+        Self {
+            ids: HashMap::new(),
+            labels: Vec::new(),
+        }
+    }
+}
+
+impl<L: Hash + Eq + Clone> NodeRegistry<L> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the id for `label`, allocating the next free id (0, 1, 2, ...)
+    /// the first time a label is seen.
+    pub fn id_of(&mut self, label: L) -> NodeId {
+        if let Some(id) = self.ids.get(&label) {
+            return *id;
+        }
+        let id = NodeId(self.labels.len());
+        self.labels.push(label.clone());
+        self.ids.insert(label, id);
+        id
+    }
+
+    /// Looks up a label's id without allocating one.
+    pub fn get(&self, label: &L) -> Option<NodeId> {
+        self.ids.get(label).copied()
+    }
+
+    /// The label an id was allocated for, if it came from this registry.
+    pub fn label_of(&self, id: &NodeId) -> Option<&L> {
+        self.labels.get(id.0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+}
+
+/// Error from `DynamicGraph::bellman_ford`: a cycle of negative total
+/// weight reachable from the start, so some distances are unbounded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NegativeCycle {
+    /// The cycle's nodes in edge order; the last has an edge back to the
+    /// first.
+    pub cycle: Vec<NodeId>,
+}
+
+impl fmt::Display for NegativeCycle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let nodes: Vec<String> = self.cycle.iter().map(|n| n.0.to_string()).collect();
+        write!(f, "negative cycle through nodes {}", nodes.join(" -> "))
+    }
+}
+
+impl std::error::Error for NegativeCycle {}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 struct Edge {
     to: NodeId,
     weight: f64,
 }
 
-#[derive(Debug, PartialEq)]
-struct State {
-    cost: f64,
+/// A binary min-heap keyed by node that supports decrease-key.
+/// Each node appears at most once, so relaxing an edge updates the existing
+/// entry in place instead of pushing a duplicate (as lazy deletion would).
+#[derive(Default)]
+struct IndexedMinHeap {
+    heap: Vec<(f64, NodeId)>,
+    // Position of each node currently in `heap`.
+    positions: HashMap<NodeId, usize>,
+    // Number of new entries ever inserted (not counting decrease-key updates).
+    insertions: usize,
+    // Number of entries ever popped.
+    pops: usize,
+}
+
+impl IndexedMinHeap {
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inserts `node` with `key`, or lowers its key if it is already queued
+    /// with a larger one. Larger keys for a queued node are ignored.
+    fn push_or_decrease(&mut self, node: NodeId, key: f64) {
+        if let Some(&pos) = self.positions.get(&node) {
+            if key < self.heap[pos].0 {
+                self.heap[pos].0 = key;
+                self.sift_up(pos);
+            }
+        } else {
+            self.heap.push((key, node));
+            let pos = self.heap.len() - 1;
+            self.positions.insert(node, pos);
+            self.insertions += 1;
+            self.sift_up(pos);
+        }
+    }
+
+    fn pop(&mut self) -> Option<(f64, NodeId)> {
+        if self.heap.is_empty() {
+            return None;
+        }
+        let last = self.heap.len() - 1;
+        self.swap(0, last);
+        let top = self.heap.pop().unwrap();
+        self.positions.remove(&top.1);
+        self.pops += 1;
+        if !self.heap.is_empty() {
+            self.sift_down(0);
+        }
+        Some(top)
+    }
+
+    fn swap(&mut self, a: usize, b: usize) {
+        self.heap.swap(a, b);
+        self.positions.insert(self.heap[a].1, a);
+        self.positions.insert(self.heap[b].1, b);
+    }
+
+    fn sift_up(&mut self, mut pos: usize) {
+        while pos > 0 {
+            let parent = (pos - 1) / 2;
+            if self.heap[pos].0 < self.heap[parent].0 {
+                self.swap(pos, parent);
+                pos = parent;
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn sift_down(&mut self, mut pos: usize) {
+        let len = self.heap.len();
+        loop {
+            let left = 2 * pos + 1;
+            let right = left + 1;
+            let mut smallest = pos;
+            if left < len && self.heap[left].0 < self.heap[smallest].0 {
+                smallest = left;
+            }
+            if right < len && self.heap[right].0 < self.heap[smallest].0 {
+                smallest = right;
+            }
+            if smallest == pos {
+                break;
+            }
+            self.swap(pos, smallest);
+            pos = smallest;
+        }
+    }
+}
+
+/// Priority of a vertex in the incremental repair queue, compared
+/// lexicographically: `[min(g, rhs) + h(start, s) + k_m, min(g, rhs)]`.
+type Key = (f64, f64);
+
+/// Estimate of the cost from the first node to the second, see
+/// `DynamicGraph::with_heuristic`.
+type Heuristic = Rc<dyn Fn(NodeId, NodeId) -> f64>;
+
+fn key_cmp(a: &Key, b: &Key) -> Ordering {
+    a.0.partial_cmp(&b.0)
+        .unwrap_or(Ordering::Equal)
+        .then(a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal))
+}
+
+#[derive(Debug)]
+struct QueueEntry {
+    key: Key,
     node: NodeId,
+    stamp: u64,
+}
+
+impl PartialEq for QueueEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
 }
 
-impl Eq for State {}
+impl Eq for QueueEntry {}
 
-impl PartialOrd for State {
+impl PartialOrd for QueueEntry {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
     }
 }
 
-impl Ord for State {
+impl Ord for QueueEntry {
     fn cmp(&self, other: &Self) -> Ordering {
         // Min-heap (reverse ordering)
-        other
-            .cost
-            .partial_cmp(&self.cost)
-            .unwrap_or(Ordering::Equal)
-    }
-}
-
-/// A dynamic graph that supports shortest path queries.
-/// For a full D* Lite, we need to handle edge updates efficiently by repairing the path.
-/// Given the complexity of full D* Lite for a library without a grid assumption,
-/// we'll implement a simpler dynamic approach: Invalidating cache or Dijkstra.
-/// However, the prompt asks for "Dynamic Shortest Paths Service" and suggests D* Lite.
-/// D* Lite is optimized for goal-directed search in changing environments (usually grids).
-/// We will implement a standard Dijkstra for baseline and a mechanism to update edges.
-/// For true "Dynamic" in general graphs, algorithms like Ramalingam-Reps are used.
-/// Since "D* Lite" is explicitly mentioned, we can try to implement a simplified version
-/// or mostly standard Dijkstra with an API that allows updates.
+        key_cmp(&other.key, &self.key)
+    }
+}
+
+/// Priority queue used by the incremental planner.
+///
+/// Re-keying or removing a vertex doesn't touch the heap: it just changes
+/// the vertex's live stamp, which turns any older heap entry into a
+/// tombstone that is skipped when it reaches the top. Once tombstones
+/// outnumber live entries the heap is rebuilt, so a long-running session
+/// with many edge updates keeps the heap proportional to the live set.
+#[derive(Default)]
+struct RepairQueue {
+    heap: BinaryHeap<QueueEntry>,
+    // Current key and stamp of every vertex that is logically queued.
+    live: HashMap<NodeId, (Key, u64)>,
+    next_stamp: u64,
+}
+
+impl RepairQueue {
+    fn insert(&mut self, node: NodeId, key: Key) {
+        let stamp = self.next_stamp;
+        self.next_stamp += 1;
+        self.live.insert(node, (key, stamp));
+        self.heap.push(QueueEntry { key, node, stamp });
+        self.compact_if_needed();
+    }
+
+    fn remove(&mut self, node: NodeId) {
+        self.live.remove(&node);
+    }
+
+    fn top_key(&mut self) -> Option<Key> {
+        self.discard_tombstones();
+        self.heap.peek().map(|e| e.key)
+    }
+
+    fn pop(&mut self) -> Option<(NodeId, Key)> {
+        self.discard_tombstones();
+        let entry = self.heap.pop()?;
+        self.live.remove(&entry.node);
+        Some((entry.node, entry.key))
+    }
+
+    fn is_tombstone(&self, entry: &QueueEntry) -> bool {
+        self.live.get(&entry.node).map(|&(_, stamp)| stamp) != Some(entry.stamp)
+    }
+
+    fn discard_tombstones(&mut self) {
+        while let Some(top) = self.heap.peek() {
+            if !self.is_tombstone(top) {
+                break;
+            }
+            self.heap.pop();
+        }
+    }
+
+    fn compact_if_needed(&mut self) {
+        if self.heap.len() > 2 * self.live.len() + 16 {
+            self.heap = self
+                .live
+                .iter()
+                .map(|(&node, &(key, stamp))| QueueEntry { key, node, stamp })
+                .collect();
+        }
+    }
+}
+
+/// State for incremental replanning toward a fixed goal (the backward search
+/// of D* Lite). `g` holds settled distances to the goal and `rhs` the
+/// one-step lookahead values; vertices where they disagree are queued for
+/// repair. Missing entries mean infinity.
+///
+/// Keys are focused on the start by the heuristic. When the start moves,
+/// rather than re-keying the whole queue, `k_m` grows by the heuristic
+/// distance moved: queued keys then underestimate their true values, and a
+/// vertex popped with an outdated key is simply requeued.
+struct Planner {
+    goal: NodeId,
+    g: HashMap<NodeId, f64>,
+    rhs: HashMap<NodeId, f64>,
+    queue: RepairQueue,
+    heuristic: Option<Heuristic>,
+    // Start of the previous `plan` call.
+    last_start: Option<NodeId>,
+    k_m: f64,
+    // Number of vertices popped from the queue so far.
+    expansions: usize,
+}
+
+impl Planner {
+    fn new(goal: NodeId, start: NodeId, heuristic: Option<Heuristic>) -> Self {
+        let mut planner = Planner {
+            goal,
+            g: HashMap::new(),
+            rhs: HashMap::new(),
+            queue: RepairQueue::default(),
+            heuristic,
+            last_start: Some(start),
+            k_m: 0.0,
+            expansions: 0,
+        };
+        planner.rhs.insert(goal, 0.0);
+        planner.queue.insert(goal, planner.key(goal));
+        planner
+    }
+
+    /// Heuristic distance from the current start to `node`.
+    fn h(&self, node: NodeId) -> f64 {
+        match (&self.heuristic, self.last_start) {
+            (Some(h), Some(start)) => h(start, node),
+            _ => 0.0,
+        }
+    }
+
+    /// Moves the search's start, accounting for the move in `k_m`.
+    fn move_start(&mut self, start: NodeId) {
+        if self.last_start != Some(start) {
+            self.k_m += self.h(start);
+            self.last_start = Some(start);
+        }
+    }
+
+    fn g(&self, node: NodeId) -> f64 {
+        *self.g.get(&node).unwrap_or(&f64::INFINITY)
+    }
+
+    fn rhs(&self, node: NodeId) -> f64 {
+        *self.rhs.get(&node).unwrap_or(&f64::INFINITY)
+    }
+
+    fn key(&self, node: NodeId) -> Key {
+        let m = self.g(node).min(self.rhs(node));
+        (m + self.h(node) + self.k_m, m)
+    }
+
+    /// Recomputes `rhs(u)` from its successors and requeues `u` if it is
+    /// now inconsistent.
+    fn update_vertex(&mut self, u: NodeId, adj: &HashMap<NodeId, Vec<Edge>>) {
+        if u != self.goal {
+            let rhs = adj
+                .get(&u)
+                .into_iter()
+                .flatten()
+                .filter(|e| e.to != u)
+                .map(|e| e.weight + self.g(e.to))
+                .fold(f64::INFINITY, f64::min);
+            self.rhs.insert(u, rhs);
+        }
+        self.queue.remove(u);
+        if self.g(u) != self.rhs(u) {
+            self.queue.insert(u, self.key(u));
+        }
+    }
+
+    fn compute_shortest_path(
+        &mut self,
+        start: NodeId,
+        adj: &HashMap<NodeId, Vec<Edge>>,
+        preds: &HashMap<NodeId, Vec<NodeId>>,
+    ) {
+        while let Some(top) = self.queue.top_key() {
+            if key_cmp(&top, &self.key(start)) != Ordering::Less && self.rhs(start) == self.g(start)
+            {
+                break;
+            }
+            let (u, old_key) = self.queue.pop().unwrap();
+            self.expansions += 1;
+            let new_key = self.key(u);
+            if key_cmp(&old_key, &new_key) == Ordering::Less {
+                self.queue.insert(u, new_key);
+            } else if self.g(u) > self.rhs(u) {
+                // Overconsistent: settle the lower value.
+                self.g.insert(u, self.rhs(u));
+                for &p in preds.get(&u).into_iter().flatten() {
+                    self.update_vertex(p, adj);
+                }
+            } else {
+                // Underconsistent: invalidate and let neighbors repair.
+                self.g.insert(u, f64::INFINITY);
+                self.update_vertex(u, adj);
+                for &p in preds.get(&u).into_iter().flatten() {
+                    self.update_vertex(p, adj);
+                }
+            }
+        }
+    }
+
+    /// Follows the cheapest successor (edge weight + g) from `start`.
+    fn extract_path(
+        &self,
+        start: NodeId,
+        adj: &HashMap<NodeId, Vec<Edge>>,
+    ) -> Option<(f64, Vec<NodeId>)> {
+        let cost = self.g(start);
+        if cost == f64::INFINITY {
+            return None;
+        }
+        let mut path = vec![start];
+        let mut curr = start;
+        while curr != self.goal {
+            let next = adj
+                .get(&curr)
+                .into_iter()
+                .flatten()
+                .filter(|e| e.to != curr)
+                .min_by(|a, b| {
+                    (a.weight + self.g(a.to))
+                        .partial_cmp(&(b.weight + self.g(b.to)))
+                        .unwrap_or(Ordering::Equal)
+                })?
+                .to;
+            path.push(next);
+            curr = next;
+            if path.len() > self.g.len() + 1 {
+                return None; // Inconsistent state; shouldn't happen.
+            }
+        }
+        Some((cost, path))
+    }
+}
+
+/// A directed graph whose edge weights can change between shortest path
+/// queries.
 ///
-/// Let's implement a robust Dijkstra service that allows graph updates.
-/// Implementing full D* Lite on a generic graph is quite involved (needs rhs values, keys, priority queue management with updates).
+/// `shortest_path` and `astar` search from scratch on every call. `plan`
+/// runs D* Lite: it searches backwards from the goal and keeps that search
+/// between calls, so after edge changes only the vertices whose distance to
+/// the goal they affect are repaired, and the start may move along the way
+/// (as a robot following the path would).
 #[derive(Default)]
 pub struct DynamicGraph {
     adj: HashMap<NodeId, Vec<Edge>>,
+    // Reverse adjacency (distinct predecessors), used by incremental repair.
+    preds: HashMap<NodeId, Vec<NodeId>>,
+    // Incremental planner state kept between `plan` calls.
+    planner: Option<Planner>,
+    heuristic: Option<Heuristic>,
+    // Number of edges with a negative weight, which Dijkstra and D* Lite
+    // can't handle.
+    negative_edges: usize,
 }
 
 impl DynamicGraph {
@@ -57,40 +461,375 @@ impl DynamicGraph {
         Self::default()
     }
 
+    /// Sets the heuristic `plan` uses to focus its search: `h(a, b)` must
+    /// never overestimate the cost of getting from `a` to `b`, and must obey
+    /// the triangle inequality `h(a, c) <= h(a, b) + h(b, c)` (e.g. a
+    /// straight-line distance scaled by the cheapest weight per unit). The
+    /// default is no heuristic.
+    pub fn with_heuristic(mut self, h: impl Fn(NodeId, NodeId) -> f64 + 'static) -> Self {
+        self.heuristic = Some(Rc::new(h));
+        self.planner = None;
+        self
+    }
+
     pub fn add_edge(&mut self, u: NodeId, v: NodeId, weight: f64) {
+        if weight < 0.0 {
+            self.negative_edges += 1;
+        }
         self.adj.entry(u).or_default().push(Edge { to: v, weight });
+        let preds = self.preds.entry(v).or_default();
+        if !preds.contains(&u) {
+            preds.push(u);
+        }
+        self.notify_planner(u);
     }
 
+    /// Sets the weight of the edge `u -> v`, adding it if missing. Parallel
+    /// `u -> v` edges all take the new weight.
     pub fn update_edge(&mut self, u: NodeId, v: NodeId, new_weight: f64) {
-        if let Some(edges) = self.adj.get_mut(&u) {
-            for edge in edges.iter_mut() {
-                if edge.to == v {
-                    edge.weight = new_weight;
-                    return;
-                }
+        let mut found = false;
+        for edge in self.adj.get_mut(&u).into_iter().flatten() {
+            if edge.to == v {
+                self.negative_edges -= usize::from(edge.weight < 0.0);
+                self.negative_edges += usize::from(new_weight < 0.0);
+                edge.weight = new_weight;
+                found = true;
             }
-            // If not found, add it?
-            edges.push(Edge {
-                to: v,
-                weight: new_weight,
-            });
+        }
+        if found {
+            self.notify_planner(u);
         } else {
             self.add_edge(u, v, new_weight);
         }
     }
 
+    /// The outgoing edges of `u` as `(target, weight)`, parallel edges and
+    /// self-loops included, in insertion order.
+    pub fn neighbors(&self, u: NodeId) -> impl Iterator<Item = (NodeId, f64)> + '_ {
+        self.adj
+            .get(&u)
+            .into_iter()
+            .flatten()
+            .map(|e| (e.to, e.weight))
+    }
+
+    /// Removes every `u -> v` edge. Returns whether there was one.
+    pub fn remove_edge(&mut self, u: NodeId, v: NodeId) -> bool {
+        let Some(edges) = self.adj.get_mut(&u) else {
+            return false;
+        };
+        let before = edges.len();
+        let mut negative = 0;
+        edges.retain(|e| {
+            negative += usize::from(e.to == v && e.weight < 0.0);
+            e.to != v
+        });
+        if edges.len() == before {
+            return false;
+        }
+        self.negative_edges -= negative;
+        if let Some(preds) = self.preds.get_mut(&v) {
+            preds.retain(|&p| p != u);
+        }
+        self.notify_planner(u);
+        true
+    }
+
+    /// Removes `u` together with all its outgoing and incoming edges.
+    pub fn remove_node(&mut self, u: NodeId) {
+        for edge in self.adj.remove(&u).into_iter().flatten() {
+            self.negative_edges -= usize::from(edge.weight < 0.0);
+            if let Some(preds) = self.preds.get_mut(&edge.to) {
+                preds.retain(|&p| p != u);
+            }
+        }
+        for p in self.preds.remove(&u).into_iter().flatten() {
+            if let Some(edges) = self.adj.get_mut(&p) {
+                edges.retain(|e| {
+                    self.negative_edges -= usize::from(e.to == u && e.weight < 0.0);
+                    e.to != u
+                });
+            }
+            self.notify_planner(p);
+        }
+        if self.planner.as_ref().is_some_and(|p| p.goal == u) {
+            self.planner = None;
+        } else {
+            self.notify_planner(u);
+        }
+    }
+
+    /// Lets the incremental planner repair `u` after its out-edges changed.
+    fn notify_planner(&mut self, u: NodeId) {
+        if let Some(planner) = self.planner.as_mut() {
+            planner.update_vertex(u, &self.adj);
+        }
+    }
+
+    /// Shortest path from `start` to `goal`, reusing work from earlier calls.
+    ///
+    /// Distances to `goal` are maintained incrementally: edge changes made
+    /// through `add_edge`/`update_edge` only requeue the affected vertices,
+    /// and the next `plan` repairs just the part of the search they
+    /// invalidate. The start may differ from call to call; calling with a
+    /// different goal starts a fresh search.
+    ///
+    /// Weights must be non-negative: if an edge with a negative weight can
+    /// be reached from `start`, this returns `None` (use `bellman_ford`).
+    pub fn plan(&mut self, start: NodeId, goal: NodeId) -> Option<(f64, Vec<NodeId>)> {
+        if self.reaches_negative_edge(start) {
+            return None;
+        }
+        if self.planner.as_ref().map(|p| p.goal) != Some(goal) {
+            self.planner = Some(Planner::new(goal, start, self.heuristic.clone()));
+        }
+        let planner = self.planner.as_mut().unwrap();
+        planner.move_start(start);
+        planner.compute_shortest_path(start, &self.adj, &self.preds);
+        planner.extract_path(start, &self.adj)
+    }
+
+    /// Shortest path from `start` to `goal`, computed from scratch.
+    ///
+    /// Parallel edges act as the cheapest of them, and self-loops never
+    /// appear in a path.
+    ///
+    /// Runs Dijkstra, so weights must be non-negative: if an edge with a
+    /// negative weight can be reached from `start`, this returns `None`
+    /// rather than a possibly wrong path. Use `bellman_ford` for such graphs.
     pub fn shortest_path(&self, start: NodeId, goal: NodeId) -> Option<(f64, Vec<NodeId>)> {
+        if self.reaches_negative_edge(start) {
+            return None;
+        }
+        self.dijkstra(start, goal, &mut IndexedMinHeap::new())
+    }
+
+    /// Whether some negative-weight edge starts at a node reachable from
+    /// `start`. Free when the graph has no negative edges at all.
+    fn reaches_negative_edge(&self, start: NodeId) -> bool {
+        if self.negative_edges == 0 {
+            return false;
+        }
+        let mut seen = HashSet::from([start]);
+        let mut stack = vec![start];
+        while let Some(u) = stack.pop() {
+            for edge in self.adj.get(&u).into_iter().flatten() {
+                if edge.weight < 0.0 {
+                    return true;
+                }
+                if seen.insert(edge.to) {
+                    stack.push(edge.to);
+                }
+            }
+        }
+        false
+    }
+
+    /// Shortest path from `start` to `goal` by A* search: nodes are expanded
+    /// in order of `f = g + h`, where `g` is the cost from `start` and
+    /// `h = heuristic(node)` estimates the remaining cost to `goal`.
+    ///
+    /// With an admissible heuristic (never overestimating the remaining
+    /// cost) the result is as cheap as `shortest_path`'s, usually after
+    /// expanding far fewer nodes; `|_| 0.0` degenerates to Dijkstra. Weights
+    /// must be non-negative.
+    pub fn astar(
+        &self,
+        start: NodeId,
+        goal: NodeId,
+        heuristic: impl Fn(NodeId) -> f64,
+    ) -> Option<(f64, Vec<NodeId>)> {
+        let mut heap = IndexedMinHeap::new();
+        self.best_first(start, goal, &heuristic, &|_, _| false, &mut heap)
+    }
+
+    /// Shortest distances from `start` to every node it can reach (Dijkstra
+    /// without stopping early); unreachable nodes are left out. Weights must
+    /// be non-negative, see `bellman_ford` otherwise.
+    pub fn distances_from(&self, start: NodeId) -> HashMap<NodeId, f64> {
+        let mut dist = HashMap::from([(start, 0.0)]);
+        let mut heap = IndexedMinHeap::new();
+        heap.push_or_decrease(start, 0.0);
+        while let Some((cost, node)) = heap.pop() {
+            for (to, weight) in self.neighbors(node) {
+                let next_cost = cost + weight;
+                if next_cost < *dist.get(&to).unwrap_or(&f64::INFINITY) {
+                    dist.insert(to, next_cost);
+                    heap.push_or_decrease(to, next_cost);
+                }
+            }
+        }
+        dist
+    }
+
+    /// Up to `k` loopless paths from `start` to `goal` by Yen's algorithm,
+    /// cheapest first (ties broken by comparing node ids along the paths).
+    /// Fewer are returned if fewer exist. Weights must be non-negative.
+    ///
+    /// Each new path branches off the previous one at some node (the spur):
+    /// it keeps the previous path up to there, then takes the cheapest route
+    /// to `goal` that avoids the root's earlier nodes and every next hop
+    /// already taken from the same root.
+    pub fn k_shortest_paths(
+        &self,
+        start: NodeId,
+        goal: NodeId,
+        k: usize,
+    ) -> Vec<(f64, Vec<NodeId>)> {
+        let mut found: Vec<(f64, Vec<NodeId>)> = Vec::new();
+        if k == 0 {
+            return found;
+        }
+        let Some(first) = self.dijkstra(start, goal, &mut IndexedMinHeap::new()) else {
+            return found;
+        };
+        found.push(first);
+        let mut candidates: Vec<(f64, Vec<NodeId>)> = Vec::new();
+
+        while found.len() < k {
+            let previous = found.last().unwrap().1.clone();
+            let mut root_cost = 0.0;
+            for i in 0..previous.len() - 1 {
+                let (root, spur) = (&previous[..=i], previous[i]);
+                let banned_nodes: HashSet<NodeId> = root[..i].iter().copied().collect();
+                let banned_hops: HashSet<NodeId> = found
+                    .iter()
+                    .filter(|(_, path)| path.len() > i + 1 && path[..=i] == *root)
+                    .map(|(_, path)| path[i + 1])
+                    .collect();
+                let skip = |u: NodeId, v: NodeId| {
+                    banned_nodes.contains(&v) || (u == spur && banned_hops.contains(&v))
+                };
+
+                let mut heap = IndexedMinHeap::new();
+                if let Some((spur_cost, spur_path)) =
+                    self.best_first(spur, goal, &|_| 0.0, &skip, &mut heap)
+                {
+                    let mut path = root[..i].to_vec();
+                    path.extend(spur_path);
+                    if !candidates.iter().chain(&found).any(|(_, p)| *p == path) {
+                        candidates.push((root_cost + spur_cost, path));
+                    }
+                }
+                root_cost += self.hop_weight(previous[i], previous[i + 1]);
+            }
+
+            let Some(best) = (0..candidates.len()).min_by(|&a, &b| {
+                let (a, b) = (&candidates[a], &candidates[b]);
+                a.0.partial_cmp(&b.0)
+                    .unwrap_or(Ordering::Equal)
+                    .then_with(|| a.1.iter().map(|n| n.0).cmp(b.1.iter().map(|n| n.0)))
+            }) else {
+                break;
+            };
+            found.push(candidates.swap_remove(best));
+        }
+        found
+    }
+
+    /// Weight of the cheapest `u -> v` edge.
+    fn hop_weight(&self, u: NodeId, v: NodeId) -> f64 {
+        self.adj
+            .get(&u)
+            .into_iter()
+            .flatten()
+            .filter(|e| e.to == v)
+            .map(|e| e.weight)
+            .fold(f64::INFINITY, f64::min)
+    }
+
+    /// Distances from `start` to every node it can reach, allowing negative
+    /// weights. Fails with a negative cycle reachable from `start`, if any.
+    pub fn bellman_ford(&self, start: NodeId) -> Result<HashMap<NodeId, f64>, NegativeCycle> {
+        let (dist, parent, still_improving) = self.relax_from(start);
+        let Some(&last) = still_improving.first() else {
+            return Ok(dist);
+        };
+        // `last` was lowered in round |V| only thanks to a negative cycle on
+        // its chain of parents; |V| steps back along it land on the cycle.
+        let mut on_cycle = last;
+        for _ in 0..=parent.len() {
+            on_cycle = parent[&on_cycle];
+        }
+        let mut cycle = vec![on_cycle];
+        let mut curr = parent[&on_cycle];
+        while curr != on_cycle {
+            cycle.push(curr);
+            curr = parent[&curr];
+        }
+        cycle.reverse();
+        Err(NegativeCycle { cycle })
+    }
+
+    /// Bellman-Ford: |V| - 1 rounds relaxing every edge, from `start`. Then
+    /// one more round, returning what it still improved (nothing unless a
+    /// negative cycle is reachable) along with the distances and parents.
+    fn relax_from(
+        &self,
+        start: NodeId,
+    ) -> (HashMap<NodeId, f64>, HashMap<NodeId, NodeId>, Vec<NodeId>) {
+        let mut nodes: HashSet<NodeId> = self.adj.keys().copied().collect();
+        nodes.extend(self.adj.values().flatten().map(|e| e.to));
+        nodes.insert(start);
+
+        let mut dist = HashMap::from([(start, 0.0)]);
+        let mut parent = HashMap::new();
+        let relax_all = |dist: &mut HashMap<NodeId, f64>, parent: &mut HashMap<NodeId, NodeId>| {
+            let mut relaxed = Vec::new();
+            for (&u, edges) in &self.adj {
+                let Some(&du) = dist.get(&u) else {
+                    continue;
+                };
+                for edge in edges {
+                    if du + edge.weight < *dist.get(&edge.to).unwrap_or(&f64::INFINITY) {
+                        dist.insert(edge.to, du + edge.weight);
+                        parent.insert(edge.to, u);
+                        relaxed.push(edge.to);
+                    }
+                }
+            }
+            relaxed
+        };
+        for _ in 1..nodes.len() {
+            if relax_all(&mut dist, &mut parent).is_empty() {
+                break;
+            }
+        }
+        let still_improving = relax_all(&mut dist, &mut parent);
+        (dist, parent, still_improving)
+    }
+
+    fn dijkstra(
+        &self,
+        start: NodeId,
+        goal: NodeId,
+        heap: &mut IndexedMinHeap,
+    ) -> Option<(f64, Vec<NodeId>)> {
+        self.best_first(start, goal, &|_| 0.0, &|_, _| false, heap)
+    }
+
+    /// Dijkstra, or A* for a non-zero `heuristic`: the heap is keyed by
+    /// distance plus heuristic. Edges `u -> v` with `skip_edge(u, v)` are
+    /// ignored.
+    fn best_first(
+        &self,
+        start: NodeId,
+        goal: NodeId,
+        heuristic: &dyn Fn(NodeId) -> f64,
+        skip_edge: &dyn Fn(NodeId, NodeId) -> bool,
+        heap: &mut IndexedMinHeap,
+    ) -> Option<(f64, Vec<NodeId>)> {
         let mut dist = HashMap::new();
-        let mut heap = BinaryHeap::new();
         let mut parent = HashMap::new();
 
         dist.insert(start, 0.0);
-        heap.push(State {
-            cost: 0.0,
-            node: start,
-        });
+        heap.push_or_decrease(start, heuristic(start));
 
-        while let Some(State { cost, node }) = heap.pop() {
+        // With a consistent heuristic (zero for Dijkstra) each node is popped
+        // at most once, with its final distance. A merely admissible one can
+        // pop a node again after finding a cheaper route to it.
+        while let Some((_, node)) = heap.pop() {
+            let cost = dist[&node];
             if node == goal {
                 let mut path = Vec::new();
                 let mut curr = goal;
@@ -103,20 +842,13 @@ impl DynamicGraph {
                 return Some((cost, path));
             }
 
-            if cost > *dist.get(&node).unwrap_or(&f64::MAX) {
-                continue;
-            }
-
             if let Some(edges) = self.adj.get(&node) {
-                for edge in edges {
+                for edge in edges.iter().filter(|e| !skip_edge(node, e.to)) {
                     let next_cost = cost + edge.weight;
                     if next_cost < *dist.get(&edge.to).unwrap_or(&f64::MAX) {
                         dist.insert(edge.to, next_cost);
                         parent.insert(edge.to, node);
-                        heap.push(State {
-                            cost: next_cost,
-                            node: edge.to,
-                        });
+                        heap.push_or_decrease(edge.to, next_cost + heuristic(edge.to));
                     }
                 }
             }
@@ -146,6 +878,62 @@ mod tests {
         assert_eq!(path, vec![n0, n1, n2]);
     }
 
+    #[test]
+    fn test_distances_from_and_neighbors() {
+        let mut graph = DynamicGraph::new();
+        let (n0, n1, n2) = (NodeId(0), NodeId(1), NodeId(2));
+        graph.add_edge(n0, n1, 1.0);
+        graph.add_edge(n1, n2, 2.0);
+        graph.add_edge(n0, n2, 10.0);
+        graph.add_edge(NodeId(3), n0, 1.0);
+
+        assert_eq!(
+            graph.distances_from(n0),
+            HashMap::from([(n0, 0.0), (n1, 1.0), (n2, 3.0)])
+        );
+        assert_eq!(graph.distances_from(n2), HashMap::from([(n2, 0.0)]));
+
+        let out: Vec<(NodeId, f64)> = graph.neighbors(n0).collect();
+        assert_eq!(out, vec![(n1, 1.0), (n2, 10.0)]);
+        assert_eq!(graph.neighbors(n2).count(), 0);
+    }
+
+    #[test]
+    fn test_self_loops() {
+        let mut graph = DynamicGraph::new();
+        let (n0, n1, n2) = (NodeId(0), NodeId(1), NodeId(2));
+        graph.add_edge(n1, n1, 0.0); // Ties with the real next hop
+        graph.add_edge(n0, n1, 1.0);
+        graph.add_edge(n1, n2, 2.0);
+        graph.add_edge(n0, n0, 5.0);
+
+        let expected = Some((3.0, vec![n0, n1, n2]));
+        assert_eq!(graph.shortest_path(n0, n2), expected);
+        assert_eq!(graph.plan(n0, n2), expected);
+
+        // A negative self-loop is a negative cycle.
+        graph.update_edge(n1, n1, -1.0);
+        assert_eq!(graph.bellman_ford(n0).unwrap_err().cycle, vec![n1]);
+        assert!(graph.bellman_ford(n2).is_ok());
+    }
+
+    #[test]
+    fn test_parallel_edges() {
+        let mut graph = DynamicGraph::new();
+        let (n0, n1) = (NodeId(0), NodeId(1));
+        graph.add_edge(n0, n1, 7.0);
+        graph.add_edge(n0, n1, 2.0);
+
+        assert_eq!(graph.shortest_path(n0, n1), Some((2.0, vec![n0, n1])));
+        assert_eq!(graph.plan(n0, n1), Some((2.0, vec![n0, n1])));
+
+        // update_edge sets every parallel edge.
+        graph.update_edge(n0, n1, 4.0);
+        assert!(graph.adj[&n0].iter().all(|e| e.weight == 4.0));
+        assert_eq!(graph.shortest_path(n0, n1), Some((4.0, vec![n0, n1])));
+        assert_eq!(graph.plan(n0, n1), Some((4.0, vec![n0, n1])));
+    }
+
     #[test]
     fn test_dynamic_update() {
         let mut graph = DynamicGraph::new();
@@ -169,4 +957,468 @@ mod tests {
         assert_eq!(cost, 0.5);
         assert_eq!(path, vec![n0, n2]);
     }
+
+    #[test]
+    fn test_remove_edge_and_node() {
+        let mut graph = DynamicGraph::new();
+        let (n0, n1, n2, n3) = (NodeId(0), NodeId(1), NodeId(2), NodeId(3));
+        graph.add_edge(n0, n1, 2.0);
+        graph.add_edge(n1, n2, 2.0);
+        graph.add_edge(n0, n2, 1.0);
+        graph.add_edge(n0, n2, 3.0);
+        graph.add_edge(n0, n3, 1.0);
+        graph.add_edge(n3, n2, 1.0);
+        graph.add_edge(n3, n3, 1.0);
+        assert_eq!(graph.plan(n0, n2), Some((1.0, vec![n0, n2])));
+
+        // Both parallel 0->2 edges go; the route now goes round.
+        assert!(graph.remove_edge(n0, n2));
+        assert!(!graph.remove_edge(n0, n2));
+        assert_eq!(graph.shortest_path(n0, n2), Some((2.0, vec![n0, n3, n2])));
+        assert_eq!(graph.plan(n0, n2), Some((2.0, vec![n0, n3, n2])));
+
+        // Closing node 3 leaves the detour through node 1.
+        graph.remove_node(n3);
+        assert!(!graph.preds[&n2].contains(&n3));
+        let expected = Some((4.0, vec![n0, n1, n2]));
+        assert_eq!(graph.shortest_path(n0, n2), expected);
+        assert_eq!(graph.plan(n0, n2), expected);
+
+        graph.remove_node(n2);
+        assert_eq!(graph.shortest_path(n0, n2), None);
+        assert_eq!(graph.plan(n0, n2), None);
+        assert_eq!(graph.plan(n0, n1), Some((2.0, vec![n0, n1])));
+    }
+
+    #[test]
+    fn test_k_shortest_paths() {
+        // Diamond: 0 -> 1 -> 3 costs 2, 0 -> 2 -> 3 costs 4.
+        let mut diamond = DynamicGraph::new();
+        let (n0, n1, n2, n3) = (NodeId(0), NodeId(1), NodeId(2), NodeId(3));
+        diamond.add_edge(n0, n1, 1.0);
+        diamond.add_edge(n1, n3, 1.0);
+        diamond.add_edge(n0, n2, 2.0);
+        diamond.add_edge(n2, n3, 2.0);
+        assert_eq!(
+            diamond.k_shortest_paths(n0, n3, 5),
+            vec![(2.0, vec![n0, n1, n3]), (4.0, vec![n0, n2, n3])]
+        );
+        assert_eq!(diamond.k_shortest_paths(n0, n3, 1).len(), 1);
+        assert!(diamond.k_shortest_paths(n3, n0, 3).is_empty());
+
+        // The example from Yen's algorithm on Wikipedia, C..H as 1..6.
+        let mut graph = DynamicGraph::new();
+        for (u, v, w) in [
+            (1, 2, 3.0),
+            (1, 3, 2.0),
+            (2, 4, 4.0),
+            (3, 2, 1.0),
+            (3, 4, 2.0),
+            (3, 5, 3.0),
+            (4, 5, 2.0),
+            (4, 6, 1.0),
+            (5, 6, 2.0),
+        ] {
+            graph.add_edge(NodeId(u), NodeId(v), w);
+        }
+        let paths = graph.k_shortest_paths(NodeId(1), NodeId(6), 3);
+        let ids: Vec<(f64, Vec<usize>)> = paths
+            .into_iter()
+            .map(|(cost, path)| (cost, path.into_iter().map(|n| n.0).collect()))
+            .collect();
+        assert_eq!(
+            ids,
+            vec![
+                (5.0, vec![1, 3, 4, 6]),
+                (7.0, vec![1, 3, 5, 6]),
+                (8.0, vec![1, 2, 4, 6])
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bellman_ford_negative_edges() {
+        let mut graph = DynamicGraph::new();
+        let (n0, n1, n2, n3) = (NodeId(0), NodeId(1), NodeId(2), NodeId(3));
+        graph.add_edge(n0, n1, 4.0);
+        graph.add_edge(n0, n2, 2.0);
+        graph.add_edge(n1, n2, -3.0);
+        graph.add_edge(n2, n3, 1.0);
+
+        // Dijkstra would settle 2 at cost 2 before seeing 0 -> 1 -> 2.
+        let dist = graph.bellman_ford(n0).unwrap();
+        assert_eq!(
+            dist,
+            HashMap::from([(n0, 0.0), (n1, 4.0), (n2, 1.0), (n3, 2.0)])
+        );
+        // Dijkstra-based searches refuse rather than answer 2 -> 3 at cost 3.
+        assert_eq!(graph.shortest_path(n0, n3), None);
+        assert_eq!(graph.plan(n0, n3), None);
+        // Searches that can't reach the negative edge still work.
+        assert_eq!(graph.shortest_path(n2, n3), Some((1.0, vec![n2, n3])));
+        assert_eq!(graph.plan(n2, n3), Some((1.0, vec![n2, n3])));
+
+        // Once it is gone, `plan` accepts the graph again.
+        graph.update_edge(n1, n2, 3.0);
+        assert_eq!(graph.plan(n0, n3), Some((3.0, vec![n0, n2, n3])));
+        graph.add_edge(n3, n0, -1.0);
+        assert_eq!(graph.plan(n0, n3), None);
+        graph.remove_node(n3);
+        assert_eq!(graph.plan(n0, n2), Some((2.0, vec![n0, n2])));
+        // Unreachable nodes are left out.
+        assert_eq!(graph.bellman_ford(n3).unwrap(), HashMap::from([(n3, 0.0)]));
+    }
+
+    #[test]
+    fn test_bellman_ford_reports_negative_cycle() {
+        let mut graph = DynamicGraph::new();
+        let (n0, n1, n2, n3) = (NodeId(0), NodeId(1), NodeId(2), NodeId(3));
+        graph.add_edge(n0, n1, 1.0);
+        graph.add_edge(n1, n2, 2.0);
+        graph.add_edge(n2, n1, -3.0);
+        graph.add_edge(n2, n3, 1.0);
+
+        let err = graph.bellman_ford(n0).unwrap_err();
+        let mut cycle = err.cycle.clone();
+        cycle.sort_by_key(|n| n.0);
+        assert_eq!(cycle, vec![n1, n2]);
+        assert_eq!(graph.shortest_path(n0, n3), None);
+
+        // Out of reach of the cycle, distances are fine.
+        assert_eq!(graph.bellman_ford(n3).unwrap(), HashMap::from([(n3, 0.0)]));
+    }
+
+    /// Reference Dijkstra using lazy deletion, kept to compare against the
+    /// indexed heap. Returns the result and the number of heap pushes.
+    fn lazy_dijkstra(
+        graph: &DynamicGraph,
+        start: NodeId,
+        goal: NodeId,
+    ) -> (Option<(f64, Vec<NodeId>)>, usize) {
+        use std::cmp::Ordering;
+        use std::collections::BinaryHeap;
+
+        #[derive(PartialEq)]
+        struct State {
+            cost: f64,
+            node: NodeId,
+        }
+
+        impl Eq for State {}
+
+        impl PartialOrd for State {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for State {
+            fn cmp(&self, other: &Self) -> Ordering {
+                // Min-heap (reverse ordering)
+                other
+                    .cost
+                    .partial_cmp(&self.cost)
+                    .unwrap_or(Ordering::Equal)
+            }
+        }
+
+        let mut dist = HashMap::new();
+        let mut parent = HashMap::new();
+        let mut heap = BinaryHeap::new();
+        let mut pushes = 1;
+        dist.insert(start, 0.0);
+        heap.push(State {
+            cost: 0.0,
+            node: start,
+        });
+
+        while let Some(State { cost, node }) = heap.pop() {
+            if node == goal {
+                let mut path = vec![goal];
+                let mut curr = goal;
+                while let Some(&p) = parent.get(&curr) {
+                    path.push(p);
+                    curr = p;
+                }
+                path.reverse();
+                return (Some((cost, path)), pushes);
+            }
+            if cost > dist[&node] {
+                continue; // Stale entry
+            }
+            for edge in graph.adj.get(&node).into_iter().flatten() {
+                let next_cost = cost + edge.weight;
+                if next_cost < *dist.get(&edge.to).unwrap_or(&f64::MAX) {
+                    dist.insert(edge.to, next_cost);
+                    parent.insert(edge.to, node);
+                    heap.push(State {
+                        cost: next_cost,
+                        node: edge.to,
+                    });
+                    pushes += 1;
+                }
+            }
+        }
+        (None, pushes)
+    }
+
+    /// Dense graph where visiting nodes in index order keeps finding
+    /// slightly cheaper routes to every later node.
+    fn dense_graph(n: usize) -> DynamicGraph {
+        let mut graph = DynamicGraph::new();
+        for u in 0..n {
+            for v in (u + 1)..n {
+                let weight = ((v - u) * (v - u)) as f64 + (n - v) as f64 * 0.5;
+                graph.add_edge(NodeId(u), NodeId(v), weight);
+            }
+        }
+        graph
+    }
+
+    #[test]
+    fn test_indexed_heap_matches_lazy_deletion() {
+        let n = 60;
+        let graph = dense_graph(n);
+
+        for goal in [1, n / 2, n - 1] {
+            let expected = lazy_dijkstra(&graph, NodeId(0), NodeId(goal)).0;
+            assert_eq!(graph.shortest_path(NodeId(0), NodeId(goal)), expected);
+        }
+    }
+
+    #[test]
+    fn test_indexed_heap_reduces_heap_operations() {
+        let n = 200;
+        let graph = dense_graph(n);
+        let goal = NodeId(n - 1);
+
+        let mut heap = IndexedMinHeap::new();
+        let indexed = graph.dijkstra(NodeId(0), goal, &mut heap);
+        let (lazy, lazy_pushes) = lazy_dijkstra(&graph, NodeId(0), goal);
+
+        assert_eq!(indexed, lazy);
+        // Each node enters the indexed heap at most once.
+        assert!(heap.insertions <= n);
+        assert!(
+            heap.insertions * 2 < lazy_pushes,
+            "indexed = {}, lazy = {}",
+            heap.insertions,
+            lazy_pushes
+        );
+    }
+
+    #[test]
+    fn test_astar_matches_dijkstra_with_fewer_pops() {
+        // 20x20 grid with 4-neighbour moves costing 1 to 3.
+        let side = 20;
+        let node = |x: usize, y: usize| NodeId(y * side + x);
+        let mut graph = DynamicGraph::new();
+        for y in 0..side {
+            for x in 0..side {
+                let weight = 1.0 + ((x * 7 + y * 13) % 3) as f64;
+                if x + 1 < side {
+                    graph.add_edge(node(x, y), node(x + 1, y), weight);
+                    graph.add_edge(node(x + 1, y), node(x, y), weight);
+                }
+                if y + 1 < side {
+                    graph.add_edge(node(x, y), node(x, y + 1), weight);
+                    graph.add_edge(node(x, y + 1), node(x, y), weight);
+                }
+            }
+        }
+        let (start, goal) = (node(2, 3), node(12, 8));
+        // Manhattan distance times the cheapest step: admissible.
+        let manhattan = |id: NodeId| {
+            let (x, y) = (id.0 % side, id.0 / side);
+            (x.abs_diff(12) + y.abs_diff(8)) as f64
+        };
+
+        let mut dijkstra_heap = IndexedMinHeap::new();
+        let expected = graph.dijkstra(start, goal, &mut dijkstra_heap).unwrap();
+        let mut astar_heap = IndexedMinHeap::new();
+        let found = graph
+            .best_first(start, goal, &manhattan, &|_, _| false, &mut astar_heap)
+            .unwrap();
+
+        assert_eq!(found.0, expected.0);
+        assert_eq!(graph.astar(start, goal, manhattan).unwrap().0, expected.0);
+        assert!(
+            astar_heap.pops < dijkstra_heap.pops,
+            "A* popped {}, Dijkstra {}",
+            astar_heap.pops,
+            dijkstra_heap.pops
+        );
+
+        assert_eq!(
+            graph.astar(start, start, manhattan),
+            Some((0.0, vec![start]))
+        );
+        assert_eq!(graph.astar(start, NodeId(side * side), |_| 0.0), None);
+    }
+
+    #[test]
+    fn test_plan_matches_dijkstra() {
+        let mut graph = DynamicGraph::new();
+        let n0 = NodeId(0);
+        let n1 = NodeId(1);
+        let n2 = NodeId(2);
+
+        graph.add_edge(n0, n1, 1.0);
+        graph.add_edge(n1, n2, 2.0);
+        graph.add_edge(n0, n2, 10.0);
+
+        assert_eq!(graph.plan(n0, n2), Some((3.0, vec![n0, n1, n2])));
+
+        graph.update_edge(n0, n2, 0.5);
+        assert_eq!(graph.plan(n0, n2), Some((0.5, vec![n0, n2])));
+
+        graph.update_edge(n0, n2, 50.0);
+        assert_eq!(graph.plan(n0, n2), Some((3.0, vec![n0, n1, n2])));
+        assert_eq!(graph.plan(n2, n0), None);
+    }
+
+    #[test]
+    fn test_plan_long_session_bounded_queue() {
+        // Deterministic pseudo-random stream of updates (LCG).
+        let mut seed: u64 = 0x2545F4914F6CDD1D;
+        let mut next = |bound: usize| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            ((seed >> 33) as usize) % bound
+        };
+
+        let n = 40;
+        let mut graph = DynamicGraph::new();
+        for u in 0..n {
+            graph.add_edge(NodeId(u), NodeId((u + 1) % n), 5.0);
+        }
+        for _ in 0..120 {
+            let (u, v) = (next(n), next(n));
+            if u != v {
+                graph.add_edge(NodeId(u), NodeId(v), (1 + next(20)) as f64);
+            }
+        }
+        let goal = NodeId(0);
+
+        for step in 0..3000 {
+            let (u, v) = (next(n), next(n));
+            if u != v {
+                // Integer weights keep both searches' sums exact.
+                graph.update_edge(NodeId(u), NodeId(v), (1 + next(20)) as f64);
+            }
+            let start = NodeId(next(n));
+            let planned = graph.plan(start, goal).map(|(cost, _)| cost);
+            let expected = graph.shortest_path(start, goal).map(|(cost, _)| cost);
+            assert_eq!(planned, expected, "mismatch at step {}", step);
+
+            let queue = &graph.planner.as_ref().unwrap().queue;
+            assert!(queue.heap.len() <= 2 * queue.live.len() + 16);
+        }
+    }
+
+    /// Total weight along `path`, taking the cheapest of parallel edges.
+    fn path_cost(graph: &DynamicGraph, path: &[NodeId]) -> f64 {
+        path.windows(2)
+            .map(|hop| {
+                graph.adj[&hop[0]]
+                    .iter()
+                    .filter(|e| e.to == hop[1])
+                    .map(|e| e.weight)
+                    .fold(f64::INFINITY, f64::min)
+            })
+            .sum()
+    }
+
+    #[test]
+    fn test_dstar_lite_replanning_while_moving() {
+        // 15x15 grid, every step costing 2 (or 1 on the middle row), with
+        // Manhattan distance as the heuristic.
+        let side = 15;
+        let node = |x: usize, y: usize| NodeId(y * side + x);
+        let coords = move |id: NodeId| (id.0 % side, id.0 / side);
+        let mut graph = DynamicGraph::new().with_heuristic(move |a, b| {
+            let ((ax, ay), (bx, by)) = (coords(a), coords(b));
+            (ax.abs_diff(bx) + ay.abs_diff(by)) as f64
+        });
+        for y in 0..side {
+            for x in 0..side {
+                let weight = if y == side / 2 { 1.0 } else { 2.0 };
+                if x + 1 < side {
+                    graph.add_edge(node(x, y), node(x + 1, y), weight);
+                    graph.add_edge(node(x + 1, y), node(x, y), weight);
+                }
+                if y + 1 < side {
+                    graph.add_edge(node(x, y), node(x, y + 1), 2.0);
+                    graph.add_edge(node(x, y + 1), node(x, y), 2.0);
+                }
+            }
+        }
+        let goal = node(side - 1, side - 1);
+        let mut start = node(0, 0);
+
+        let check = |graph: &mut DynamicGraph, start| {
+            let (cost, path) = graph.plan(start, goal).unwrap();
+            let expected = graph.shortest_path(start, goal).unwrap();
+            assert_eq!(cost, expected.0);
+            assert_eq!(path_cost(graph, &path), cost);
+            assert_eq!((path[0], *path.last().unwrap()), (start, goal));
+            path
+        };
+        let mut path = check(&mut graph, start);
+        let initial = graph.planner.as_ref().unwrap().expansions;
+
+        // Walk along the path, closing off the road ahead now and then.
+        let mut step = 0;
+        while start != goal {
+            if step % 3 == 0 && path.len() > 3 {
+                graph.update_edge(path[2], path[3], 40.0);
+                graph.update_edge(path[3], path[2], 40.0);
+            }
+            if step == 4 {
+                // A shortcut opening up behind the robot.
+                graph.update_edge(node(0, 0), node(1, 0), 1.0);
+            }
+            start = path[1];
+            path = check(&mut graph, start);
+            step += 1;
+        }
+
+        // All the repairs together cost less than the initial search.
+        let repairs = graph.planner.as_ref().unwrap().expansions - initial;
+        assert!(
+            repairs < initial,
+            "{} repair expansions over {} steps, initial search {}",
+            repairs,
+            step,
+            initial
+        );
+    }
+
+    #[test]
+    fn test_graph_from_named_nodes() {
+        let mut names = NodeRegistry::new();
+        let mut graph = DynamicGraph::new();
+        for (from, to, weight) in [
+            ("home", "park", 2.0),
+            ("park", "office", 2.0),
+            ("home", "highway", 1.0),
+            ("highway", "office", 5.0),
+        ] {
+            let (u, v) = (names.id_of(from), names.id_of(to));
+            graph.add_edge(u, v, weight);
+        }
+
+        // Re-interning returns the same id.
+        assert_eq!(names.id_of("home"), NodeId(0));
+        assert_eq!(names.len(), 4);
+        assert_eq!(names.get(&"nowhere"), None);
+
+        let (cost, path) = graph
+            .shortest_path(names.id_of("home"), names.id_of("office"))
+            .unwrap();
+        let route: Vec<&str> = path.iter().map(|id| *names.label_of(id).unwrap()).collect();
+        assert_eq!(cost, 4.0);
+        assert_eq!(route, vec!["home", "park", "office"]);
+    }
 }
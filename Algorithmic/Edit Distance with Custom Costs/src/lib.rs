@@ -64,6 +64,87 @@ pub fn edit_distance(s1: &str, s2: &str, costs: &EditCosts) -> usize {
     dp[m][n]
 }
 
+/// Like `edit_distance`, but gives up as soon as the distance is known to
+/// exceed `max_distance`, returning `None`.
+///
+/// Costs are non-negative, so the smallest value in a DP row is a lower
+/// bound on the final distance; once it passes the threshold no later row
+/// can come back under it. Uses two rows instead of the full table.
+pub fn edit_distance_within(
+    s1: &str,
+    s2: &str,
+    costs: &EditCosts,
+    max_distance: usize,
+) -> Option<usize> {
+    let chars1: Vec<char> = s1.chars().collect();
+    let chars2: Vec<char> = s2.chars().collect();
+    let n = chars2.len();
+
+    let mut prev: Vec<usize> = (0..=n).map(|j| j * costs.insertion).collect();
+    let mut curr = vec![0; n + 1];
+    if prev.iter().min().is_some_and(|&d| d > max_distance) {
+        return None;
+    }
+
+    for (i, &c1) in chars1.iter().enumerate() {
+        curr[0] = (i + 1) * costs.deletion;
+        for j in 1..=n {
+            let sub_cost = if c1 == chars2[j - 1] {
+                0
+            } else {
+                costs.substitution
+            };
+            curr[j] = min(
+                prev[j] + costs.deletion,
+                min(curr[j - 1] + costs.insertion, prev[j - 1] + sub_cost),
+            );
+        }
+        if curr.iter().min().is_some_and(|&d| d > max_distance) {
+            return None;
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+
+    Some(prev[n]).filter(|&d| d <= max_distance)
+}
+
+/// Returns the `k` candidates in `corpus` closest to `query`, nearest first
+/// (ties keep corpus order), each with its distance.
+///
+/// Once `k` candidates are held, each further candidate is only computed up
+/// to the current k-th best distance, so clearly distant words are abandoned
+/// after a few DP rows.
+pub fn closest_matches<'a>(
+    query: &str,
+    corpus: &'a [&'a str],
+    k: usize,
+    costs: &EditCosts,
+) -> Vec<(&'a str, usize)> {
+    let mut best: Vec<(&'a str, usize)> = Vec::with_capacity(k + 1);
+    if k == 0 {
+        return best;
+    }
+
+    for &candidate in corpus {
+        let limit = if best.len() < k {
+            usize::MAX
+        } else {
+            // Only a strictly smaller distance can displace the k-th best.
+            match best[k - 1].1.checked_sub(1) {
+                Some(limit) => limit,
+                None => break, // k exact matches already found
+            }
+        };
+        if let Some(distance) = edit_distance_within(query, candidate, costs, limit) {
+            let pos = best.partition_point(|&(_, d)| d <= distance);
+            best.insert(pos, (candidate, distance));
+            best.truncate(k);
+        }
+    }
+
+    best
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -97,4 +178,41 @@ mod tests {
         let costs = EditCosts::default();
         assert_eq!(edit_distance("hello", "hello", &costs), 0);
     }
+
+    #[test]
+    fn test_edit_distance_within() {
+        let costs = EditCosts::default();
+        assert_eq!(
+            edit_distance_within("kitten", "sitting", &costs, 3),
+            Some(3)
+        );
+        assert_eq!(edit_distance_within("kitten", "sitting", &costs, 2), None);
+        assert_eq!(edit_distance_within("", "abc", &costs, 5), Some(3));
+    }
+
+    #[test]
+    fn test_closest_matches() {
+        let costs = EditCosts::default();
+        let corpus = [
+            "world",
+            "hello",
+            "yellow",
+            "help",
+            "banana",
+            "hero",
+            "helicopter",
+            "he",
+        ];
+
+        let matches = closest_matches("helo", &corpus, 3, &costs);
+        assert_eq!(matches, vec![("hello", 1), ("help", 1), ("hero", 1)]);
+
+        let all = closest_matches("helo", &corpus, corpus.len(), &costs);
+        assert_eq!(all.len(), corpus.len());
+        let rank = |word| all.iter().position(|&(w, _)| w == word).unwrap();
+        for distant in ["banana", "helicopter", "world"] {
+            assert!(rank("hello") < rank(distant));
+            assert!(rank("help") < rank(distant));
+        }
+    }
 }
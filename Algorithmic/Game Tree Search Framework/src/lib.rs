@@ -1,13 +1,30 @@
+use rand::Rng;
+use std::collections::HashMap;
+use std::ops::{Add, Neg, Sub};
+use std::time::{Duration, Instant};
+
 /// A trait representing a game state.
 pub trait GameState: Clone + Sized {
     /// The type of move/action.
     type Action;
     /// The type of player identifier.
     type Player: Copy + PartialEq;
+    /// The type of evaluation scores; `i32` together with `WIN_SCORE` /
+    /// `LOSS_SCORE` works out of the box.
+    type Score: Score;
 
     /// Returns a list of legal moves from the current state.
     fn legal_moves(&self) -> Vec<Self::Action>;
 
+    /// Appends the legal moves to `buf`, which the solver passes in empty.
+    ///
+    /// The solver reuses one buffer per search depth, so overriding this to
+    /// push moves directly (instead of building a fresh `Vec`) removes the
+    /// per-node allocation. The default delegates to `legal_moves`.
+    fn legal_moves_into(&self, buf: &mut Vec<Self::Action>) {
+        buf.extend(self.legal_moves());
+    }
+
     /// Applies a move to the state, returning a new state.
     fn apply(&self, action: &Self::Action) -> Self;
 
@@ -16,10 +33,254 @@ pub trait GameState: Clone + Sized {
 
     /// Returns the score of the state from the perspective of the maximizing player.
     /// Usually positive if maximizing player wins, negative if they lose.
-    fn evaluate(&self, player: Self::Player) -> i32;
+    /// Decided games should score `WIN_SCORE` / `LOSS_SCORE`.
+    fn evaluate(&self, player: Self::Player) -> Self::Score;
 
     /// Returns the player whose turn it is.
     fn current_player(&self) -> Self::Player;
+
+    /// A hash identifying this position, used to look it up in a
+    /// `TranspositionTable`. States that compare equal for the game must get
+    /// the same key, including whose turn it is. The default `None` opts the
+    /// state out of caching.
+    fn hash_key(&self) -> Option<u64> {
+        None
+    }
+
+    /// For chance nodes (dice rolls, card draws), the possible successor
+    /// states with their probabilities, which should sum to 1. Only
+    /// `ExpectiminimaxSolver` consults this; the default `None` marks a
+    /// regular decision node.
+    fn chance_outcomes(&self) -> Option<Vec<(Self, f64)>> {
+        None
+    }
+}
+
+/// A totally ordered score type for the negamax search.
+///
+/// Negation flips the point of view between the players, and `INFINITY`
+/// bounds the alpha-beta window, so no overflow guards are needed as long as
+/// every score the search sees lies strictly between `-INFINITY` and
+/// `INFINITY`. Types with a natural infinity (e.g. a totally ordered `f64`
+/// wrapper) can use it directly.
+pub trait Score: Copy + Ord + Neg<Output = Self> {
+    /// Strictly greater than every score; `-INFINITY` is strictly less.
+    const INFINITY: Self;
+
+    /// Turns a score seen from a position `ply` plies below the root into
+    /// the score backed up to the root. The default keeps it as is; `i32`
+    /// uses this to prefer faster wins and slower losses.
+    fn at_ply(self, ply: u32) -> Self {
+        let _ = ply;
+        self
+    }
+
+    /// Inverse of `at_ply`.
+    fn without_ply(self, ply: u32) -> Self {
+        let _ = ply;
+        self
+    }
+
+    /// Whether this is a forced result that `at_ply` shifts by distance.
+    /// Such scores change between search depths, so aspiration windows are
+    /// not centred on them.
+    fn is_decisive(self) -> bool {
+        false
+    }
+}
+
+/// Score of a won game. `evaluate` should return this (and `LOSS_SCORE` for a
+/// lost game) so the solver can recognise forced results; heuristic scores
+/// must stay well inside the range. Returned values are clamped to
+/// `[LOSS_SCORE, WIN_SCORE]`.
+pub const WIN_SCORE: i32 = 30_000;
+/// Score of a lost game. See `WIN_SCORE`.
+pub const LOSS_SCORE: i32 = -WIN_SCORE;
+
+/// Scores within this many points of `WIN_SCORE`/`LOSS_SCORE` are forced
+/// results and get shortened by one point per ply from the root, so the
+/// solver prefers faster wins and slower losses.
+const MAX_PLY: i32 = 1_000;
+const DECISIVE_SCORE: i32 = WIN_SCORE - MAX_PLY;
+
+impl Score for i32 {
+    /// Strictly outside every (clamped) score, and safely negatable.
+    const INFINITY: i32 = WIN_SCORE + 1;
+
+    /// Clamps into `[LOSS_SCORE, WIN_SCORE]` and moves forced wins and losses
+    /// one point toward zero per ply, so nearer results dominate.
+    /// Saturating arithmetic keeps this safe for any `ply`.
+    fn at_ply(self, ply: u32) -> i32 {
+        let score = self.clamp(LOSS_SCORE, WIN_SCORE);
+        let ply = i32::try_from(ply).unwrap_or(i32::MAX);
+        if score > DECISIVE_SCORE {
+            score.saturating_sub(ply).max(DECISIVE_SCORE + 1)
+        } else if score < -DECISIVE_SCORE {
+            score.saturating_add(ply).min(-DECISIVE_SCORE - 1)
+        } else {
+            score
+        }
+    }
+
+    fn without_ply(self, ply: u32) -> i32 {
+        let ply = i32::try_from(ply).unwrap_or(i32::MAX);
+        if self > DECISIVE_SCORE {
+            self.saturating_add(ply)
+        } else if self < -DECISIVE_SCORE {
+            self.saturating_sub(ply)
+        } else {
+            self
+        }
+    }
+
+    fn is_decisive(self) -> bool {
+        self.abs() > DECISIVE_SCORE
+    }
+}
+
+/// How a cached score relates to the true value of its position.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Bound {
+    Exact,
+    /// The search failed high: the true value is at least the score.
+    Lower,
+    /// The search failed low: the true value is at most the score.
+    Upper,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct TableEntry<S> {
+    depth: u32,
+    score: S,
+    bound: Bound,
+}
+
+/// Cache of searched positions keyed by `GameState::hash_key`, so positions
+/// reached through different move orders are only searched once.
+///
+/// Entries from a search at depth `d` are reused by later searches of that
+/// position at depth `d` or less, so keeping a table across searches of the
+/// same game (e.g. between iterative deepening steps) pays off too.
+pub struct TranspositionTable<S = i32> {
+    entries: HashMap<u64, TableEntry<S>>,
+}
+
+impl<S> Default for TranspositionTable<S> {
+    fn default() -> Self {
+        Self {
+            entries: HashMap::new(),
+        }
+    }
+}
+
+impl<S> TranspositionTable<S> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of cached positions.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// Counters collected during a search.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SearchStats {
+    /// Positions searched below the root.
+    pub nodes: u64,
+    /// Lookups that found a usable entry (deep enough) in the table.
+    pub table_hits: u64,
+    /// Table hits that settled the position without searching it.
+    pub table_cutoffs: u64,
+}
+
+/// Reusable move buffers for the search, one per ply.
+///
+/// Keeping a `SearchScratch` around between searches (see
+/// `MinimaxSolver::find_best_move_with_scratch`) lets move generation reuse
+/// the capacity grown by earlier searches instead of allocating at every node.
+pub struct SearchScratch<A, S = i32> {
+    move_buffers: Vec<Vec<A>>,
+    /// When set, the search gives up once this instant has passed.
+    deadline: Option<Instant>,
+    timed_out: bool,
+    table: Option<TranspositionTable<S>>,
+    stats: SearchStats,
+    /// When set, `pv[ply]` holds the best line (as move indices) found below
+    /// the node currently searched at `ply`.
+    pv: Option<Vec<Vec<usize>>>,
+}
+
+impl<A, S> Default for SearchScratch<A, S> {
+    fn default() -> Self {
+        Self {
+            move_buffers: Vec::new(),
+            deadline: None,
+            timed_out: false,
+            table: None,
+            stats: SearchStats::default(),
+            pv: None,
+        }
+    }
+}
+
+impl<A, S> SearchScratch<A, S> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Takes the (cleared) buffer for `ply`; hand it back with `put_back`.
+    fn take(&mut self, ply: usize) -> Vec<A> {
+        if self.move_buffers.len() <= ply {
+            self.move_buffers.resize_with(ply + 1, Vec::new);
+        }
+        let mut buf = std::mem::take(&mut self.move_buffers[ply]);
+        buf.clear();
+        buf
+    }
+
+    fn put_back(&mut self, ply: usize, buf: Vec<A>) {
+        self.move_buffers[ply] = buf;
+    }
+
+    /// Whether the deadline has passed. Once true it stays true, so every
+    /// level of the search unwinds without consulting the clock again.
+    fn out_of_time(&mut self) -> bool {
+        if !self.timed_out
+            && let Some(deadline) = self.deadline
+        {
+            self.timed_out = Instant::now() >= deadline;
+        }
+        self.timed_out
+    }
+
+    fn clear_line(&mut self, ply: usize) {
+        if let Some(pv) = &mut self.pv {
+            if pv.len() <= ply + 1 {
+                pv.resize_with(ply + 2, Vec::new);
+            }
+            pv[ply].clear();
+        }
+    }
+
+    /// Records move `index` followed by the child's line as the best line at `ply`.
+    fn update_line(&mut self, ply: usize, index: usize) {
+        if let Some(pv) = &mut self.pv {
+            let (head, tail) = pv.split_at_mut(ply + 1);
+            head[ply].clear();
+            head[ply].push(index);
+            head[ply].extend_from_slice(&tail[0]);
+        }
+    }
 }
 
 /// A generic minimax solver with alpha-beta pruning.
@@ -29,98 +290,436 @@ impl MinimaxSolver {
     /// Finds the best move for the current player using minimax with alpha-beta pruning.
     /// `depth` is the maximum search depth.
     pub fn find_best_move<G: GameState>(state: &G, depth: u32) -> Option<G::Action> {
-        let player = state.current_player();
-        let moves = state.legal_moves();
+        Self::find_best_move_with_scratch(state, depth, &mut SearchScratch::new())
+    }
+
+    /// Same as `find_best_move`, but generates moves into the caller's
+    /// `scratch` buffers, so repeated searches don't allocate per node.
+    pub fn find_best_move_with_scratch<G: GameState>(
+        state: &G,
+        depth: u32,
+        scratch: &mut SearchScratch<G::Action, G::Score>,
+    ) -> Option<G::Action> {
+        let mut moves = scratch.take(0);
+        state.legal_moves_into(&mut moves);
+        let best_move = if moves.is_empty() {
+            None
+        } else {
+            let (i, _) = Self::search_root(
+                state,
+                &moves,
+                depth,
+                -G::Score::INFINITY,
+                G::Score::INFINITY,
+                scratch,
+            );
+            Some(moves.swap_remove(i))
+        };
+        scratch.put_back(0, moves);
+        best_move
+    }
 
+    /// Same as `find_best_move`, but also returns the move's negamax score
+    /// from the current player's perspective: near `WIN_SCORE` for a forced
+    /// win, near `LOSS_SCORE` for a forced loss.
+    pub fn find_best_move_scored<G: GameState>(
+        state: &G,
+        depth: u32,
+    ) -> Option<(G::Action, G::Score)> {
+        let mut moves = state.legal_moves();
         if moves.is_empty() {
             return None;
         }
+        let mut scratch = SearchScratch::new();
+        let (i, score) = Self::search_root(
+            state,
+            &moves,
+            depth,
+            -G::Score::INFINITY,
+            G::Score::INFINITY,
+            &mut scratch,
+        );
+        Some((moves.swap_remove(i), score))
+    }
+
+    /// Returns the root's negamax score and the principal variation: the
+    /// line of best play for both sides, starting with the move
+    /// `find_best_move` would pick. The line has at most `depth` moves and
+    /// stops early where the game ends.
+    ///
+    /// A state without legal moves returns `(None, [])`; with `depth == 0`
+    /// the line is empty and the score is the static evaluation.
+    pub fn find_best_line<G: GameState>(
+        state: &G,
+        depth: u32,
+    ) -> (Option<G::Score>, Vec<G::Action>) {
+        let player = state.current_player();
+        let mut moves = state.legal_moves();
+        if moves.is_empty() || state.is_terminal() {
+            return (None, Vec::new());
+        }
+        if depth == 0 {
+            return (Some(Self::leaf_score(state, player, 0)), Vec::new());
+        }
 
-        let mut best_move = None;
-        let mut best_score = i32::MIN + 1; // Avoid overflow when negating MIN
-        let alpha = i32::MIN + 1;
-        let beta = i32::MAX;
+        let mut scratch = SearchScratch::new();
+        scratch.pv = Some(Vec::new());
+        scratch.clear_line(0);
+        let (_, score) = Self::search_root(
+            state,
+            &moves,
+            depth,
+            -G::Score::INFINITY,
+            G::Score::INFINITY,
+            &mut scratch,
+        );
+        let indices = scratch.pv.take().unwrap_or_default().swap_remove(0);
 
-        let mut current_alpha = alpha;
+        // Replay the indices to recover the actions themselves.
+        let mut line = Vec::with_capacity(indices.len());
+        let mut position = state.clone();
+        for index in indices {
+            if !line.is_empty() {
+                moves.clear();
+                position.legal_moves_into(&mut moves);
+            }
+            let action = moves.swap_remove(index);
+            position = position.apply(&action);
+            line.push(action);
+        }
+        (Some(score), line)
+    }
 
-        for m in moves {
-            let next_state = state.apply(&m);
+    /// Same as `find_best_move`, but also reports search statistics and, when
+    /// `table` is given, caches positions in it: a cached score either
+    /// settles a transposed position outright or narrows its alpha-beta
+    /// window.
+    ///
+    /// Only states whose `hash_key` returns `Some` are cached. Note that a
+    /// cached entry from a deeper search is used as-is, so with a depth limit
+    /// the result can differ from (be better informed than) `find_best_move`.
+    pub fn find_best_move_with_stats<G: GameState>(
+        state: &G,
+        depth: u32,
+        table: Option<&mut TranspositionTable<G::Score>>,
+    ) -> (Option<G::Action>, SearchStats) {
+        let mut scratch = SearchScratch::new();
+        match table {
+            Some(table) => {
+                scratch.table = Some(std::mem::take(table));
+                let best_move = Self::find_best_move_with_scratch(state, depth, &mut scratch);
+                *table = scratch.table.take().unwrap_or_default();
+                (best_move, scratch.stats)
+            }
+            None => {
+                let best_move = Self::find_best_move_with_scratch(state, depth, &mut scratch);
+                (best_move, scratch.stats)
+            }
+        }
+    }
 
-            // When calling recursively, if the player changes, we negate the bounds and swap them.
-            // alpha is the best we (current max) can guarantee.
-            // beta is the best the opponent (min) can guarantee.
-            // In recursive call for opponent:
-            // new_alpha = -beta
-            // new_beta = -current_alpha
+    /// Iterative deepening: searches depth 1, 2, ..., `max_depth`, trying the
+    /// previous iteration's best move first at each depth.
+    ///
+    /// With `aspiration_window: Some(delta)`, each depth after the first is
+    /// searched with the narrow window `[prev - delta, prev + delta]` around
+    /// the previous score, which prunes far more than a full window. When the
+    /// result falls outside it (fail-low/fail-high) that side is doubled and
+    /// the depth re-searched, so the chosen move is the same as with `None`
+    /// (always full window). Forced-result scores shift between depths, so
+    /// they are always re-searched with a full window. Window arithmetic
+    /// stays within a few multiples of `Score::INFINITY`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `delta` is not positive.
+    pub fn find_best_move_iterative<G: GameState>(
+        state: &G,
+        max_depth: u32,
+        aspiration_window: Option<G::Score>,
+    ) -> Option<G::Action>
+    where
+        G::Score: Add<Output = G::Score> + Sub<Output = G::Score>,
+    {
+        let infinity = G::Score::INFINITY;
+        let aspiration_window = aspiration_window.map(|delta| {
+            assert!(delta > -delta, "aspiration window must be positive");
+            delta.min(infinity)
+        });
 
-            // Handle overflow if beta is MIN (shouldn't be, but good to be safe)
-            let next_beta = if beta == i32::MIN { i32::MAX } else { -beta };
-            let next_alpha = if current_alpha == i32::MIN {
-                i32::MAX
-            } else {
-                -current_alpha
-            };
+        let mut scratch = SearchScratch::new();
+        let mut moves = state.legal_moves();
+        if moves.is_empty() {
+            return None;
+        }
 
-            let next_player = next_state.current_player();
-            let score = if next_player != player {
-                let recursive_val =
-                    Self::negamax(&next_state, depth - 1, next_beta, next_alpha, next_player);
-                if recursive_val == i32::MIN {
-                    i32::MAX
-                } else {
-                    -recursive_val
+        let mut previous_score: Option<G::Score> = None;
+        for depth in 1..=max_depth.max(1) {
+            let (best, score) = match (aspiration_window, previous_score) {
+                (Some(delta), Some(prev)) if !prev.is_decisive() => {
+                    let (mut below, mut above) = (delta, delta);
+                    loop {
+                        let alpha = (prev - below).max(-infinity);
+                        let beta = (prev + above).min(infinity);
+                        let (i, score) =
+                            Self::search_root(state, &moves, depth, alpha, beta, &mut scratch);
+                        if score <= alpha && alpha > -infinity {
+                            below = below + below;
+                        } else if score >= beta && beta < infinity {
+                            above = above + above;
+                        } else {
+                            break (i, score);
+                        }
+                    }
                 }
-            } else {
-                Self::negamax(&next_state, depth - 1, current_alpha, beta, player)
+                _ => Self::search_root(state, &moves, depth, -infinity, infinity, &mut scratch),
             };
+            moves[..=best].rotate_right(1);
+            previous_score = Some(score);
+        }
+
+        Some(moves.swap_remove(0))
+    }
+
+    /// Iterative deepening against a clock: searches depth 1, 2, ...,
+    /// `max_depth` until `budget` runs out, trying the previous iteration's
+    /// best move first at each depth.
+    ///
+    /// Returns the best move of the deepest iteration that finished in time.
+    /// An iteration interrupted by the deadline is thrown away, since its
+    /// partial result may be worse than the previous one. If not even depth 1
+    /// completes, the first legal move is returned.
+    pub fn find_best_move_timed<G: GameState>(
+        state: &G,
+        max_depth: u32,
+        budget: Duration,
+    ) -> Option<G::Action> {
+        let mut scratch = SearchScratch::new();
+        scratch.deadline = Some(Instant::now() + budget);
+        let mut moves = state.legal_moves();
+        if moves.is_empty() {
+            return None;
+        }
+
+        for depth in 1..=max_depth.max(1) {
+            let (best, _) = Self::search_root(
+                state,
+                &moves,
+                depth,
+                -G::Score::INFINITY,
+                G::Score::INFINITY,
+                &mut scratch,
+            );
+            if scratch.out_of_time() {
+                break;
+            }
+            moves[..=best].rotate_right(1);
+        }
+
+        Some(moves.swap_remove(0))
+    }
+
+    /// Searches the non-empty root `moves` within `(alpha, beta)` and returns
+    /// the index of the first best move and its score. Scores outside the
+    /// window are only bounds, as usual for fail-soft alpha-beta.
+    fn search_root<G: GameState>(
+        state: &G,
+        moves: &[G::Action],
+        depth: u32,
+        mut alpha: G::Score,
+        beta: G::Score,
+        scratch: &mut SearchScratch<G::Action, G::Score>,
+    ) -> (usize, G::Score) {
+        let player = state.current_player();
+        let mut best_index = 0;
+        let mut best_score = -G::Score::INFINITY;
+
+        for (i, m) in moves.iter().enumerate() {
+            let next_state = state.apply(m);
+            let score = Self::child_score(
+                &next_state,
+                depth.saturating_sub(1),
+                alpha,
+                beta,
+                player,
+                1,
+                scratch,
+            );
+            if scratch.timed_out {
+                break;
+            }
 
             if score > best_score {
                 best_score = score;
-                best_move = Some(m);
+                best_index = i;
+                scratch.update_line(0, i);
             }
 
-            if score > current_alpha {
-                current_alpha = score;
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
             }
         }
 
-        best_move
+        (best_index, best_score)
+    }
+
+    /// Picks a root move at random, weighted by a softmax over the moves'
+    /// negamax scores: `P(move) ∝ exp(score / temperature)`.
+    ///
+    /// Low temperatures concentrate on the best moves and high temperatures
+    /// approach uniform play, which is useful for varied, human-like
+    /// opponents. A temperature of 0 (or below) always returns the best move,
+    /// breaking ties the same way as `find_best_move`.
+    pub fn select_move_softmax<G: GameState>(
+        state: &G,
+        depth: u32,
+        temperature: f64,
+        rng: &mut impl Rng,
+    ) -> Option<G::Action>
+    where
+        G::Score: Into<f64>,
+    {
+        let scored = Self::root_scores(state, depth);
+        let best_score = scored.iter().map(|(_, s)| *s).max()?;
+
+        let index = if temperature <= 0.0 {
+            scored.iter().position(|(_, s)| *s == best_score)?
+        } else {
+            // Shift by the best score so the largest weight is exp(0) = 1.
+            let weights: Vec<f64> = scored
+                .iter()
+                .map(|(_, s)| (((*s).into() - best_score.into()) / temperature).exp())
+                .collect();
+            let mut r = rng.random::<f64>() * weights.iter().sum::<f64>();
+            let mut chosen = weights.len() - 1;
+            for (i, w) in weights.iter().enumerate() {
+                if r < *w {
+                    chosen = i;
+                    break;
+                }
+                r -= w;
+            }
+            chosen
+        };
+
+        scored.into_iter().nth(index).map(|(m, _)| m)
+    }
+
+    /// Exact negamax score of every legal root move (full window, no
+    /// pruning across root moves), in `legal_moves` order.
+    fn root_scores<G: GameState>(state: &G, depth: u32) -> Vec<(G::Action, G::Score)> {
+        let player = state.current_player();
+        let mut scratch = SearchScratch::new();
+        state
+            .legal_moves()
+            .into_iter()
+            .map(|m| {
+                let next_state = state.apply(&m);
+                let depth = depth.saturating_sub(1);
+                let score = Self::child_score(
+                    &next_state,
+                    depth,
+                    -G::Score::INFINITY,
+                    G::Score::INFINITY,
+                    player,
+                    1,
+                    &mut scratch,
+                );
+                (m, score)
+            })
+            .collect()
+    }
+
+    /// Scores `next_state` (reached by `player` moving) from `player`'s
+    /// perspective, flipping the window when the turn passes to the opponent.
+    fn child_score<G: GameState>(
+        next_state: &G,
+        depth: u32,
+        alpha: G::Score,
+        beta: G::Score,
+        player: G::Player,
+        ply: u32,
+        scratch: &mut SearchScratch<G::Action, G::Score>,
+    ) -> G::Score {
+        let next_player = next_state.current_player();
+        if next_player != player {
+            -Self::negamax(next_state, depth, -beta, -alpha, next_player, ply, scratch)
+        } else {
+            Self::negamax(next_state, depth, alpha, beta, player, ply, scratch)
+        }
+    }
+
+    /// `evaluate` as seen from the root, `ply` plies up (see `Score::at_ply`).
+    fn leaf_score<G: GameState>(state: &G, player: G::Player, ply: u32) -> G::Score {
+        state.evaluate(player).at_ply(ply)
     }
 
     fn negamax<G: GameState>(
         state: &G,
         depth: u32,
-        mut alpha: i32,
-        beta: i32,
+        mut alpha: G::Score,
+        mut beta: G::Score,
         player: G::Player,
-    ) -> i32 {
+        ply: u32,
+        scratch: &mut SearchScratch<G::Action, G::Score>,
+    ) -> G::Score {
+        if scratch.out_of_time() {
+            // Meaningless, but the caller discards the whole iteration.
+            return alpha;
+        }
+        scratch.stats.nodes += 1;
+        scratch.clear_line(ply as usize);
         if depth == 0 || state.is_terminal() {
-            return state.evaluate(player);
+            return Self::leaf_score(state, player, ply);
         }
 
-        let moves = state.legal_moves();
-        if moves.is_empty() {
-            return state.evaluate(player);
+        let key = scratch.table.as_ref().and_then(|_| state.hash_key());
+        if let (Some(table), Some(key)) = (&scratch.table, key)
+            && let Some(entry) = table.entries.get(&key)
+            && entry.depth >= depth
+        {
+            scratch.stats.table_hits += 1;
+            let score = entry.score.at_ply(ply);
+            match entry.bound {
+                Bound::Exact => alpha = beta,
+                Bound::Lower => alpha = alpha.max(score),
+                Bound::Upper => beta = beta.min(score),
+            }
+            if alpha >= beta {
+                scratch.stats.table_cutoffs += 1;
+                return score;
+            }
         }
+        let window = (alpha, beta);
 
-        let mut value = i32::MIN + 1;
+        let mut moves = scratch.take(ply as usize);
+        state.legal_moves_into(&mut moves);
+        if moves.is_empty() {
+            scratch.put_back(ply as usize, moves);
+            return Self::leaf_score(state, player, ply);
+        }
 
-        for m in moves {
-            let next_state = state.apply(&m);
-            let next_player = next_state.current_player();
+        let mut value = -G::Score::INFINITY;
 
-            let score = if next_player != player {
-                let recursive_val =
-                    Self::negamax(&next_state, depth - 1, -beta, -alpha, next_player);
-                if recursive_val == i32::MIN {
-                    i32::MAX
-                } else {
-                    -recursive_val
-                }
-            } else {
-                Self::negamax(&next_state, depth - 1, alpha, beta, player)
-            };
+        for (i, m) in moves.iter().enumerate() {
+            let next_state = state.apply(m);
+            let score = Self::child_score(
+                &next_state,
+                depth - 1,
+                alpha,
+                beta,
+                player,
+                ply + 1,
+                scratch,
+            );
+            if scratch.timed_out {
+                break;
+            }
 
+            if score > alpha {
+                scratch.update_line(ply as usize, i);
+            }
             value = value.max(score);
             alpha = alpha.max(value);
             if alpha >= beta {
@@ -128,13 +727,106 @@ impl MinimaxSolver {
             }
         }
 
+        scratch.put_back(ply as usize, moves);
+        if let (Some(table), Some(key)) = (&mut scratch.table, key)
+            && !scratch.timed_out
+        {
+            let bound = if value <= window.0 {
+                Bound::Upper
+            } else if value >= window.1 {
+                Bound::Lower
+            } else {
+                Bound::Exact
+            };
+            table.entries.insert(
+                key,
+                TableEntry {
+                    depth,
+                    score: value.without_ply(ply),
+                    bound,
+                },
+            );
+        }
         value
     }
 }
 
+/// Expectiminimax for games with chance nodes (see
+/// `GameState::chance_outcomes`): decision nodes take the best move for the
+/// player to move, chance nodes average their outcomes weighted by
+/// probability.
+///
+/// Expected values rule out alpha-beta cutoffs, so this searches the full
+/// tree to `depth`. Chance nodes don't use up depth. Without chance nodes it
+/// picks the same moves as `MinimaxSolver`.
+pub struct ExpectiminimaxSolver;
+
+impl ExpectiminimaxSolver {
+    /// Finds the move with the best expected score for the current player.
+    /// The root must be a decision node; returns `None` if it has no moves.
+    pub fn find_best_move<G: GameState>(state: &G, depth: u32) -> Option<G::Action>
+    where
+        G::Score: Into<f64>,
+    {
+        let player = state.current_player();
+        let mut best: Option<(G::Action, f64)> = None;
+        for m in state.legal_moves() {
+            let score = Self::value(&state.apply(&m), depth.saturating_sub(1), player, 1);
+            if best
+                .as_ref()
+                .is_none_or(|(_, best_score)| score > *best_score)
+            {
+                best = Some((m, score));
+            }
+        }
+        best.map(|(m, _)| m)
+    }
+
+    /// Expected score of `state` for its current player, searching `depth`
+    /// plies of decisions.
+    pub fn expected_value<G: GameState>(state: &G, depth: u32) -> f64
+    where
+        G::Score: Into<f64>,
+    {
+        Self::value(state, depth, state.current_player(), 0)
+    }
+
+    /// Value of `state` from `player`'s perspective: `player` maximizes,
+    /// everyone else minimizes.
+    fn value<G: GameState>(state: &G, depth: u32, player: G::Player, ply: u32) -> f64
+    where
+        G::Score: Into<f64>,
+    {
+        let leaf = || state.evaluate(player).at_ply(ply).into();
+        if depth == 0 || state.is_terminal() {
+            return leaf();
+        }
+        if let Some(outcomes) = state.chance_outcomes() {
+            return outcomes
+                .iter()
+                .map(|(outcome, p)| p * Self::value(outcome, depth, player, ply))
+                .sum();
+        }
+
+        let maximizing = state.current_player() == player;
+        let scores = state
+            .legal_moves()
+            .into_iter()
+            .map(|m| Self::value(&state.apply(&m), depth - 1, player, ply + 1));
+        let best = if maximizing {
+            scores.fold(f64::NEG_INFINITY, f64::max)
+        } else {
+            scores.fold(f64::INFINITY, f64::min)
+        };
+        if best.is_infinite() { leaf() } else { best }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
 
     #[derive(Clone, Copy, PartialEq, Debug)]
     enum Player {
@@ -187,6 +879,7 @@ mod tests {
     impl GameState for TicTacToe {
         type Action = usize;
         type Player = Player;
+        type Score = i32;
 
         fn legal_moves(&self) -> Vec<usize> {
             if self.check_winner().is_some() {
@@ -225,6 +918,18 @@ mod tests {
         fn current_player(&self) -> Player {
             self.turn
         }
+
+        fn hash_key(&self) -> Option<u64> {
+            // Base-3 digits per cell; the turn follows from the board.
+            Some(self.board.iter().fold(0, |key, cell| {
+                key * 3
+                    + match cell {
+                        None => 0,
+                        Some(Player::X) => 1,
+                        Some(Player::O) => 2,
+                    }
+            }))
+        }
     }
 
     #[test]
@@ -265,5 +970,655 @@ mod tests {
 
         let best_move = MinimaxSolver::find_best_move(&game, 5);
         assert_eq!(best_move, Some(2));
+        assert_eq!(
+            MinimaxSolver::find_best_move_scored(&game, 5),
+            Some((2, 10))
+        );
+    }
+
+    #[test]
+    fn test_softmax_zero_temperature_matches_best_move() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut game = TicTacToe::new();
+        game.board = [
+            Some(Player::X),
+            Some(Player::X),
+            None,
+            None,
+            Some(Player::O),
+            None,
+            None,
+            None,
+            Some(Player::O),
+        ];
+        game.turn = Player::X;
+
+        for depth in 1..=5 {
+            assert_eq!(
+                MinimaxSolver::select_move_softmax(&game, depth, 0.0, &mut rng),
+                MinimaxSolver::find_best_move(&game, depth)
+            );
+        }
+        assert_eq!(
+            MinimaxSolver::select_move_softmax(&TicTacToe::new(), 3, 0.0, &mut rng),
+            MinimaxSolver::find_best_move(&TicTacToe::new(), 3)
+        );
+    }
+
+    #[test]
+    fn test_softmax_high_temperature_varies() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut game = TicTacToe::new();
+        game.board = [
+            Some(Player::X),
+            Some(Player::X),
+            None,
+            None,
+            Some(Player::O),
+            None,
+            None,
+            None,
+            Some(Player::O),
+        ];
+        game.turn = Player::X;
+        let best = MinimaxSolver::find_best_move(&game, 5);
+
+        let picks: Vec<usize> = (0..200)
+            .map(|_| MinimaxSolver::select_move_softmax(&game, 5, 100.0, &mut rng).unwrap())
+            .collect();
+        assert!(picks.contains(&best.unwrap()));
+        assert!(picks.iter().any(|&m| Some(m) != best));
+        assert!(picks.iter().all(|m| game.legal_moves().contains(m)));
+    }
+
+    /// Two routes to the same result: move 1 ends the game at once, move 0
+    /// takes a forced five-ply detour. `root_wins` decides whether the result
+    /// is a win or a loss for the root player.
+    #[derive(Clone)]
+    struct MateRace {
+        at_root: bool,
+        plies_left: u32,
+        turn: Player,
+        root_wins: bool,
+    }
+
+    impl GameState for MateRace {
+        type Action = usize;
+        type Player = Player;
+        type Score = i32;
+
+        fn legal_moves(&self) -> Vec<usize> {
+            if self.at_root {
+                vec![0, 1]
+            } else if self.plies_left > 0 {
+                vec![0]
+            } else {
+                vec![]
+            }
+        }
+
+        fn apply(&self, action: &usize) -> Self {
+            let plies_left = match (self.at_root, action) {
+                (true, 0) => 4,
+                (true, _) => 0,
+                (false, _) => self.plies_left - 1,
+            };
+            MateRace {
+                at_root: false,
+                plies_left,
+                turn: match self.turn {
+                    Player::X => Player::O,
+                    Player::O => Player::X,
+                },
+                root_wins: self.root_wins,
+            }
+        }
+
+        fn is_terminal(&self) -> bool {
+            !self.at_root && self.plies_left == 0
+        }
+
+        fn evaluate(&self, player: Player) -> i32 {
+            if !self.is_terminal() {
+                return 0;
+            }
+            // Deliberately extreme values: the solver must clamp them.
+            if (player == Player::X) == self.root_wins {
+                i32::MAX
+            } else {
+                i32::MIN
+            }
+        }
+
+        fn current_player(&self) -> Player {
+            self.turn
+        }
+    }
+
+    #[test]
+    fn test_prefers_shorter_wins_and_longer_losses() {
+        let race = |root_wins| MateRace {
+            at_root: true,
+            plies_left: 0,
+            turn: Player::X,
+            root_wins,
+        };
+
+        let scores: Vec<i32> = MinimaxSolver::root_scores(&race(true), 50)
+            .into_iter()
+            .map(|(_, s)| s)
+            .collect();
+        assert_eq!(scores, vec![WIN_SCORE - 5, WIN_SCORE - 1]);
+        assert_eq!(MinimaxSolver::find_best_move(&race(true), 50), Some(1));
+
+        let scores: Vec<i32> = MinimaxSolver::root_scores(&race(false), 50)
+            .into_iter()
+            .map(|(_, s)| s)
+            .collect();
+        assert_eq!(scores, vec![LOSS_SCORE + 5, LOSS_SCORE + 1]);
+        assert_eq!(MinimaxSolver::find_best_move(&race(false), 50), Some(0));
+        assert_eq!(
+            MinimaxSolver::find_best_move_scored(&race(false), 50),
+            Some((0, LOSS_SCORE + 5))
+        );
+    }
+
+    #[test]
+    fn test_leaf_score_saturates() {
+        let terminal = MateRace {
+            at_root: false,
+            plies_left: 0,
+            turn: Player::O,
+            root_wins: true,
+        };
+        assert_eq!(
+            MinimaxSolver::leaf_score(&terminal, Player::X, u32::MAX),
+            DECISIVE_SCORE + 1
+        );
+        assert_eq!(
+            MinimaxSolver::leaf_score(&terminal, Player::O, u32::MAX),
+            -DECISIVE_SCORE - 1
+        );
+        assert_eq!(
+            MinimaxSolver::leaf_score(&terminal, Player::X, 0),
+            WIN_SCORE
+        );
+    }
+
+    thread_local! {
+        // (calls to the allocating `legal_moves`, buffer growths in `legal_moves_into`)
+        static MOVE_GEN: std::cell::Cell<(usize, usize)> = const { std::cell::Cell::new((0, 0)) };
+    }
+
+    /// TicTacToe that generates moves straight into the solver's buffer and
+    /// counts how often that buffer had to grow.
+    #[derive(Clone)]
+    struct BufferedTicTacToe(TicTacToe);
+
+    impl GameState for BufferedTicTacToe {
+        type Action = usize;
+        type Player = Player;
+        type Score = i32;
+
+        fn legal_moves(&self) -> Vec<usize> {
+            MOVE_GEN.with(|c| c.set((c.get().0 + 1, c.get().1)));
+            self.0.legal_moves()
+        }
+
+        fn legal_moves_into(&self, buf: &mut Vec<usize>) {
+            let capacity = buf.capacity();
+            if self.0.check_winner().is_none() {
+                buf.extend((0..9).filter(|&i| self.0.board[i].is_none()));
+            }
+            if buf.capacity() != capacity {
+                MOVE_GEN.with(|c| c.set((c.get().0, c.get().1 + 1)));
+            }
+        }
+
+        fn apply(&self, action: &usize) -> Self {
+            BufferedTicTacToe(self.0.apply(action))
+        }
+
+        fn is_terminal(&self) -> bool {
+            self.0.is_terminal()
+        }
+
+        fn evaluate(&self, player: Player) -> i32 {
+            self.0.evaluate(player)
+        }
+
+        fn current_player(&self) -> Player {
+            self.0.turn
+        }
+    }
+
+    #[test]
+    fn test_scratch_buffers_reuse_allocations() {
+        let mut game = TicTacToe::new();
+        game.board[4] = Some(Player::X);
+        game.turn = Player::O;
+        let buffered = BufferedTicTacToe(game.clone());
+        let mut scratch = SearchScratch::new();
+
+        MOVE_GEN.with(|c| c.set((0, 0)));
+        let first = MinimaxSolver::find_best_move_with_scratch(&buffered, 8, &mut scratch);
+        let (legal_moves_calls, first_growths) = MOVE_GEN.with(|c| c.get());
+
+        // Same answer as the allocating path, without ever calling it.
+        assert_eq!(first, MinimaxSolver::find_best_move(&game, 8));
+        assert_eq!(legal_moves_calls, 0);
+        // At most one growth per ply buffer.
+        assert!(first_growths <= 9, "{} growths", first_growths);
+
+        // A second search on the warmed-up scratch never allocates.
+        MOVE_GEN.with(|c| c.set((0, 0)));
+        let second = MinimaxSolver::find_best_move_with_scratch(&buffered, 8, &mut scratch);
+        assert_eq!(second, first);
+        assert_eq!(MOVE_GEN.with(|c| c.get()), (0, 0));
+    }
+
+    thread_local! {
+        static NODES: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+    }
+
+    /// A synthetic game tree: each of the five moves nudges a running score
+    /// by a pseudo-random amount in `[-10, 10]`, so evaluations drift slowly
+    /// with depth the way real heuristic scores do.
+    #[derive(Clone)]
+    struct RandomWalk {
+        seed: u64,
+        score: i32, // from the first player's perspective
+        plies_left: u32,
+        turn: usize,
+    }
+
+    impl GameState for RandomWalk {
+        type Action = u64;
+        type Player = usize;
+        type Score = i32;
+
+        fn legal_moves(&self) -> Vec<u64> {
+            if self.plies_left == 0 {
+                Vec::new()
+            } else {
+                (0..5).collect()
+            }
+        }
+
+        fn apply(&self, action: &u64) -> Self {
+            NODES.with(|n| n.set(n.get() + 1));
+            // splitmix64 step keyed by the move.
+            let mut z = self
+                .seed
+                .wrapping_add((action + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            z ^= z >> 31;
+            RandomWalk {
+                seed: z,
+                score: self.score + (z % 21) as i32 - 10,
+                plies_left: self.plies_left - 1,
+                turn: 1 - self.turn,
+            }
+        }
+
+        fn is_terminal(&self) -> bool {
+            self.plies_left == 0
+        }
+
+        fn evaluate(&self, player: usize) -> i32 {
+            let score = if player == 0 { self.score } else { -self.score };
+            // The side to move is about to pick its best nudge; crediting it
+            // up front keeps scores from swinging with the depth's parity.
+            if player == self.turn {
+                score + 3
+            } else {
+                score - 3
+            }
+        }
+
+        fn current_player(&self) -> usize {
+            self.turn
+        }
+    }
+
+    #[test]
+    fn test_aspiration_windows_reduce_nodes() {
+        let game = RandomWalk {
+            seed: 42,
+            score: 0,
+            plies_left: 12,
+            turn: 0,
+        };
+
+        let search = |window| {
+            NODES.with(|n| n.set(0));
+            let best = MinimaxSolver::find_best_move_iterative(&game, 8, window);
+            (best, NODES.with(|n| n.get()))
+        };
+        let (full_move, full_nodes) = search(None);
+        let (narrow_move, narrow_nodes) = search(Some(4));
+
+        assert_eq!(narrow_move, full_move);
+        assert_eq!(full_move, MinimaxSolver::find_best_move(&game, 8));
+        assert!(
+            narrow_nodes < full_nodes,
+            "aspiration {} vs full {}",
+            narrow_nodes,
+            full_nodes
+        );
+    }
+
+    /// `RandomWalk` whose evaluation takes a while, like a heavy heuristic.
+    #[derive(Clone)]
+    struct SlowWalk(RandomWalk);
+
+    impl GameState for SlowWalk {
+        type Action = u64;
+        type Player = usize;
+        type Score = i32;
+
+        fn legal_moves(&self) -> Vec<u64> {
+            self.0.legal_moves()
+        }
+
+        fn apply(&self, action: &u64) -> Self {
+            SlowWalk(self.0.apply(action))
+        }
+
+        fn is_terminal(&self) -> bool {
+            self.0.is_terminal()
+        }
+
+        fn evaluate(&self, player: usize) -> i32 {
+            std::thread::sleep(Duration::from_micros(200));
+            self.0.evaluate(player)
+        }
+
+        fn current_player(&self) -> usize {
+            self.0.current_player()
+        }
+    }
+
+    #[test]
+    fn test_timed_search_respects_budget() {
+        let game = SlowWalk(RandomWalk {
+            seed: 7,
+            score: 0,
+            plies_left: 30,
+            turn: 0,
+        });
+        // A full depth-30 search would evaluate 5^30 leaves and never end,
+        // so returning at all shows the deadline cut it short. How deep it
+        // got depends on the machine, so only the move itself is checked.
+        let best = MinimaxSolver::find_best_move_timed(&game, 30, Duration::from_millis(50));
+        assert!(game.legal_moves().contains(&best.unwrap()));
+    }
+
+    #[test]
+    fn test_timed_search_with_ample_budget_completes() {
+        let game = RandomWalk {
+            seed: 42,
+            score: 0,
+            plies_left: 12,
+            turn: 0,
+        };
+        assert_eq!(
+            MinimaxSolver::find_best_move_timed(&game, 5, Duration::from_secs(60)),
+            MinimaxSolver::find_best_move(&game, 5)
+        );
+        // Out of time before depth 1 finishes: still some legal move.
+        assert!(
+            MinimaxSolver::find_best_move_timed(&TicTacToe::new(), 9, Duration::ZERO).is_some()
+        );
+    }
+
+    #[test]
+    fn test_transposition_table_reduces_nodes() {
+        let game = TicTacToe::new();
+
+        let (plain_move, plain) = MinimaxSolver::find_best_move_with_stats(&game, 9, None);
+        let mut table = TranspositionTable::new();
+        let (cached_move, cached) =
+            MinimaxSolver::find_best_move_with_stats(&game, 9, Some(&mut table));
+
+        assert_eq!(plain_move, MinimaxSolver::find_best_move(&game, 9));
+        assert_eq!(cached_move, plain_move);
+        assert_eq!((plain.table_hits, plain.table_cutoffs), (0, 0));
+        assert!(cached.table_cutoffs > 0);
+        assert!(
+            cached.nodes < plain.nodes,
+            "with table {} vs without {}",
+            cached.nodes,
+            plain.nodes
+        );
+        assert!(!table.is_empty());
+
+        // A warm table answers the root's children straight away.
+        let (again, warm) = MinimaxSolver::find_best_move_with_stats(&game, 9, Some(&mut table));
+        assert_eq!(again, plain_move);
+        assert!(warm.nodes <= 9, "{} nodes", warm.nodes);
+    }
+
+    #[test]
+    fn test_best_line_starts_with_best_move() {
+        let mut game = TicTacToe::new();
+        game.board = [
+            Some(Player::X),
+            Some(Player::X),
+            None,
+            None,
+            Some(Player::O),
+            None,
+            None,
+            None,
+            Some(Player::O),
+        ];
+        game.turn = Player::X;
+        let (score, line) = MinimaxSolver::find_best_line(&game, 5);
+        assert_eq!(line, vec![2]);
+        assert_eq!(score, Some(10));
+
+        let game = TicTacToe::new();
+        for depth in [1, 4, 9] {
+            let (score, line) = MinimaxSolver::find_best_line(&game, depth);
+            assert_eq!(
+                line.first().copied(),
+                MinimaxSolver::find_best_move(&game, depth)
+            );
+            assert!(line.len() <= depth as usize);
+            if depth == 9 {
+                // Perfect play fills the board for a draw.
+                assert_eq!((score, line.len()), (Some(0), 9));
+            }
+            let end = line.iter().fold(game.clone(), |g, m| {
+                assert!(g.legal_moves().contains(m));
+                g.apply(m)
+            });
+            assert_eq!(end.evaluate(Player::X), 0);
+        }
+
+        let mut won = TicTacToe::new();
+        won.board[..3].fill(Some(Player::O));
+        assert_eq!(MinimaxSolver::find_best_line(&won, 3), (None, vec![]));
+    }
+
+    /// A totally ordered `f64` score with real infinities.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Real(f64);
+
+    impl Eq for Real {}
+
+    impl PartialOrd for Real {
+        fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl Ord for Real {
+        fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+            self.0.total_cmp(&other.0)
+        }
+    }
+
+    impl std::ops::Neg for Real {
+        type Output = Real;
+
+        fn neg(self) -> Real {
+            Real(-self.0)
+        }
+    }
+
+    impl Score for Real {
+        const INFINITY: Real = Real(f64::INFINITY);
+    }
+
+    /// TicTacToe scored as the winner's share of the remaining board.
+    #[derive(Clone)]
+    struct RealTicTacToe(TicTacToe);
+
+    impl GameState for RealTicTacToe {
+        type Action = usize;
+        type Player = Player;
+        type Score = Real;
+
+        fn legal_moves(&self) -> Vec<usize> {
+            self.0.legal_moves()
+        }
+
+        fn apply(&self, action: &usize) -> Self {
+            RealTicTacToe(self.0.apply(action))
+        }
+
+        fn is_terminal(&self) -> bool {
+            self.0.is_terminal()
+        }
+
+        fn evaluate(&self, player: Player) -> Real {
+            let empty = self.0.board.iter().filter(|c| c.is_none()).count() as f64;
+            Real(self.0.evaluate(player) as f64 * (1.0 + empty) / 10.0)
+        }
+
+        fn current_player(&self) -> Player {
+            self.0.turn
+        }
+    }
+
+    #[test]
+    fn test_custom_score_type() {
+        let mut game = TicTacToe::new();
+        game.board = [
+            Some(Player::X),
+            Some(Player::O),
+            Some(Player::X),
+            Some(Player::O),
+            Some(Player::O),
+            None,
+            None,
+            None,
+            None,
+        ];
+        game.turn = Player::X;
+        let real = RealTicTacToe(game.clone());
+
+        assert_eq!(MinimaxSolver::find_best_move(&real, 5), Some(5));
+        assert_eq!(
+            MinimaxSolver::find_best_move(&real, 5),
+            MinimaxSolver::find_best_move(&game, 5)
+        );
+
+        // X completes the right column, leaving one empty cell: 10 * 2 / 10.
+        let mut win = game.clone();
+        win.board[5] = Some(Player::X);
+        win.board[6] = Some(Player::O);
+        let (_, score) = MinimaxSolver::find_best_move_scored(&RealTicTacToe(win), 3).unwrap();
+        assert_eq!(score, Real(2.0));
+    }
+
+    /// One decision: bank `safe` points, or roll a die and score the face.
+    #[derive(Clone)]
+    enum DiceGame {
+        Start { safe: i32 },
+        Rolling,
+        Done(i32),
+    }
+
+    impl GameState for DiceGame {
+        type Action = bool; // true = roll
+        type Player = u8;
+        type Score = i32;
+
+        fn legal_moves(&self) -> Vec<bool> {
+            match self {
+                DiceGame::Start { .. } => vec![false, true],
+                _ => vec![],
+            }
+        }
+
+        fn apply(&self, roll: &bool) -> Self {
+            match (self, roll) {
+                (DiceGame::Start { safe }, false) => DiceGame::Done(*safe),
+                _ => DiceGame::Rolling,
+            }
+        }
+
+        fn is_terminal(&self) -> bool {
+            matches!(self, DiceGame::Done(_))
+        }
+
+        fn evaluate(&self, _player: u8) -> i32 {
+            match self {
+                DiceGame::Done(points) => *points,
+                _ => 0,
+            }
+        }
+
+        fn current_player(&self) -> u8 {
+            0
+        }
+
+        fn chance_outcomes(&self) -> Option<Vec<(Self, f64)>> {
+            match self {
+                DiceGame::Rolling => Some(
+                    (1..=6)
+                        .map(|face| (DiceGame::Done(face), 1.0 / 6.0))
+                        .collect(),
+                ),
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn test_expectiminimax_averages_chance_nodes() {
+        assert!((ExpectiminimaxSolver::expected_value(&DiceGame::Rolling, 1) - 3.5).abs() < 1e-12);
+
+        let cautious = DiceGame::Start { safe: 3 };
+        assert_eq!(
+            ExpectiminimaxSolver::find_best_move(&cautious, 2),
+            Some(true)
+        );
+        assert!((ExpectiminimaxSolver::expected_value(&cautious, 2) - 3.5).abs() < 1e-12);
+
+        let greedy = DiceGame::Start { safe: 4 };
+        assert_eq!(
+            ExpectiminimaxSolver::find_best_move(&greedy, 2),
+            Some(false)
+        );
+        assert_eq!(ExpectiminimaxSolver::expected_value(&greedy, 2), 4.0);
+    }
+
+    #[test]
+    fn test_expectiminimax_without_chance_matches_minimax() {
+        let mut game = TicTacToe::new();
+        game.board[0] = Some(Player::X);
+        game.board[4] = Some(Player::O);
+        game.turn = Player::X;
+        for depth in [1, 3, 7] {
+            assert_eq!(
+                ExpectiminimaxSolver::find_best_move(&game, depth),
+                MinimaxSolver::find_best_move(&game, depth)
+            );
+        }
     }
 }
@@ -1,18 +1,86 @@
 use std::cmp::min;
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::Hash;
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct NodeId(pub usize);
 
+/// Interns arbitrary labels (names, coordinates, ...) as dense `NodeId`s,
+/// so a graph can be built from the caller's own identifiers without
+/// hand-assigning numbers, and results can be mapped back to labels.
+#[derive(Clone, Debug)]
+pub struct NodeRegistry<L> {
+    ids: HashMap<L, NodeId>,
+    labels: Vec<L>,
+}
+
+impl<L> Default for NodeRegistry<L> {
+    fn default() -> Self {
+        Self {
+            ids: HashMap::new(),
+            labels: Vec::new(),
+        }
+    }
+}
+
+impl<L: Hash + Eq + Clone> NodeRegistry<L> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the id for `label`, allocating the next free id (0, 1, 2, ...)
+    /// the first time a label is seen.
+    pub fn id_of(&mut self, label: L) -> NodeId {
+        if let Some(id) = self.ids.get(&label) {
+            return id.clone();
+        }
+        let id = NodeId(self.labels.len());
+        self.labels.push(label.clone());
+        self.ids.insert(label, id.clone());
+        id
+    }
+
+    /// Looks up a label's id without allocating one.
+    pub fn get(&self, label: &L) -> Option<NodeId> {
+        self.ids.get(label).cloned()
+    }
+
+    /// The label an id was allocated for, if it came from this registry.
+    pub fn label_of(&self, id: &NodeId) -> Option<&L> {
+        self.labels.get(id.0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+}
+
 #[derive(Clone, Debug)]
 struct Edge {
     to: NodeId,
     capacity: i32,
     flow: i32,
+    cost: i64,       // Per unit of flow; the reverse edge has the negated cost
     rev_edge: usize, // Index of reverse edge in adjacency list of `to`
+    reverse: bool,   // Residual counterpart of an edge the caller added
+}
+
+/// Work counters for one `dinic` run.
+#[derive(Debug, Default)]
+struct DinicStats {
+    phases: usize,
+    /// Times a current-arc pointer moved past an edge.
+    arc_advances: usize,
+    /// Total edges over all augmenting paths.
+    path_edges: usize,
 }
 
-/// Edmonds-Karp algorithm implementation for Max Flow.
+/// Flow network over a residual graph: max flow (Edmonds-Karp, Dinic) and
+/// min-cost flow.
 #[derive(Default)]
 pub struct MaxFlow {
     adj: HashMap<NodeId, Vec<Edge>>,
@@ -26,6 +94,12 @@ impl MaxFlow {
     /// Adds a directed edge with capacity.
     /// Automatically adds a reverse edge with 0 capacity for residual graph.
     pub fn add_edge(&mut self, u: NodeId, v: NodeId, cap: i32) {
+        self.add_edge_with_cost(u, v, cap, 0);
+    }
+
+    /// Adds a directed edge with capacity and a cost per unit of flow, for
+    /// the min-cost flow algorithms.
+    pub fn add_edge_with_cost(&mut self, u: NodeId, v: NodeId, cap: i32, cost: i64) {
         let u_idx = self.adj.entry(u.clone()).or_default().len();
         let v_idx = self.adj.entry(v.clone()).or_default().len();
 
@@ -33,18 +107,83 @@ impl MaxFlow {
             to: v.clone(),
             capacity: cap,
             flow: 0,
+            cost,
             rev_edge: v_idx,
+            reverse: false,
         });
 
         self.adj.get_mut(&v).unwrap().push(Edge {
             to: u,
             capacity: 0, // Reverse edge has 0 capacity in original graph
             flow: 0,
+            cost: -cost,
             rev_edge: u_idx,
+            reverse: true,
         });
     }
 
+    /// Maximum matching of a bipartite graph with `left` and `right` nodes,
+    /// given `(l, r)` edges with `l < left` and `r < right`. Returns the
+    /// matched pairs, sorted.
+    ///
+    /// Builds the usual unit-capacity network (a source feeding every left
+    /// node, every right node draining into a sink) and runs `dinic`; each
+    /// left-to-right edge carrying flow is a matched pair.
+    pub fn bipartite_matching(
+        left: usize,
+        right: usize,
+        edges: &[(usize, usize)],
+    ) -> Vec<(usize, usize)> {
+        let mut graph = MaxFlow::new();
+        let (source, sink) = (NodeId(0), NodeId(1 + left + right));
+        let left_node = |l: usize| NodeId(1 + l);
+        let right_node = |r: usize| NodeId(1 + left + r);
+        for l in 0..left {
+            graph.add_edge(source.clone(), left_node(l), 1);
+        }
+        for r in 0..right {
+            graph.add_edge(right_node(r), sink.clone(), 1);
+        }
+        for &(l, r) in edges {
+            assert!(l < left && r < right, "edge ({l}, {r}) out of range");
+            graph.add_edge(left_node(l), right_node(r), 1);
+        }
+        graph.dinic(source.clone(), sink.clone());
+
+        let mut pairs: Vec<_> = graph
+            .edge_flows()
+            .into_iter()
+            .filter(|(u, v, f)| *f > 0 && *u != source && *v != sink)
+            .map(|(u, v, _)| (u.0 - 1, v.0 - 1 - left))
+            .collect();
+        pairs.sort();
+        pairs
+    }
+
+    /// Zeroes the flow on every edge, keeping the edges and capacities.
+    ///
+    /// The algorithms all start from the flow already in the graph, so a
+    /// second run only finds what the first left over; call this in between
+    /// to solve from scratch (e.g. after adding edges).
+    pub fn reset_flows(&mut self) {
+        for edge in self.adj.values_mut().flatten() {
+            edge.flow = 0;
+        }
+    }
+
     pub fn edmonds_karp(&mut self, source: NodeId, sink: NodeId) -> i32 {
+        self.edmonds_karp_with_hook(source, sink, |_, _| {})
+    }
+
+    /// Same as `edmonds_karp`, but calls `on_augment` with every augmenting
+    /// path (source first, sink last) and the flow pushed along it, in the
+    /// order they are found. Useful for visualising the algorithm.
+    pub fn edmonds_karp_with_hook(
+        &mut self,
+        source: NodeId,
+        sink: NodeId,
+        mut on_augment: impl FnMut(&[NodeId], i32),
+    ) -> i32 {
         let mut max_flow = 0;
 
         loop {
@@ -79,16 +218,20 @@ impl MaxFlow {
 
             // Find bottleneck capacity
             let mut path_flow = i32::MAX;
+            let mut path = vec![sink.clone()];
             let mut curr = sink.clone();
             while curr != source {
                 if let Some(Some((prev, edge_idx))) = parent.get(&curr) {
                     let edge = &self.adj[prev][*edge_idx];
                     path_flow = min(path_flow, edge.capacity - edge.flow);
                     curr = prev.clone();
+                    path.push(curr.clone());
                 } else {
                     panic!("Broken path reconstruction");
                 }
             }
+            path.reverse();
+            on_augment(&path, path_flow);
 
             // Update residual capacities
             max_flow += path_flow;
@@ -111,6 +254,399 @@ impl MaxFlow {
 
         max_flow
     }
+
+    /// Dinic's algorithm: `O(V^2 E)`, usually much faster than Edmonds-Karp
+    /// on large graphs.
+    ///
+    /// Each phase builds a BFS level graph and pushes a blocking flow along
+    /// it. Every node keeps a current-arc pointer to the next edge worth
+    /// trying; it only moves forward within a phase (a skipped edge cannot
+    /// become useful again until the levels are rebuilt), so a phase scans
+    /// each edge at most once beyond the augmenting paths themselves.
+    pub fn dinic(&mut self, source: NodeId, sink: NodeId) -> i32 {
+        self.dinic_with_stats(source, sink).0
+    }
+
+    fn dinic_with_stats(&mut self, source: NodeId, sink: NodeId) -> (i32, DinicStats) {
+        let mut stats = DinicStats::default();
+        let mut max_flow = 0;
+        if source == sink {
+            return (max_flow, stats);
+        }
+
+        loop {
+            // BFS level graph over edges with residual capacity.
+            let mut level = HashMap::new();
+            let mut queue = VecDeque::new();
+            level.insert(source.clone(), 0);
+            queue.push_back(source.clone());
+            while let Some(u) = queue.pop_front() {
+                for edge in self.adj.get(&u).into_iter().flatten() {
+                    if edge.capacity > edge.flow && !level.contains_key(&edge.to) {
+                        level.insert(edge.to.clone(), level[&u] + 1);
+                        queue.push_back(edge.to.clone());
+                    }
+                }
+            }
+            if !level.contains_key(&sink) {
+                break;
+            }
+
+            stats.phases += 1;
+            let mut next_arc: HashMap<NodeId, usize> = HashMap::new();
+            loop {
+                let pushed =
+                    self.dinic_push(&source, &sink, i32::MAX, &level, &mut next_arc, &mut stats);
+                if pushed == 0 {
+                    break;
+                }
+                max_flow += pushed;
+            }
+        }
+
+        (max_flow, stats)
+    }
+
+    /// Pushes up to `limit` units from `u` to `sink` along one path of the
+    /// level graph, advancing current-arc pointers past dead edges.
+    fn dinic_push(
+        &mut self,
+        u: &NodeId,
+        sink: &NodeId,
+        limit: i32,
+        level: &HashMap<NodeId, usize>,
+        next_arc: &mut HashMap<NodeId, usize>,
+        stats: &mut DinicStats,
+    ) -> i32 {
+        if u == sink {
+            return limit;
+        }
+        loop {
+            let i = *next_arc.get(u).unwrap_or(&0);
+            let Some(edge) = self.adj.get(u).and_then(|edges| edges.get(i)) else {
+                return 0;
+            };
+            let (to, residual, rev) = (edge.to.clone(), edge.capacity - edge.flow, edge.rev_edge);
+
+            if residual > 0 && level.get(&to) == Some(&(level[u] + 1)) {
+                let pushed =
+                    self.dinic_push(&to, sink, min(limit, residual), level, next_arc, stats);
+                if pushed > 0 {
+                    // The edge may still have capacity left: don't advance.
+                    stats.path_edges += 1;
+                    self.adj.get_mut(u).unwrap()[i].flow += pushed;
+                    self.adj.get_mut(&to).unwrap()[rev].flow -= pushed;
+                    return pushed;
+                }
+            }
+            next_arc.insert(u.clone(), i + 1);
+            stats.arc_advances += 1;
+        }
+    }
+
+    /// Min-cost max flow by successive shortest paths: repeatedly augments
+    /// along the cheapest residual source-to-sink path (Bellman-Ford, since
+    /// residual edges have negative costs). Returns `(flow, total cost)`.
+    ///
+    /// Assumes the graph starts without negative-cost cycles.
+    pub fn min_cost_max_flow(&mut self, source: NodeId, sink: NodeId) -> (i32, i64) {
+        let mut flow = 0;
+        while let Some(path) = self.cheapest_residual_path(&source, &sink) {
+            flow += self.augment(&path);
+        }
+        (flow, self.flow_cost())
+    }
+
+    /// Min-cost max flow by cycle canceling: finds any maximum flow, then
+    /// pushes flow around negative-cost residual cycles (found with
+    /// Bellman-Ford) until none remain, which is exactly when the flow is
+    /// cheapest. Slower than `min_cost_max_flow` but easy to follow.
+    /// Returns `(flow, total cost)`.
+    pub fn min_cost_flow_cycle_canceling(&mut self, source: NodeId, sink: NodeId) -> (i32, i64) {
+        let flow = self.edmonds_karp(source, sink);
+        while let Some(cycle) = self.negative_residual_cycle() {
+            self.augment(&cycle);
+        }
+        (flow, self.flow_cost())
+    }
+
+    /// Total cost of the current flow. Each unit is counted on its edge and,
+    /// with both signs flipped, on the reverse edge, hence the halving.
+    fn flow_cost(&self) -> i64 {
+        let doubled: i64 = self
+            .adj
+            .values()
+            .flatten()
+            .map(|e| e.flow as i64 * e.cost)
+            .sum();
+        doubled / 2
+    }
+
+    /// Pushes the bottleneck amount along `path`, given as (node, edge index)
+    /// hops, and returns it.
+    fn augment(&mut self, path: &[(NodeId, usize)]) -> i32 {
+        let amount = path
+            .iter()
+            .map(|(u, i)| {
+                let edge = &self.adj[u][*i];
+                edge.capacity - edge.flow
+            })
+            .min()
+            .unwrap_or(0);
+        for (u, i) in path {
+            let edge = &mut self.adj.get_mut(u).unwrap()[*i];
+            edge.flow += amount;
+            let (to, rev) = (edge.to.clone(), edge.rev_edge);
+            self.adj.get_mut(&to).unwrap()[rev].flow -= amount;
+        }
+        amount
+    }
+
+    /// Bellman-Ford over residual edges, starting from the nodes already in
+    /// `dist`. Returns the predecessor hop of every reached node, plus a node
+    /// still being improved after `V` rounds, which only happens when a
+    /// negative cycle is reachable.
+    fn residual_bellman_ford(
+        &self,
+        dist: &mut HashMap<NodeId, i64>,
+    ) -> (HashMap<NodeId, (NodeId, usize)>, Option<NodeId>) {
+        let mut pred = HashMap::new();
+        let mut last_relaxed = None;
+        for _ in 0..self.adj.len() {
+            last_relaxed = None;
+            for (u, edges) in &self.adj {
+                let Some(&du) = dist.get(u) else {
+                    continue;
+                };
+                for (i, edge) in edges.iter().enumerate() {
+                    if edge.capacity > edge.flow
+                        && dist.get(&edge.to).is_none_or(|&dv| du + edge.cost < dv)
+                    {
+                        dist.insert(edge.to.clone(), du + edge.cost);
+                        pred.insert(edge.to.clone(), (u.clone(), i));
+                        last_relaxed = Some(edge.to.clone());
+                    }
+                }
+            }
+            if last_relaxed.is_none() {
+                break;
+            }
+        }
+        (pred, last_relaxed)
+    }
+
+    /// Cheapest source-to-sink path in the residual graph, as hops.
+    fn cheapest_residual_path(
+        &self,
+        source: &NodeId,
+        sink: &NodeId,
+    ) -> Option<Vec<(NodeId, usize)>> {
+        let mut dist = HashMap::from([(source.clone(), 0)]);
+        let (pred, _) = self.residual_bellman_ford(&mut dist);
+        if source == sink || !pred.contains_key(sink) {
+            return None;
+        }
+        let mut path = Vec::new();
+        let mut curr = sink.clone();
+        while curr != *source {
+            let (prev, i) = pred[&curr].clone();
+            path.push((prev.clone(), i));
+            curr = prev;
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Some negative-cost cycle of the residual graph, as hops.
+    fn negative_residual_cycle(&self) -> Option<Vec<(NodeId, usize)>> {
+        // Starting every node at distance 0 acts like a virtual source
+        // connected to all of them, so any negative cycle is detected.
+        let mut dist: HashMap<NodeId, i64> = self.adj.keys().map(|u| (u.clone(), 0)).collect();
+        let (pred, relaxed) = self.residual_bellman_ford(&mut dist);
+        let mut node = relaxed?;
+        // Walking back V steps from a node improved in round V lands on the cycle.
+        for _ in 0..self.adj.len() {
+            node = pred[&node].0.clone();
+        }
+        let mut cycle = Vec::new();
+        let mut curr = node.clone();
+        loop {
+            let (prev, i) = pred[&curr].clone();
+            cycle.push((prev.clone(), i));
+            curr = prev;
+            if curr == node {
+                break;
+            }
+        }
+        cycle.reverse();
+        Some(cycle)
+    }
+
+    /// The current flow on every edge added with `add_edge` or
+    /// `add_edge_with_cost`, as `(from, to, flow)`, whatever its capacity.
+    /// Reverse residual edges are left out. Sorted by source node, then in
+    /// insertion order.
+    pub fn edge_flows(&self) -> Vec<(NodeId, NodeId, i32)> {
+        let mut flows: Vec<_> = self
+            .adj
+            .iter()
+            .flat_map(|(u, edges)| {
+                edges
+                    .iter()
+                    .filter(|e| !e.reverse)
+                    .map(move |e| (u.clone(), e.to.clone(), e.flow))
+            })
+            .collect();
+        flows.sort_by_key(|(u, _, _)| u.0);
+        flows
+    }
+
+    /// Edges of a minimum cut, once a max flow has been computed: the
+    /// original edges leading from the nodes still reachable from `source`
+    /// in the residual graph to the rest. Their capacities sum to the max
+    /// flow value. Sorted by node ids.
+    pub fn min_cut(&self, source: NodeId) -> Vec<(NodeId, NodeId)> {
+        let mut reachable = HashSet::from([source.clone()]);
+        let mut queue = VecDeque::from([source]);
+        while let Some(u) = queue.pop_front() {
+            for edge in self.adj.get(&u).into_iter().flatten() {
+                if edge.capacity > edge.flow && reachable.insert(edge.to.clone()) {
+                    queue.push_back(edge.to.clone());
+                }
+            }
+        }
+
+        let mut cut: Vec<_> = reachable
+            .iter()
+            .flat_map(|u| {
+                self.adj
+                    .get(u)
+                    .into_iter()
+                    .flatten()
+                    .filter(|e| !e.reverse && !reachable.contains(&e.to))
+                    .map(move |e| (u.clone(), e.to.clone()))
+            })
+            .collect();
+        cut.sort_by_key(|(u, v)| (u.0, v.0));
+        cut
+    }
+
+    /// Splits the current flow into simple source-to-sink paths, each paired
+    /// with the amount of flow it carries; the amounts sum to the flow value.
+    ///
+    /// Only genuine forward flow is considered (positive flow on the edges
+    /// the caller added), never the negative bookkeeping on reverse edges.
+    /// Flow cycles carry nothing from source to sink, so they are cancelled
+    /// first; otherwise a path walk could loop around one and report
+    /// spurious, non-simple paths.
+    pub fn decompose_flow(&self, source: NodeId, sink: NodeId) -> Vec<(Vec<NodeId>, i32)> {
+        let mut flows: HashMap<NodeId, Vec<(NodeId, i32)>> = HashMap::new();
+        for (u, edges) in &self.adj {
+            let out = flows.entry(u.clone()).or_default();
+            for edge in edges.iter().filter(|e| !e.reverse && e.flow > 0) {
+                // Parallel edges are merged so each (u, v) pair appears once.
+                match out.iter_mut().find(|(v, _)| *v == edge.to) {
+                    Some((_, f)) => *f += edge.flow,
+                    None => out.push((edge.to.clone(), edge.flow)),
+                }
+            }
+        }
+
+        while let Some(cycle) = find_flow_cycle(&flows) {
+            let hops = || (0..cycle.len()).map(|i| (&cycle[i], &cycle[(i + 1) % cycle.len()]));
+            let amount = hops()
+                .map(|(u, v)| flow_between(&flows, u, v))
+                .min()
+                .unwrap();
+            for (u, v) in hops() {
+                add_flow(&mut flows, u, v, -amount);
+            }
+        }
+
+        // The remaining flow is acyclic, so by conservation every walk from
+        // the source along positive flow ends at the sink.
+        let mut paths = Vec::new();
+        loop {
+            let mut path = vec![source.clone()];
+            while *path.last().unwrap() != sink {
+                let next = flows
+                    .get(path.last().unwrap())
+                    .and_then(|out| out.iter().find(|(_, f)| *f > 0));
+                match next {
+                    Some((v, _)) => path.push(v.clone()),
+                    None => break,
+                }
+            }
+            if path.len() == 1 || *path.last().unwrap() != sink {
+                break;
+            }
+            let amount = path
+                .windows(2)
+                .map(|w| flow_between(&flows, &w[0], &w[1]))
+                .min()
+                .unwrap();
+            for w in path.windows(2) {
+                add_flow(&mut flows, &w[0], &w[1], -amount);
+            }
+            paths.push((path, amount));
+        }
+        paths
+    }
+}
+
+fn flow_between(flows: &HashMap<NodeId, Vec<(NodeId, i32)>>, u: &NodeId, v: &NodeId) -> i32 {
+    flows[u]
+        .iter()
+        .find(|(to, _)| to == v)
+        .map_or(0, |(_, f)| *f)
+}
+
+fn add_flow(flows: &mut HashMap<NodeId, Vec<(NodeId, i32)>>, u: &NodeId, v: &NodeId, delta: i32) {
+    if let Some((_, f)) = flows.get_mut(u).unwrap().iter_mut().find(|(to, _)| to == v) {
+        *f += delta;
+    }
+}
+
+/// Finds a directed cycle among edges with positive flow, as a node list.
+fn find_flow_cycle(flows: &HashMap<NodeId, Vec<(NodeId, i32)>>) -> Option<Vec<NodeId>> {
+    // `on_stack[n]` is true while `n` is on the DFS path, false once finished.
+    let mut on_stack = HashMap::new();
+    let mut stack = Vec::new();
+    for start in flows.keys() {
+        if !on_stack.contains_key(start)
+            && let Some(cycle) = cycle_dfs(flows, start, &mut on_stack, &mut stack)
+        {
+            return Some(cycle);
+        }
+    }
+    None
+}
+
+fn cycle_dfs(
+    flows: &HashMap<NodeId, Vec<(NodeId, i32)>>,
+    u: &NodeId,
+    on_stack: &mut HashMap<NodeId, bool>,
+    stack: &mut Vec<NodeId>,
+) -> Option<Vec<NodeId>> {
+    on_stack.insert(u.clone(), true);
+    stack.push(u.clone());
+    for (v, _) in flows[u].iter().filter(|(_, f)| *f > 0) {
+        match on_stack.get(v) {
+            Some(true) => {
+                let pos = stack.iter().position(|n| n == v).unwrap();
+                return Some(stack[pos..].to_vec());
+            }
+            Some(false) => {}
+            None => {
+                if let Some(cycle) = cycle_dfs(flows, v, on_stack, stack) {
+                    return Some(cycle);
+                }
+            }
+        }
+    }
+    on_stack.insert(u.clone(), false);
+    stack.pop();
+    None
 }
 
 #[cfg(test)]
@@ -144,4 +680,324 @@ mod tests {
 
         assert_eq!(graph.edmonds_karp(s, t), 12);
     }
+
+    #[test]
+    fn test_min_cut() {
+        let mut graph = MaxFlow::new();
+        let edges = [(0, 1, 10), (0, 2, 10), (1, 2, 2), (1, 3, 4), (2, 3, 8)];
+        for (u, v, cap) in edges {
+            graph.add_edge(NodeId(u), NodeId(v), cap);
+        }
+        assert_eq!(graph.edmonds_karp(NodeId(0), NodeId(3)), 12);
+
+        let cut = graph.min_cut(NodeId(0));
+        assert_eq!(cut, vec![(NodeId(1), NodeId(3)), (NodeId(2), NodeId(3))]);
+        let capacity: i32 = cut
+            .iter()
+            .map(|(u, v)| {
+                let (_, _, cap) = edges
+                    .iter()
+                    .find(|(a, b, _)| (*a, *b) == (u.0, v.0))
+                    .unwrap();
+                cap
+            })
+            .sum();
+        assert_eq!(capacity, 12);
+
+        // A source without edges (e.g. interned but not connected yet) has
+        // an empty cut.
+        assert_eq!(graph.min_cut(NodeId(42)), vec![]);
+        assert_eq!(MaxFlow::new().min_cut(NodeId(0)), vec![]);
+    }
+
+    #[test]
+    fn test_edge_flows_conserve_flow() {
+        let mut graph = MaxFlow::new();
+        for (u, v, cap) in [(0, 1, 10), (0, 2, 10), (1, 2, 2), (1, 3, 4), (2, 3, 8)] {
+            graph.add_edge(NodeId(u), NodeId(v), cap);
+        }
+        graph.edmonds_karp(NodeId(0), NodeId(3));
+
+        let flows = graph.edge_flows();
+        assert_eq!(flows.len(), 5);
+        let net_out = |n: usize| -> i32 {
+            flows
+                .iter()
+                .map(|(u, v, f)| match (u.0 == n, v.0 == n) {
+                    (true, false) => *f,
+                    (false, true) => -f,
+                    _ => 0,
+                })
+                .sum()
+        };
+        assert_eq!(net_out(0), 12);
+        assert_eq!(net_out(1), 0);
+        assert_eq!(net_out(2), 0);
+        assert_eq!(net_out(3), -12);
+
+        // An edge added with no capacity is still listed, with no flow.
+        graph.add_edge(NodeId(3), NodeId(0), 0);
+        let flows = graph.edge_flows();
+        assert_eq!(flows.len(), 6);
+        assert!(flows.contains(&(NodeId(3), NodeId(0), 0)));
+        assert!(flows.iter().all(|(_, _, f)| *f >= 0));
+    }
+
+    #[test]
+    fn test_reset_flows_allows_resolving() {
+        let mut graph = MaxFlow::new();
+        for (u, v, cap) in [(0, 1, 10), (0, 2, 10), (1, 2, 2), (1, 3, 4), (2, 3, 8)] {
+            graph.add_edge(NodeId(u), NodeId(v), cap);
+        }
+        assert_eq!(graph.edmonds_karp(NodeId(0), NodeId(3)), 12);
+        assert_eq!(graph.edmonds_karp(NodeId(0), NodeId(3)), 0);
+
+        graph.reset_flows();
+        assert!(graph.edge_flows().iter().all(|(_, _, f)| *f == 0));
+        assert_eq!(graph.edmonds_karp(NodeId(0), NodeId(3)), 12);
+        graph.reset_flows();
+        assert_eq!(graph.dinic(NodeId(0), NodeId(3)), 12);
+    }
+
+    #[test]
+    fn test_bipartite_matching() {
+        // Worker 0 can only do job 1, so worker 1 must take job 0, leaving
+        // job 2 for worker 2; worker 3 has no jobs. The matching is unique.
+        let edges = [(0, 1), (1, 0), (1, 1), (2, 0), (2, 1), (2, 2)];
+        assert_eq!(
+            MaxFlow::bipartite_matching(4, 3, &edges),
+            vec![(0, 1), (1, 0), (2, 2)]
+        );
+
+        assert_eq!(MaxFlow::bipartite_matching(2, 2, &[]), vec![]);
+    }
+
+    #[test]
+    fn test_max_flow_with_named_nodes() {
+        let mut names = NodeRegistry::new();
+        let mut graph = MaxFlow::new();
+        for (from, to, cap) in [
+            ("plant", "depot_a", 10),
+            ("plant", "depot_b", 10),
+            ("depot_a", "depot_b", 2),
+            ("depot_a", "store", 4),
+            ("depot_b", "store", 8),
+        ] {
+            graph.add_edge(names.id_of(from), names.id_of(to), cap);
+        }
+
+        assert_eq!(names.label_of(&NodeId(0)), Some(&"plant"));
+        let (s, t) = (names.id_of("plant"), names.id_of("store"));
+        assert_eq!(graph.edmonds_karp(s, t), 12);
+    }
+
+    #[test]
+    fn test_decompose_flow_cancels_cycles() {
+        let mut graph = MaxFlow::new();
+        let (s, a, b, c, t) = (NodeId(0), NodeId(1), NodeId(2), NodeId(3), NodeId(4));
+        graph.add_edge(s.clone(), a.clone(), 5);
+        graph.add_edge(a.clone(), t.clone(), 5);
+        graph.add_edge(a.clone(), b.clone(), 3);
+        graph.add_edge(b.clone(), c.clone(), 3);
+        graph.add_edge(c.clone(), a.clone(), 3);
+        assert_eq!(graph.edmonds_karp(s.clone(), t.clone()), 5);
+
+        // Route a circulation a -> b -> c -> a on top of the max flow; it is
+        // still a valid optimal flow, but now contains a cycle.
+        for (u, v) in [(&a, &b), (&b, &c), (&c, &a)] {
+            let i = graph.adj[u].iter().position(|e| e.to == *v).unwrap();
+            let edge = &mut graph.adj.get_mut(u).unwrap()[i];
+            edge.flow += 3;
+            let rev = edge.rev_edge;
+            graph.adj.get_mut(v).unwrap()[rev].flow -= 3;
+        }
+
+        let paths = graph.decompose_flow(s.clone(), t.clone());
+        assert_eq!(paths, vec![(vec![s.clone(), a.clone(), t.clone()], 5)]);
+        for (path, _) in &paths {
+            assert_eq!(path.first(), Some(&s));
+            assert_eq!(path.last(), Some(&t));
+            for (i, node) in path.iter().enumerate() {
+                assert!(!path[i + 1..].contains(node));
+            }
+        }
+        assert_eq!(paths.iter().map(|(_, f)| f).sum::<i32>(), 5);
+    }
+
+    #[test]
+    fn test_augment_hook_reports_paths() {
+        let mut graph = MaxFlow::new();
+        let (s, a, b, t) = (NodeId(0), NodeId(1), NodeId(2), NodeId(3));
+        graph.add_edge(s.clone(), a.clone(), 10);
+        graph.add_edge(s.clone(), b.clone(), 10);
+        graph.add_edge(a.clone(), b.clone(), 2);
+        graph.add_edge(a.clone(), t.clone(), 4);
+        graph.add_edge(b.clone(), t.clone(), 8);
+
+        let mut augmentations = Vec::new();
+        let max_flow = graph.edmonds_karp_with_hook(s.clone(), t.clone(), |path, flow| {
+            augmentations.push((path.to_vec(), flow));
+        });
+
+        assert_eq!(max_flow, 12);
+        assert_eq!(augmentations.iter().map(|(_, f)| f).sum::<i32>(), 12);
+        for (path, flow) in &augmentations {
+            assert_eq!(path.first(), Some(&s));
+            assert_eq!(path.last(), Some(&t));
+            assert!(*flow > 0);
+        }
+        // BFS finds the two-edge paths before the longer one.
+        assert!(augmentations[0].0.len() <= augmentations.last().unwrap().0.len());
+    }
+
+    /// `layers` layers of `width` nodes, fully connected between consecutive
+    /// layers, with the source before the first and the sink after the last.
+    fn layered_graph(layers: usize, width: usize) -> (MaxFlow, NodeId, NodeId, usize) {
+        let mut graph = MaxFlow::new();
+        let node = |layer: usize, k: usize| NodeId(1 + layer * width + k);
+        let (s, t) = (NodeId(0), NodeId(1 + layers * width));
+        let mut edges = 0;
+        for k in 0..width {
+            graph.add_edge(s.clone(), node(0, k), 50);
+            graph.add_edge(node(layers - 1, k), t.clone(), 50);
+            edges += 2;
+        }
+        for layer in 0..layers - 1 {
+            for a in 0..width {
+                for b in 0..width {
+                    let cap = ((layer * 31 + a * 17 + b * 7) % 13) as i32 + 1;
+                    graph.add_edge(node(layer, a), node(layer + 1, b), cap);
+                    edges += 1;
+                }
+            }
+        }
+        (graph, s, t, edges)
+    }
+
+    #[test]
+    fn test_dinic_matches_edmonds_karp() {
+        let (mut reference, s, t, _) = layered_graph(6, 8);
+        let expected = reference.edmonds_karp(s.clone(), t.clone());
+
+        let (mut graph, s, t, _) = layered_graph(6, 8);
+        assert_eq!(graph.dinic(s.clone(), t.clone()), expected);
+
+        // Same answer on the small graphs above, and on a second run.
+        let mut complex = MaxFlow::new();
+        for (u, v, cap) in [(0, 1, 10), (0, 2, 10), (1, 2, 2), (1, 3, 4), (2, 3, 8)] {
+            complex.add_edge(NodeId(u), NodeId(v), cap);
+        }
+        assert_eq!(complex.dinic(NodeId(0), NodeId(3)), 12);
+        assert_eq!(complex.dinic(NodeId(0), NodeId(3)), 0);
+    }
+
+    #[test]
+    fn test_dinic_matches_edmonds_karp_on_random_graphs() {
+        // Small LCG so the graphs are reproducible without a rand dependency.
+        let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = |bound: usize| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) as usize % bound
+        };
+
+        let mut total = 0;
+        for _ in 0..50 {
+            let nodes = 10 + next(30);
+            let edges: Vec<_> = (0..nodes * 4)
+                .map(|_| (next(nodes), next(nodes), 1 + next(20) as i32))
+                .filter(|(u, v, _)| u != v)
+                .collect();
+            let build = || {
+                let mut graph = MaxFlow::new();
+                for &(u, v, cap) in &edges {
+                    graph.add_edge(NodeId(u), NodeId(v), cap);
+                }
+                graph
+            };
+            let (s, t) = (NodeId(0), NodeId(nodes - 1));
+            let expected = build().edmonds_karp(s.clone(), t.clone());
+            assert_eq!(build().dinic(s, t), expected, "edges: {:?}", edges);
+            total += expected;
+        }
+        assert!(total > 0);
+    }
+
+    /// Operation-count benchmark: with current arcs, each phase advances past
+    /// every edge (residual arcs included) at most once, so the work is
+    /// bounded by phases * 2E plus the augmenting paths, instead of
+    /// rescanning dead edges on every DFS like a naive blocking flow.
+    #[test]
+    fn test_dinic_current_arc_work_bound() {
+        for (layers, width) in [(4, 6), (8, 10), (12, 16)] {
+            let (mut graph, s, t, edges) = layered_graph(layers, width);
+            let vertices = layers * width + 2;
+            let (flow, stats) = graph.dinic_with_stats(s, t);
+
+            assert!(flow > 0);
+            // Levels strictly grow between phases.
+            assert!(stats.phases < vertices);
+            assert!(
+                stats.arc_advances <= stats.phases * 2 * edges,
+                "{:?} with E = {}",
+                stats,
+                edges
+            );
+            assert!(stats.arc_advances + stats.path_edges <= vertices * vertices * edges);
+        }
+    }
+
+    #[test]
+    fn test_min_cost_flow_prefers_cheaper_route() {
+        let mut graph = MaxFlow::new();
+        let (supply, s, cheap, dear, t) = (NodeId(0), NodeId(1), NodeId(2), NodeId(3), NodeId(4));
+        // Two routes of capacity 5, but only 7 units to send.
+        graph.add_edge_with_cost(supply.clone(), s.clone(), 7, 0);
+        graph.add_edge_with_cost(s.clone(), dear.clone(), 5, 3);
+        graph.add_edge_with_cost(dear.clone(), t.clone(), 5, 3);
+        graph.add_edge_with_cost(s.clone(), cheap.clone(), 5, 1);
+        graph.add_edge_with_cost(cheap.clone(), t.clone(), 5, 1);
+
+        assert_eq!(
+            graph.min_cost_max_flow(supply.clone(), t.clone()),
+            (7, 5 * 2 + 2 * 6)
+        );
+        let mut paths = graph.decompose_flow(supply.clone(), t.clone());
+        paths.sort_by_key(|(_, flow)| -flow);
+        assert_eq!(
+            paths,
+            vec![
+                (vec![supply.clone(), s.clone(), cheap, t.clone()], 5),
+                (vec![supply, s, dear, t], 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cycle_canceling_matches_successive_shortest_paths() {
+        let build = || {
+            let mut graph = MaxFlow::new();
+            let (s, x, a, b, t) = (NodeId(0), NodeId(1), NodeId(2), NodeId(3), NodeId(4));
+            for (u, v, cap, cost) in [
+                (&s, &x, 5, 0),
+                (&x, &t, 5, 10), // Shortest in hops, but expensive
+                (&x, &a, 5, 1),
+                (&a, &t, 3, 1),
+                (&x, &b, 2, 2),
+                (&b, &t, 2, 3),
+                (&a, &b, 1, 0),
+            ] {
+                graph.add_edge_with_cost(u.clone(), v.clone(), cap, cost);
+            }
+            (graph, s, t)
+        };
+
+        // 3 units x-a-t (2 each), 1 unit x-a-b-t (4), 1 unit x-b-t (5).
+        let (mut ssp, s, t) = build();
+        assert_eq!(ssp.min_cost_max_flow(s, t), (5, 15));
+
+        let (mut canceling, s, t) = build();
+        assert_eq!(canceling.min_cost_flow_cycle_canceling(s, t), (5, 15));
+    }
 }
@@ -1,13 +1,92 @@
+use std::collections::HashMap;
 use std::collections::hash_map::DefaultHasher;
-use std::hash::{Hash, Hasher};
+use std::hash::{BuildHasher, BuildHasherDefault, Hash, Hasher};
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Leading bytes of the `to_bytes` format: magic followed by a version.
+const HLL_MAGIC: &[u8; 3] = b"HLL";
+const HLL_FORMAT_VERSION: u8 = 1;
+
+/// A sparse sketch switches to dense registers once it holds more than
+/// `m / SPARSE_DIVISOR` entries; a map entry costs roughly 10 bytes against
+/// one byte per dense register, so past that point sparse stops saving memory.
+const SPARSE_DIVISOR: usize = 16;
+
+/// Dense register array: one byte per register, or 6-bit registers packed
+/// back to back (a register may straddle two bytes).
+#[derive(Clone, Debug)]
+struct Registers {
+    bytes: Vec<u8>,
+    len: usize,
+    packed: bool,
+}
+
+impl Registers {
+    fn new(len: usize, packed: bool) -> Self {
+        let bytes = if packed { (len * 6).div_ceil(8) } else { len };
+        Registers {
+            bytes: vec![0; bytes],
+            len,
+            packed,
+        }
+    }
+
+    fn get(&self, i: usize) -> u8 {
+        if !self.packed {
+            return self.bytes[i];
+        }
+        let (byte, shift) = (i * 6 / 8, i * 6 % 8);
+        let window =
+            self.bytes[byte] as u16 | (*self.bytes.get(byte + 1).unwrap_or(&0) as u16) << 8;
+        ((window >> shift) & 0x3F) as u8
+    }
+
+    fn set(&mut self, i: usize, value: u8) {
+        if !self.packed {
+            self.bytes[i] = value;
+            return;
+        }
+        debug_assert!(
+            value < 64,
+            "register value {} needs more than 6 bits",
+            value
+        );
+        let (byte, shift) = (i * 6 / 8, i * 6 % 8);
+        let mask = 0x3Fu16 << shift;
+        let bits = (value as u16) << shift;
+        self.bytes[byte] = (self.bytes[byte] & !(mask as u8)) | bits as u8;
+        if shift > 2 {
+            // The top bits spill into the next byte.
+            let high = &mut self.bytes[byte + 1];
+            *high = (*high & !((mask >> 8) as u8)) | (bits >> 8) as u8;
+        }
+    }
+
+    fn iter(&self) -> impl Iterator<Item = u8> + '_ {
+        (0..self.len).map(|i| self.get(i))
+    }
+
+    fn to_vec(&self) -> Vec<u8> {
+        self.iter().collect()
+    }
+}
 
 /// HyperLogLog is a probabilistic data structure for estimating the cardinality of a set.
 /// It uses significantly less memory than storing the set itself.
-pub struct HyperLogLog {
-    b: u8,              // Number of bits used for the register index
-    m: usize,           // Number of registers (2^b)
-    registers: Vec<u8>, // The registers storing the max leading zeros
-    alpha_m: f64,       // Correction constant
+///
+/// Items are hashed with `S`, by default a fixed-key `DefaultHasher` so that
+/// separately built sketches agree. Sketches can only be meaningfully merged
+/// or compared when they were built with the same hasher.
+#[derive(Clone, Debug)]
+pub struct HyperLogLog<S = BuildHasherDefault<DefaultHasher>> {
+    b: u8,                // Number of bits used for the register index
+    m: usize,             // Number of registers (2^b)
+    registers: Registers, // The registers storing the max leading zeros (empty while sparse)
+    alpha_m: f64,         // Correction constant
+    // Non-zero registers as index -> rank, used instead of `registers` until
+    // it grows past `m / SPARSE_DIVISOR` entries.
+    sparse: Option<HashMap<u32, u8>>,
+    build_hasher: S,
 }
 
 impl HyperLogLog {
@@ -19,14 +98,34 @@ impl HyperLogLog {
     ///
     /// * `error_rate` - The desired standard error (e.g., 0.01). Must be between 0 and 1.
     pub fn new(error_rate: f64) -> Self {
-        // Formula: error_rate approx 1.04 / sqrt(m)
-        // => m = (1.04 / error_rate)^2
-        // m must be a power of 2, so we find the nearest power of 2.
-        let m_float = (1.04 / error_rate).powi(2);
-        let b = m_float.log2().ceil() as u8;
-
-        // Enforce reasonable bounds for b (e.g., 4..=16)
-        let b = b.clamp(4, 16);
+        HyperLogLog::with_hasher(error_rate, BuildHasherDefault::default())
+    }
+
+    /// Creates a sketch with exactly `2^b` registers, for callers who want to
+    /// pick the precision rather than an error rate. `b` must be in `4..=16`.
+    pub fn with_precision(b: u8) -> Result<HyperLogLog, String> {
+        if !(4..=16).contains(&b) {
+            return Err(format!("Unsupported HyperLogLog precision b = {}", b));
+        }
+        Ok(HyperLogLog::empty(b, BuildHasherDefault::default()))
+    }
+
+    /// Restores a sketch written by `to_bytes`; see `from_bytes_with_hasher`
+    /// for what is rejected.
+    pub fn from_bytes(data: &[u8]) -> Result<HyperLogLog, String> {
+        HyperLogLog::from_bytes_with_hasher(data, BuildHasherDefault::default())
+    }
+}
+
+impl<S: BuildHasher> HyperLogLog<S> {
+    /// Like `new`, but hashes items with `build_hasher`, e.g. a seeded or
+    /// faster hash than SipHash.
+    pub fn with_hasher(error_rate: f64, build_hasher: S) -> Self {
+        HyperLogLog::empty(precision_for(error_rate), build_hasher)
+    }
+
+    /// Creates an empty sketch with `2^b` registers.
+    fn empty(b: u8, build_hasher: S) -> Self {
         let m = 1 << b;
 
         let alpha_m = match m {
@@ -39,73 +138,138 @@ impl HyperLogLog {
         HyperLogLog {
             b,
             m,
-            registers: vec![0; m],
+            registers: Registers::new(0, false),
             alpha_m,
+            sparse: Some(HashMap::new()),
+            build_hasher,
         }
     }
 
-    /// Adds an item to the HyperLogLog sketch.
-    pub fn add<T: Hash + ?Sized>(&mut self, item: &T) {
-        let mut hasher = DefaultHasher::new();
-        item.hash(&mut hasher);
-        let hash = hasher.finish(); // u64 hash
+    /// Stores dense registers in 6 bits each instead of a byte, saving 25% of
+    /// the memory at a small cost per access. Ranks never exceed
+    /// `64 - b + 1 <= 61`, so nothing is lost.
+    pub fn with_packed_registers(mut self) -> Self {
+        if !self.registers.packed {
+            let mut packed = Registers::new(self.registers.len, true);
+            for (i, rank) in self.registers.iter().enumerate() {
+                packed.set(i, rank);
+            }
+            self.registers = packed;
+        }
+        self
+    }
 
-        // Extract the first b bits to determine the register index
-        let j = (hash >> (64 - self.b)) as usize;
+    /// Expected relative standard error of `count`, `1.04 / sqrt(m)`.
+    pub fn relative_error(&self) -> f64 {
+        1.04 / (self.m as f64).sqrt()
+    }
 
-        // Use the remaining bits to count leading zeros
-        // We mask out the first b bits, then count leading zeros + 1
-        // Since we used high bits for index, we look at the lower (64 - b) bits.
-        // Or, technically, HLL usually says "w is the number of leading zeros in the binary representation of the rest".
-        // Since we used the top b bits, we can shift left by b and count leading zeros of the result.
-        // Note: if the remaining bits are all 0, leading_zeros is 64.
-        // But the max rank we can store is usually small (e.g., 5 bits for 32 registers, but we have u8).
-        // We usually define rank = leading_zeros + 1.
+    /// Empties the sketch for reuse, keeping its precision, hasher and
+    /// register allocation (a dense sketch stays dense).
+    pub fn clear(&mut self) {
+        match &mut self.sparse {
+            Some(sparse) => sparse.clear(),
+            None => self.registers.bytes.fill(0),
+        }
+    }
 
-        // Shift hash left by b to remove the index bits from the MSB position.
-        // The relevant bits are now at the beginning of the 64-bit word.
-        let w = hash << self.b;
+    /// True while the sketch stores only its non-zero registers.
+    pub fn is_sparse(&self) -> bool {
+        self.sparse.is_some()
+    }
 
-        // Count leading zeros on the modified hash.
-        // If w is 0, all remaining bits were 0. rank is (64 - b) + 1.
-        // However, trailing bits of the original hash are what matters.
-        // Let's stick to the standard:
-        // x = hash
-        // j = <first b bits>
-        // w = <remaining bits>
-        // rho(w) = position of leftmost 1-bit in w (1-indexed).
+    /// Raises register `j` to at least `rank`, converting to the dense
+    /// representation when the sparse map gets too large.
+    fn set_max(&mut self, j: usize, rank: u8) {
+        match &mut self.sparse {
+            Some(sparse) => {
+                let register = sparse.entry(j as u32).or_insert(0);
+                *register = (*register).max(rank);
+                if sparse.len() > self.m / SPARSE_DIVISOR {
+                    self.densify();
+                }
+            }
+            None => {
+                if rank > self.registers.get(j) {
+                    self.registers.set(j, rank);
+                }
+            }
+        }
+    }
 
-        // In our case, we shifted left, so the "remaining bits" are now at the top.
-        // So leading_zeros() gives the number of 0s before the first 1.
-        // +1 gives the 1-based index.
-        let zeros = w.leading_zeros() as u8;
-        let rank = zeros + 1;
+    fn densify(&mut self) {
+        if let Some(sparse) = self.sparse.take() {
+            let mut registers = Registers::new(self.m, self.registers.packed);
+            for (&j, &rank) in &sparse {
+                registers.set(j as usize, rank);
+            }
+            self.registers = registers;
+        }
+    }
 
-        if rank > self.registers[j] {
-            self.registers[j] = rank;
+    fn dense_registers_from(&self, sparse: &HashMap<u32, u8>) -> Vec<u8> {
+        let mut registers = vec![0; self.m];
+        for (&j, &rank) in sparse {
+            registers[j as usize] = rank;
         }
+        registers
+    }
+
+    /// All `m` registers, whichever representation is in use.
+    fn dense_registers(&self) -> Vec<u8> {
+        match &self.sparse {
+            Some(sparse) => self.dense_registers_from(sparse),
+            None => self.registers.to_vec(),
+        }
+    }
+
+    /// Adds an item to the HyperLogLog sketch.
+    pub fn add<T: Hash + ?Sized>(&mut self, item: &T) {
+        let hash = self.build_hasher.hash_one(item); // u64 hash
+        self.add_hash(hash);
+    }
+
+    /// Updates the registers for an already-hashed item.
+    fn add_hash(&mut self, hash: u64) {
+        let (j, rank) = register_and_rank(hash, self.b);
+        self.set_max(j, rank);
     }
 
     /// Estimates the cardinality of the set.
     pub fn count(&self) -> u64 {
         let m = self.m as f64;
-        let sum_inverse_powers: f64 = self
-            .registers
-            .iter()
-            .map(|&val| 2.0f64.powi(-(val as i32)))
-            .sum();
+        // Each zero register contributes 2^0 = 1 to the harmonic sum.
+        let (sum_inverse_powers, zero_registers) = match &self.sparse {
+            Some(sparse) => (
+                (self.m - sparse.len()) as f64
+                    + sparse
+                        .values()
+                        .map(|&val| 2.0f64.powi(-(val as i32)))
+                        .sum::<f64>(),
+                self.m - sparse.len(),
+            ),
+            None => (
+                self.registers
+                    .iter()
+                    .map(|val| 2.0f64.powi(-(val as i32)))
+                    .sum(),
+                self.registers.iter().filter(|&r| r == 0).count(),
+            ),
+        };
 
         let raw_estimate = self.alpha_m * m * m / sum_inverse_powers;
 
         // Corrections
-        if raw_estimate <= 2.5 * m {
-            // Small range correction
-            let v = self.registers.iter().filter(|&&r| r == 0).count();
-            if v > 0 {
-                (m * (m / v as f64).ln()) as u64
-            } else {
-                raw_estimate as u64
+        if raw_estimate <= 5.0 * m {
+            // HyperLogLog++: linear counting while it is reliable, otherwise
+            // the raw estimate minus its empirically measured bias.
+            if zero_registers > 0 {
+                let linear_count = m * (m / zero_registers as f64).ln();
+                if linear_count <= LINEAR_COUNTING_THRESHOLD[self.b as usize - 4] {
+                    return linear_count as u64;
+                }
             }
+            (raw_estimate - self.estimate_bias(raw_estimate)).max(0.0) as u64
         } else if raw_estimate > (1.0 / 30.0) * 2.0f64.powi(32) {
             // Large range correction (for 32-bit hashes, but we use 64-bit...
             // actually standard correction is for 32-bit limit.
@@ -120,22 +284,805 @@ impl HyperLogLog {
         }
     }
 
+    /// LogLog-Beta estimate: a single formula,
+    /// `alpha_m * m * (m - z) / (beta(z) + sum 2^-M[i])` with `z` the number
+    /// of zero registers, that is smooth over the whole range instead of
+    /// switching between corrections at empirical thresholds. `count` is
+    /// unchanged; this is an alternative to it.
+    pub fn count_beta(&self) -> u64 {
+        let m = self.m as f64;
+        let registers = self.dense_registers();
+        let zeros = registers.iter().filter(|&&r| r == 0).count() as f64;
+        let sum_inverse_powers: f64 = registers.iter().map(|&r| 2.0f64.powi(-(r as i32))).sum();
+
+        let c = &LOGLOG_BETA[self.b as usize - 4];
+        let zl = (zeros + 1.0).ln();
+        let beta = c[0] * zeros + (1..8).map(|k| c[k] * zl.powi(k as i32)).sum::<f64>();
+        (self.alpha_m * m * (m - zeros) / (beta + sum_inverse_powers)).round() as u64
+    }
+
+    /// Bias of a raw estimate, linearly interpolated between the two nearest
+    /// points of the empirical table for this precision.
+    fn estimate_bias(&self, raw_estimate: f64) -> f64 {
+        let raws = RAW_ESTIMATE_DATA[self.b as usize - 4];
+        let biases = BIAS_DATA[self.b as usize - 4];
+        let i = raws.partition_point(|&r| r < raw_estimate);
+        if i == 0 {
+            return biases[0];
+        }
+        if i == raws.len() {
+            return biases[raws.len() - 1];
+        }
+        let t = (raw_estimate - raws[i - 1]) / (raws[i] - raws[i - 1]);
+        biases[i - 1] + t * (biases[i] - biases[i - 1])
+    }
+
     /// Merges another HyperLogLog into this one.
     /// Both must have the same configuration (b/m).
-    pub fn merge(&mut self, other: &HyperLogLog) -> Result<(), String> {
+    ///
+    /// Merging takes the element-wise max of the registers, so it is
+    /// associative, commutative and idempotent: sketches can be combined in
+    /// any order or grouping (e.g. a reduce tree across machines) and the
+    /// result is identical to a single sketch fed every item.
+    pub fn merge(&mut self, other: &HyperLogLog<S>) -> Result<(), String> {
         if self.m != other.m {
             return Err("Cannot merge HyperLogLogs with different precision".to_string());
         }
 
-        for i in 0..self.m {
-            if other.registers[i] > self.registers[i] {
-                self.registers[i] = other.registers[i];
+        match &other.sparse {
+            Some(sparse) => {
+                for (&j, &rank) in sparse {
+                    self.set_max(j as usize, rank);
+                }
+            }
+            None => {
+                self.densify();
+                for i in 0..self.m {
+                    let rank = other.registers.get(i);
+                    if rank > self.registers.get(i) {
+                        self.registers.set(i, rank);
+                    }
+                }
             }
         }
         Ok(())
     }
+
+    /// Estimated size of the intersection, by inclusion-exclusion:
+    /// `|A| + |B| - |A ∪ B|`, with the union built by `union`.
+    ///
+    /// Accuracy caveat: the three estimates' errors add up, and they are
+    /// relative to the *union*, not the intersection. A small overlap
+    /// between large sets can come out as zero or as several times its true
+    /// size; treat the result as rough unless the overlap is a sizeable
+    /// fraction of the union. Fails if the precisions differ.
+    pub fn intersect_count(&self, other: &HyperLogLog<S>) -> Result<u64, String>
+    where
+        S: Clone,
+    {
+        let union = HyperLogLog::union(&[self, other])?;
+        let (a, b) = (self.count(), other.count());
+        Ok((a + b).saturating_sub(union.count()).min(a).min(b))
+    }
+
+    /// Serializes the sketch as `"HLL"`, a format version byte, `b`, and then
+    /// the `2^b` registers, one byte each.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(HLL_MAGIC.len() + 2 + self.m);
+        data.extend_from_slice(HLL_MAGIC);
+        data.push(HLL_FORMAT_VERSION);
+        data.push(self.b);
+        data.extend_from_slice(&self.dense_registers());
+        data
+    }
+
+    /// Restores a sketch written by `to_bytes`. Rejects data with the wrong
+    /// magic or version, an unsupported precision, a register count that
+    /// doesn't match it, or a rank no 64-bit hash could produce.
+    ///
+    /// The hasher is not part of the format: `build_hasher` must match the
+    /// one the sketch was built with.
+    pub fn from_bytes_with_hasher(data: &[u8], build_hasher: S) -> Result<Self, String> {
+        let header = HLL_MAGIC.len() + 2;
+        if data.len() < header || &data[..HLL_MAGIC.len()] != HLL_MAGIC {
+            return Err("Not a serialized HyperLogLog".to_string());
+        }
+        let version = data[HLL_MAGIC.len()];
+        if version != HLL_FORMAT_VERSION {
+            return Err(format!(
+                "Unsupported HyperLogLog format version {}",
+                version
+            ));
+        }
+        let b = data[HLL_MAGIC.len() + 1];
+        if !(4..=16).contains(&b) {
+            return Err(format!("Unsupported HyperLogLog precision b = {}", b));
+        }
+
+        let mut hll = HyperLogLog::empty(b, build_hasher);
+        let registers = &data[header..];
+        if registers.len() != hll.m {
+            return Err(format!(
+                "Expected {} registers, found {}",
+                hll.m,
+                registers.len()
+            ));
+        }
+        if registers.iter().any(|&r| r > 64 - b + 1) {
+            return Err("Register value out of range".to_string());
+        }
+        for (j, &rank) in registers.iter().enumerate() {
+            if rank > 0 {
+                hll.set_max(j, rank);
+            }
+        }
+        Ok(hll)
+    }
+
+    /// A new sketch of the union of `sketches`, leaving the inputs untouched:
+    /// the element-wise max of their registers. Fails on an empty slice or
+    /// if the precisions differ.
+    pub fn union(sketches: &[&HyperLogLog<S>]) -> Result<Self, String>
+    where
+        S: Clone,
+    {
+        let (first, rest) = sketches
+            .split_first()
+            .ok_or_else(|| "Cannot take the union of no HyperLogLogs".to_string())?;
+        if rest.iter().any(|sketch| sketch.b != first.b) {
+            return Err("Cannot merge HyperLogLogs with different precision".to_string());
+        }
+        let mut union = (*first).clone();
+        for sketch in rest {
+            union.merge(sketch)?;
+        }
+        Ok(union)
+    }
+
+    /// Folds any number of equal-precision sketches into one.
+    ///
+    /// Because `merge` is associative and commutative, the order in which
+    /// sketches arrive doesn't affect the result, so partial merges from
+    /// different workers can be combined freely. Fails on an empty input or
+    /// mismatched precisions.
+    pub fn merge_many(sketches: impl IntoIterator<Item = HyperLogLog<S>>) -> Result<Self, String> {
+        let mut sketches = sketches.into_iter();
+        let mut merged = sketches
+            .next()
+            .ok_or_else(|| "Cannot merge an empty set of HyperLogLogs".to_string())?;
+        for sketch in sketches {
+            merged.merge(&sketch)?;
+        }
+        Ok(merged)
+    }
 }
 
+/// Register index and rank for a hash at precision `b`.
+fn register_and_rank(hash: u64, b: u8) -> (usize, u8) {
+    // Extract the first b bits to determine the register index
+    let j = (hash >> (64 - b)) as usize;
+
+    // Use the remaining bits to count leading zeros
+    // We mask out the first b bits, then count leading zeros + 1
+    // Since we used high bits for index, we look at the lower (64 - b) bits.
+    // Or, technically, HLL usually says "w is the number of leading zeros in the binary representation of the rest".
+    // Since we used the top b bits, we can shift left by b and count leading zeros of the result.
+    // Note: if the remaining bits are all 0, leading_zeros is 64.
+    // But the max rank we can store is usually small (e.g., 5 bits for 32 registers, but we have u8).
+    // We usually define rank = leading_zeros + 1.
+
+    // Shift hash left by b to remove the index bits from the MSB position.
+    // The relevant bits are now at the beginning of the 64-bit word.
+    let w = hash << b;
+
+    // Count leading zeros on the modified hash.
+    // If w is 0, all remaining bits were 0. rank is (64 - b) + 1.
+    // However, trailing bits of the original hash are what matters.
+    // Let's stick to the standard:
+    // x = hash
+    // j = <first b bits>
+    // w = <remaining bits>
+    // rho(w) = position of leftmost 1-bit in w (1-indexed).
+
+    // In our case, we shifted left, so the "remaining bits" are now at the top.
+    // So leading_zeros() gives the number of 0s before the first 1.
+    // +1 gives the 1-based index.
+    // Only 64 - b bits remain, so cap the count there: an all-zero tail
+    // gives the maximum rank 64 - b + 1 rather than 65.
+    let zeros = (w.leading_zeros() as u8).min(64 - b);
+    let rank = zeros + 1;
+
+    (j, rank)
+}
+
+/// Number of index bits for a target standard error, clamped to `4..=16`.
+fn precision_for(error_rate: f64) -> u8 {
+    // Formula: error_rate approx 1.04 / sqrt(m)
+    // => m = (1.04 / error_rate)^2
+    // m must be a power of 2, so we find the nearest power of 2.
+    let m_float = (1.04 / error_rate).powi(2);
+    let b = m_float.log2().ceil() as u8;
+
+    // Enforce reasonable bounds for b (e.g., 4..=16)
+    b.clamp(4, 16)
+}
+
+/// A HyperLogLog that several threads can feed at once through `&self`.
+///
+/// Every register is an `AtomicU8` raised with an atomic max, so inserts are
+/// lock-free and never lose an update. It is always dense; `to_hyperloglog`
+/// takes a snapshot for counting, merging or serialization.
+#[derive(Debug)]
+pub struct AtomicHyperLogLog<S = BuildHasherDefault<DefaultHasher>> {
+    b: u8,
+    registers: Vec<AtomicU8>,
+    build_hasher: S,
+}
+
+impl AtomicHyperLogLog {
+    pub fn new(error_rate: f64) -> Self {
+        AtomicHyperLogLog::with_hasher(error_rate, BuildHasherDefault::default())
+    }
+}
+
+impl<S: BuildHasher> AtomicHyperLogLog<S> {
+    pub fn with_hasher(error_rate: f64, build_hasher: S) -> Self {
+        let b = precision_for(error_rate);
+        AtomicHyperLogLog {
+            b,
+            registers: (0..1usize << b).map(|_| AtomicU8::new(0)).collect(),
+            build_hasher,
+        }
+    }
+
+    pub fn add<T: Hash + ?Sized>(&self, item: &T) {
+        let (j, rank) = register_and_rank(self.build_hasher.hash_one(item), self.b);
+        // Compare-and-max: retries internally until the register is at least
+        // `rank`, whatever other threads write in between.
+        self.registers[j].fetch_max(rank, Ordering::Relaxed);
+    }
+
+    /// Estimated cardinality of a snapshot of the registers.
+    pub fn count(&self) -> u64
+    where
+        S: Clone,
+    {
+        self.to_hyperloglog().count()
+    }
+
+    /// Copies the current registers into a plain `HyperLogLog`. Inserts
+    /// racing with the copy may or may not be included.
+    pub fn to_hyperloglog(&self) -> HyperLogLog<S>
+    where
+        S: Clone,
+    {
+        let mut hll = HyperLogLog::empty(self.b, self.build_hasher.clone());
+        for (j, register) in self.registers.iter().enumerate() {
+            let rank = register.load(Ordering::Relaxed);
+            if rank > 0 {
+                hll.set_max(j, rank);
+            }
+        }
+        hll
+    }
+}
+
+/// Count-Min sketch: approximate per-item frequencies in fixed memory.
+///
+/// Estimates never undercount. With `width = ceil(e / epsilon)` and
+/// `depth = ceil(ln(1 / delta))`, an estimate exceeds the true count by more
+/// than `epsilon * N` (N = total additions) with probability at most `delta`.
+#[derive(Clone, Debug)]
+pub struct CountMinSketch {
+    width: usize,
+    depth: usize,
+    counters: Vec<u64>, // depth rows of width counters
+}
+
+impl CountMinSketch {
+    pub fn new(epsilon: f64, delta: f64) -> Self {
+        let width = (std::f64::consts::E / epsilon).ceil().max(1.0) as usize;
+        let depth = (1.0 / delta).ln().ceil().max(1.0) as usize;
+        CountMinSketch {
+            width,
+            depth,
+            counters: vec![0; width * depth],
+        }
+    }
+
+    fn cell<T: Hash + ?Sized>(&self, row: usize, item: &T) -> usize {
+        let mut hasher = DefaultHasher::new();
+        row.hash(&mut hasher);
+        item.hash(&mut hasher);
+        row * self.width + (hasher.finish() % self.width as u64) as usize
+    }
+
+    /// Records one occurrence of `item` and returns its new estimated count.
+    pub fn add<T: Hash + ?Sized>(&mut self, item: &T) -> u64 {
+        let mut estimate = u64::MAX;
+        for row in 0..self.depth {
+            let cell = self.cell(row, item);
+            self.counters[cell] += 1;
+            estimate = estimate.min(self.counters[cell]);
+        }
+        estimate
+    }
+
+    /// Estimated number of times `item` was added (never an undercount).
+    pub fn estimate<T: Hash + ?Sized>(&self, item: &T) -> u64 {
+        (0..self.depth)
+            .map(|row| self.counters[self.cell(row, item)])
+            .min()
+            .unwrap_or(0)
+    }
+}
+
+/// Counts distinct items seen at least `t` times ("heavy distinct elements").
+///
+/// A Count-Min sketch tracks each item's multiplicity, and one HyperLogLog
+/// per threshold `1..=max_threshold` receives the item once its estimated
+/// count reaches that threshold. The error is one-sided on multiplicity:
+/// Count-Min only overestimates, so an item is never missed for being
+/// undercounted, but a light item colliding with heavy ones can be counted
+/// early. On top of that sits the usual HyperLogLog relative error.
+#[derive(Clone, Debug)]
+pub struct ThresholdDistinctCounter {
+    frequencies: CountMinSketch,
+    // levels[i] holds items whose estimated count reached i + 1.
+    levels: Vec<HyperLogLog>,
+}
+
+impl ThresholdDistinctCounter {
+    /// `max_threshold` is the largest `t` that can be queried; `error_rate`
+    /// configures the HyperLogLogs and `epsilon`/`delta` the Count-Min sketch.
+    pub fn new(max_threshold: u64, error_rate: f64, epsilon: f64, delta: f64) -> Self {
+        ThresholdDistinctCounter {
+            frequencies: CountMinSketch::new(epsilon, delta),
+            levels: (0..max_threshold.max(1))
+                .map(|_| HyperLogLog::new(error_rate))
+                .collect(),
+        }
+    }
+
+    pub fn add<T: Hash + ?Sized>(&mut self, item: &T) {
+        let count = self.frequencies.add(item) as usize;
+        let mut hasher = DefaultHasher::new();
+        item.hash(&mut hasher);
+        let hash = hasher.finish();
+        // Other items can push this one's estimate up by more than one step,
+        // so fill every level it has reached; re-adding to an HLL is a no-op.
+        for level in self.levels.iter_mut().take(count) {
+            level.add_hash(hash);
+        }
+    }
+
+    /// Estimated number of distinct items added at least `t` times.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `t` exceeds the `max_threshold` given at construction.
+    pub fn count_at_least(&self, t: u64) -> u64 {
+        assert!(
+            t as usize <= self.levels.len(),
+            "threshold {} exceeds max_threshold {}",
+            t,
+            self.levels.len()
+        );
+        self.levels[t.max(1) as usize - 1].count()
+    }
+}
+
+/// Approximate top-k heavy hitters using the Space-Saving algorithm.
+///
+/// At most `k` items are monitored. An unmonitored arrival replaces the item
+/// with the smallest count and inherits that count (plus one), so reported
+/// counts never underestimate: each is at most `N / k` too high, where N is
+/// the stream length. Any item occurring more than `N / k` times is
+/// guaranteed to be monitored.
+#[derive(Clone, Debug)]
+pub struct TopK<T> {
+    k: usize,
+    // item -> (count, maximum overestimation of count)
+    counters: HashMap<T, (u64, u64)>,
+}
+
+impl<T: Hash + Eq + Clone> TopK<T> {
+    pub fn new(k: usize) -> Self {
+        assert!(k > 0, "TopK needs room for at least one item");
+        TopK {
+            k,
+            counters: HashMap::with_capacity(k),
+        }
+    }
+
+    pub fn add(&mut self, item: T) {
+        if let Some((count, _)) = self.counters.get_mut(&item) {
+            *count += 1;
+            return;
+        }
+        if self.counters.len() < self.k {
+            self.counters.insert(item, (1, 0));
+            return;
+        }
+        let evicted = self
+            .counters
+            .iter()
+            .min_by_key(|(_, (count, _))| *count)
+            .map(|(victim, _)| victim.clone())
+            .unwrap();
+        let (min_count, _) = self.counters.remove(&evicted).unwrap();
+        self.counters.insert(item, (min_count + 1, min_count));
+    }
+
+    /// Monitored items with their (over)estimated counts, most frequent
+    /// first.
+    pub fn top(&self) -> Vec<(T, u64)> {
+        let mut items: Vec<(T, u64)> = self
+            .counters
+            .iter()
+            .map(|(item, (count, _))| (item.clone(), *count))
+            .collect();
+        items.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        items
+    }
+
+    /// How much `item`'s reported count may exceed its true count, or `None`
+    /// if it is not monitored.
+    pub fn error(&self, item: &T) -> Option<u64> {
+        self.counters.get(item).map(|(_, error)| *error)
+    }
+
+    /// Folds in a summary of another stream, as if both streams had been fed
+    /// to one `TopK` (with the same error bound over the combined length).
+    ///
+    /// An item missing from a full summary may still have occurred there up
+    /// to that summary's minimum count times, so that minimum is added to
+    /// keep the counts upper bounds.
+    pub fn merge(&mut self, other: &TopK<T>) {
+        let floor = |topk: &TopK<T>| {
+            if topk.counters.len() < topk.k {
+                0
+            } else {
+                topk.counters
+                    .values()
+                    .map(|(count, _)| *count)
+                    .min()
+                    .unwrap_or(0)
+            }
+        };
+        let (own_floor, other_floor) = (floor(self), floor(other));
+
+        let mut merged: HashMap<T, (u64, u64)> = HashMap::new();
+        for item in self.counters.keys().chain(other.counters.keys()) {
+            if merged.contains_key(item) {
+                continue;
+            }
+            let (c1, e1) = self
+                .counters
+                .get(item)
+                .copied()
+                .unwrap_or((own_floor, own_floor));
+            let (c2, e2) = other
+                .counters
+                .get(item)
+                .copied()
+                .unwrap_or((other_floor, other_floor));
+            merged.insert(item.clone(), (c1 + c2, e1 + e2));
+        }
+
+        let mut entries: Vec<(T, (u64, u64))> = merged.into_iter().collect();
+        entries.sort_by_key(|(_, (count, _))| std::cmp::Reverse(*count));
+        entries.truncate(self.k);
+        self.counters = entries.into_iter().collect();
+    }
+}
+
+/// Largest linear-counting estimate HyperLogLog++ still trusts, per
+/// precision `b = 4..=16` (Heule, Nunkesser & Hall, 2013). Above it the
+/// bias-corrected raw estimate is more accurate.
+const LINEAR_COUNTING_THRESHOLD: [f64; 13] = [
+    10.0, 20.0, 40.0, 80.0, 220.0, 400.0, 900.0, 1800.0, 3100.0, 6500.0, 11500.0, 20000.0, 50000.0,
+];
+
+/// Empirical bias of the raw estimate, per precision `b = 4..=16`:
+/// `RAW_ESTIMATE_DATA[b - 4][i]` is the mean raw estimate observed at 40
+/// evenly spaced cardinalities from 0 to `5.5 * m`, and `BIAS_DATA[b - 4][i]`
+/// the mean amount by which it overshot the true cardinality there.
+///
+/// Generated the way the HyperLogLog++ paper does, by simulation of this
+/// implementation (2000 runs for b <= 8, 500 for b <= 12, 150 above, with
+/// uniformly random 64-bit hashes).
+const RAW_ESTIMATE_DATA: [&[f64]; 13] = [
+    &[
+        10.8, 11.7, 12.7, 13.8, 15.6, 16.8, 18.1, 19.4, 21.5, 23.0, 24.6, 26.1, 28.6, 30.4, 32.2,
+        33.8, 36.5, 38.4, 40.2, 42.2, 45.2, 47.1, 49.1, 51.0, 53.8, 55.7, 57.8, 59.9, 63.0, 65.1,
+        67.2, 69.2, 71.9, 73.9, 75.7, 77.6, 80.6, 82.7, 84.7, 87.8,
+    ],
+    &[
+        22.3, 24.2, 26.8, 29.0, 32.0, 34.4, 37.6, 40.4, 44.1, 47.1, 51.0, 54.2, 58.3, 61.7, 66.1,
+        69.7, 74.3, 78.0, 82.5, 86.1, 90.8, 94.6, 99.5, 103.4, 108.2, 112.2, 117.1, 121.0, 126.0,
+        129.8, 134.8, 139.0, 143.8, 147.9, 153.0, 157.1, 162.1, 166.1, 171.1, 176.2,
+    ],
+    &[
+        45.4, 49.8, 54.5, 59.6, 64.9, 70.5, 76.4, 82.5, 89.0, 95.8, 102.7, 109.8, 117.1, 124.6,
+        132.4, 140.2, 148.2, 156.5, 164.9, 173.2, 182.0, 190.5, 199.3, 208.1, 217.0, 225.8, 234.6,
+        243.4, 252.5, 261.2, 270.5, 279.6, 288.6, 297.5, 306.3, 315.1, 323.9, 333.0, 341.8, 351.5,
+    ],
+    &[
+        91.6, 100.5, 110.0, 120.1, 130.8, 142.1, 153.9, 166.3, 179.3, 192.7, 206.7, 221.2, 236.0,
+        251.1, 266.8, 282.6, 298.8, 315.5, 332.1, 348.9, 366.9, 384.2, 401.5, 418.8, 436.5, 454.1,
+        471.7, 489.6, 507.3, 525.1, 543.3, 561.2, 579.1, 597.2, 615.1, 633.4, 651.4, 669.8, 687.6,
+        706.4,
+    ],
+    &[
+        183.9, 201.7, 220.8, 240.9, 262.3, 284.9, 308.5, 333.2, 358.7, 385.8, 414.1, 442.6, 472.1,
+        502.4, 533.3, 564.9, 596.8, 629.6, 662.9, 696.6, 731.4, 765.6, 800.0, 834.7, 869.7, 905.0,
+        939.5, 975.1, 1010.4, 1046.2, 1082.8, 1118.5, 1154.2, 1190.2, 1225.7, 1262.4, 1298.9,
+        1334.4, 1370.3, 1407.5,
+    ],
+    &[
+        368.5, 404.2, 442.4, 482.9, 525.8, 572.0, 619.7, 668.9, 720.7, 774.3, 830.7, 888.4, 946.8,
+        1006.6, 1068.2, 1131.4, 1195.8, 1262.1, 1327.2, 1393.5, 1461.6, 1530.6, 1598.9, 1667.7,
+        1737.3, 1808.2, 1881.0, 1952.0, 2022.9, 2094.3, 2164.7, 2237.1, 2311.0, 2383.0, 2454.5,
+        2527.7, 2601.2, 2673.5, 2747.8, 2820.2,
+    ],
+    &[
+        737.8, 809.4, 885.6, 967.3, 1052.8, 1144.0, 1238.7, 1337.5, 1441.4, 1547.3, 1658.3, 1772.7,
+        1890.8, 2012.0, 2135.2, 2261.8, 2391.1, 2521.6, 2655.2, 2787.6, 2924.0, 3058.5, 3195.8,
+        3332.9, 3473.0, 3614.9, 3756.0, 3897.2, 4038.5, 4180.0, 4323.9, 4466.1, 4610.4, 4751.8,
+        4893.4, 5039.7, 5179.7, 5323.9, 5470.8, 5617.0,
+    ],
+    &[
+        1476.4, 1619.2, 1772.4, 1935.4, 2107.2, 2288.4, 2477.3, 2675.9, 2882.4, 3097.8, 3320.0,
+        3550.9, 3787.6, 4030.7, 4278.8, 4533.9, 4791.1, 5051.0, 5318.3, 5588.6, 5861.5, 6140.5,
+        6420.1, 6699.8, 6981.9, 7263.1, 7545.8, 7832.0, 8116.2, 8403.1, 8692.3, 8978.6, 9262.5,
+        9550.5, 9841.4, 10130.1, 10421.2, 10714.1, 11001.2, 11289.3,
+    ],
+    &[
+        2953.7, 3240.5, 3546.6, 3871.2, 4213.5, 4575.7, 4953.5, 5350.4, 5763.0, 6192.6, 6636.5,
+        7097.4, 7570.2, 8054.3, 8548.0, 9056.9, 9572.4, 10096.5, 10626.4, 11165.0, 11709.5,
+        12259.7, 12810.9, 13365.1, 13927.8, 14490.8, 15055.9, 15626.6, 16197.9, 16767.9, 17340.3,
+        17918.9, 18498.0, 19071.5, 19653.9, 20233.2, 20816.7, 21395.4, 21968.5, 22546.1,
+    ],
+    &[
+        5908.1, 6482.0, 7094.2, 7742.3, 8427.6, 9148.3, 9906.6, 10700.5, 11527.0, 12387.0, 13273.8,
+        14187.4, 15135.6, 16103.1, 17095.2, 18108.5, 19143.1, 20190.5, 21249.1, 22323.1, 23407.3,
+        24504.0, 25603.6, 26719.8, 27834.0, 28952.6, 30088.7, 31237.2, 32373.3, 33513.7, 34655.7,
+        35805.5, 36932.1, 38077.3, 39226.6, 40372.0, 41523.6, 42669.1, 43819.3, 44978.0,
+    ],
+    &[
+        11817.0, 12966.8, 14191.3, 15488.0, 16863.3, 18312.4, 19835.1, 21426.4, 23083.2, 24801.9,
+        26587.2, 28424.4, 30315.3, 32262.1, 34248.6, 36283.3, 38345.7, 40437.2, 42574.6, 44733.6,
+        46914.1, 49095.6, 51308.6, 53558.6, 55808.8, 58050.7, 60295.6, 62573.7, 64853.8, 67136.4,
+        69424.2, 71726.5, 74033.6, 76341.1, 78650.5, 80937.7, 83250.7, 85576.1, 87864.1, 90182.6,
+    ],
+    &[
+        23634.8, 25931.7, 28377.6, 30975.4, 33720.4, 36609.1, 39648.1, 42821.5, 46133.1, 49568.4,
+        53118.4, 56800.4, 60587.2, 64467.5, 68433.6, 72488.9, 76621.5, 80816.9, 85067.2, 89371.1,
+        93724.1, 98120.9, 102547.1, 107012.1, 111505.6, 116031.8, 120566.5, 125093.4, 129677.3,
+        134240.8, 138826.1, 143402.1, 147977.4, 152598.9, 157217.1, 161820.8, 166445.7, 171051.8,
+        175644.6, 180263.9,
+    ],
+    &[
+        47270.3, 51866.3, 56762.4, 61956.3, 67445.9, 73238.3, 79318.2, 85665.4, 92298.3, 99183.6,
+        106293.0, 113621.4, 121182.1, 128937.1, 136894.2, 144973.9, 153230.3, 161604.0, 170099.8,
+        178708.7, 187413.4, 196168.2, 205031.5, 213963.7, 222992.3, 232004.2, 241066.2, 250167.1,
+        259286.4, 268429.0, 277561.7, 286728.8, 295872.9, 305103.9, 314323.7, 323495.2, 332727.9,
+        341975.8, 351207.6, 360393.2,
+    ],
+];
+
+const BIAS_DATA: [&[f64]; 13] = [
+    &[
+        10.8, 9.7, 8.7, 7.8, 6.6, 5.8, 5.1, 4.4, 3.5, 3.0, 2.6, 2.1, 1.6, 1.4, 1.2, 0.8, 0.5, 0.4,
+        0.2, 0.2, 0.2, 0.1, 0.1, -0.0, -0.2, -0.3, -0.2, -0.1, 0.0, 0.1, 0.2, 0.2, -0.1, -0.1,
+        -0.3, -0.4, -0.4, -0.3, -0.3, -0.2,
+    ],
+    &[
+        22.3, 20.2, 17.8, 16.0, 14.0, 12.4, 10.6, 9.4, 8.1, 7.1, 6.0, 5.2, 4.3, 3.7, 3.1, 2.7, 2.3,
+        2.0, 1.5, 1.1, 0.8, 0.6, 0.5, 0.4, 0.2, 0.2, 0.1, -0.0, 0.0, -0.2, -0.2, -0.0, -0.2, -0.1,
+        -0.0, 0.1, 0.1, 0.1, 0.1, 0.2,
+    ],
+    &[
+        45.4, 40.8, 36.5, 32.6, 28.9, 25.5, 22.4, 19.5, 17.0, 14.8, 12.7, 10.8, 9.1, 7.6, 6.4, 5.2,
+        4.2, 3.5, 2.9, 2.2, 2.0, 1.5, 1.3, 1.1, 1.0, 0.8, 0.6, 0.4, 0.5, 0.2, 0.5, 0.6, 0.6, 0.5,
+        0.3, 0.1, -0.1, -0.0, -0.2, -0.5,
+    ],
+    &[
+        91.6, 82.5, 74.0, 66.1, 58.8, 52.1, 45.9, 40.3, 35.3, 30.7, 26.7, 23.2, 20.0, 17.1, 14.8,
+        12.6, 10.8, 9.5, 8.1, 6.9, 5.9, 5.2, 4.5, 3.8, 3.5, 3.1, 2.7, 2.6, 2.3, 2.1, 2.3, 2.2, 2.1,
+        2.2, 2.1, 2.4, 2.4, 2.8, 2.6, 2.4,
+    ],
+    &[
+        183.9, 165.7, 148.8, 132.9, 118.3, 104.9, 92.5, 81.2, 70.7, 61.8, 53.1, 45.6, 39.1, 33.4,
+        28.3, 23.9, 19.8, 16.6, 13.9, 11.6, 9.4, 7.6, 6.0, 4.7, 3.7, 3.0, 1.5, 1.1, 0.4, 0.2, -0.2,
+        -0.5, -0.8, -0.8, -1.3, -0.6, -0.1, -0.6, -0.7, -0.5,
+    ],
+    &[
+        368.5, 332.2, 298.4, 266.9, 237.8, 211.0, 186.7, 163.9, 143.7, 125.3, 108.7, 94.4, 80.8,
+        68.6, 58.2, 48.4, 40.8, 35.1, 28.2, 22.5, 17.6, 14.6, 10.9, 7.7, 5.3, 3.2, 4.0, 3.0, 1.9,
+        1.3, -1.3, -0.9, 1.0, 1.0, 0.5, 0.7, 2.2, 2.5, 4.8, 4.2,
+    ],
+    &[
+        737.8, 665.4, 597.6, 534.3, 475.8, 422.0, 372.7, 327.5, 286.4, 248.3, 214.3, 184.7, 158.8,
+        135.0, 114.2, 95.8, 81.1, 67.6, 56.2, 44.6, 36.0, 26.5, 18.8, 11.9, 8.0, 4.9, 2.0, -1.8,
+        -4.5, -7.0, -8.1, -9.9, -10.6, -13.2, -15.6, -14.3, -18.3, -19.1, -16.2, -15.0,
+    ],
+    &[
+        1476.4, 1331.2, 1195.4, 1069.4, 952.2, 844.4, 745.3, 654.9, 572.4, 498.8, 432.0, 373.9,
+        322.6, 276.7, 235.8, 201.9, 170.1, 142.0, 120.3, 101.6, 85.5, 75.5, 66.1, 57.8, 50.9, 43.1,
+        36.8, 34.0, 30.2, 28.1, 28.3, 25.6, 20.5, 19.5, 22.4, 22.1, 24.2, 28.1, 26.2, 25.3,
+    ],
+    &[
+        2953.7, 2663.5, 2391.6, 2139.2, 1903.5, 1687.7, 1488.5, 1307.4, 1142.0, 994.6, 860.5,
+        743.4, 639.2, 545.3, 462.0, 392.9, 330.4, 277.5, 229.4, 190.0, 157.5, 129.7, 102.9, 80.1,
+        64.8, 49.8, 37.9, 30.6, 24.9, 16.9, 11.3, 12.9, 14.0, 9.5, 14.9, 16.2, 21.7, 23.4, 18.5,
+        18.1,
+    ],
+    &[
+        5908.1, 5327.0, 4784.2, 4277.3, 3806.6, 3372.3, 2975.6, 2614.5, 2285.0, 1990.0, 1721.8,
+        1479.4, 1272.6, 1085.1, 922.2, 779.5, 659.1, 551.5, 454.1, 373.1, 302.3, 244.0, 187.6,
+        148.8, 108.0, 70.6, 51.7, 45.2, 26.3, 10.7, -2.3, -7.5, -36.9, -46.7, -52.4, -62.0, -66.4,
+        -75.9, -80.7, -78.0,
+    ],
+    &[
+        11817.0, 10656.8, 9570.3, 8557.0, 7621.3, 6760.4, 5972.1, 5253.4, 4599.2, 4006.9, 3482.2,
+        3008.4, 2589.3, 2225.1, 1901.6, 1625.3, 1376.7, 1158.2, 984.6, 833.6, 703.1, 574.6, 476.6,
+        416.6, 355.8, 286.7, 221.6, 188.7, 158.8, 130.4, 108.2, 99.5, 95.6, 93.1, 91.5, 68.7, 70.7,
+        86.1, 63.1, 70.6,
+    ],
+    &[
+        23634.8, 21310.7, 19135.6, 17112.4, 15236.4, 13504.1, 11922.1, 10474.5, 9164.1, 7978.4,
+        6907.4, 5968.4, 5134.2, 4393.5, 3738.6, 3172.9, 2683.5, 2257.9, 1887.2, 1570.1, 1302.1,
+        1077.9, 883.1, 727.1, 598.6, 503.8, 417.5, 323.4, 286.3, 228.8, 193.1, 148.1, 101.4, 101.9,
+        99.1, 81.8, 85.7, 70.8, 42.6, 39.9,
+    ],
+    &[
+        47270.3, 42624.3, 38278.4, 34230.3, 30476.9, 27027.3, 23865.2, 20970.4, 18360.3, 16003.6,
+        13871.0, 11957.4, 10275.1, 8788.1, 7503.2, 6340.9, 5354.3, 4486.0, 3739.8, 3106.7, 2568.4,
+        2081.2, 1702.5, 1392.7, 1178.3, 948.2, 768.2, 627.1, 503.4, 404.0, 294.7, 219.8, 120.9,
+        109.9, 87.7, 17.2, 6.9, 12.8, 2.6, -54.8,
+    ],
+];
+
+/// LogLog-Beta coefficients per precision `b = 4..=16`:
+/// `beta(z) = c[0] * z + sum_{k=1..7} c[k] * ln(z + 1)^k` for `z` zero registers
+/// (Qin, Kim & Tung, 2016). Fitted by weighted least squares on simulated
+/// sketches of this implementation; for b = 14 the fit matches the accuracy
+/// of the coefficients published in the paper.
+const LOGLOG_BETA: [[f64; 8]; 13] = [
+    [
+        -10281.0785861608,
+        10299.0168595099,
+        5045.7625351940,
+        1909.6727335915,
+        214.2895588666,
+        217.5086530473,
+        -30.6471881689,
+        9.3585822865,
+    ],
+    [
+        999.9169004830,
+        -1013.0169027978,
+        -451.2153841528,
+        -245.0697401485,
+        25.1379020620,
+        -40.6995343422,
+        7.2999639757,
+        -1.2990292478,
+    ],
+    [
+        -115.7967903146,
+        116.1719996362,
+        51.2931598854,
+        32.4334018404,
+        -7.1961139733,
+        6.7038103440,
+        -1.2874437198,
+        0.1880165701,
+    ],
+    [
+        -7.5008192872,
+        6.3502629691,
+        4.3251702786,
+        2.1291082749,
+        -1.0309492033,
+        0.7166273588,
+        -0.1416944244,
+        0.0163166064,
+    ],
+    [
+        1.6206088310,
+        -4.5549056804,
+        5.9532712439,
+        -7.1981213008,
+        3.4716355801,
+        -1.0146414688,
+        0.1469339844,
+        -0.0102382238,
+    ],
+    [
+        -3.5301166182,
+        7.0017023357,
+        -9.5050329733,
+        13.1285168261,
+        -6.7122648631,
+        2.0010947555,
+        -0.2902244794,
+        0.0194819053,
+    ],
+    [
+        0.0625640621,
+        -0.7295696154,
+        0.2830609494,
+        -0.7328792874,
+        0.5599353366,
+        -0.2164124503,
+        0.0370550673,
+        -0.0026706185,
+    ],
+    [
+        -1.2692260340,
+        4.5740243113,
+        -9.8441232069,
+        11.9698656048,
+        -6.0850025164,
+        1.6259242352,
+        -0.2113574343,
+        0.0116515639,
+    ],
+    [
+        -0.2136092780,
+        3.2269214487,
+        -5.3766113233,
+        2.5038833072,
+        -0.2348564916,
+        -0.0957336403,
+        0.0245397404,
+        -0.0016533979,
+    ],
+    [
+        -0.4695167359,
+        -8.4379066328,
+        11.4984975809,
+        -5.1553046101,
+        0.8244758771,
+        0.0468419555,
+        -0.0225300675,
+        0.0017245385,
+    ],
+    [
+        -0.3079318057,
+        -6.8894173976,
+        14.3988287881,
+        -10.6989673739,
+        3.7924794476,
+        -0.6709630626,
+        0.0592246685,
+        -0.0020212927,
+    ],
+    [
+        -0.3758228032,
+        -5.4962342861,
+        7.7853936251,
+        -4.6044742574,
+        1.2405906278,
+        -0.1388725631,
+        0.0046703946,
+        0.0003084013,
+    ],
+    [
+        -0.3185254118,
+        -36.9112626716,
+        55.9860487044,
+        -34.0421936886,
+        10.0479429743,
+        -1.5419140705,
+        0.1195047986,
+        -0.0035425594,
+    ],
+];
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -146,7 +1093,21 @@ mod tests {
     fn test_initialization() {
         let hll = HyperLogLog::new(0.01);
         assert!(hll.m > 0);
-        assert_eq!(hll.registers.len(), hll.m);
+        assert_eq!(hll.dense_registers().len(), hll.m);
+    }
+
+    #[test]
+    fn test_with_precision() {
+        let hll = HyperLogLog::with_precision(14).unwrap();
+        assert_eq!(hll.m, 16384);
+        assert_eq!(hll.relative_error(), 1.04 / 128.0);
+        assert_eq!(HyperLogLog::with_precision(4).unwrap().m, 16);
+
+        assert!(HyperLogLog::with_precision(3).is_err());
+        assert!(HyperLogLog::with_precision(17).is_err());
+
+        // `new` picks the smallest m meeting the requested error.
+        assert!(HyperLogLog::new(0.01).relative_error() <= 0.01);
     }
 
     #[test]
@@ -166,6 +1127,123 @@ mod tests {
         assert!((count as i64 - 5).abs() <= 1);
     }
 
+    #[test]
+    fn test_sparse_until_threshold() {
+        let mut hll = HyperLogLog::new(0.01);
+        for i in 0..20 {
+            hll.add(&i);
+        }
+        assert!(hll.is_sparse());
+        assert!((hll.count() as i64 - 20).abs() <= 1);
+
+        // Round-trips and merges keep a small sketch sparse...
+        let mut restored = HyperLogLog::from_bytes(&hll.to_bytes()).unwrap();
+        assert!(restored.is_sparse());
+        assert_eq!(restored.count(), hll.count());
+
+        // ...until enough registers are set to make dense storage cheaper.
+        let mut big = HyperLogLog::new(0.01);
+        for i in 0..10_000 {
+            big.add(&i);
+        }
+        assert!(!big.is_sparse());
+        restored.merge(&big).unwrap();
+        assert!(!restored.is_sparse());
+        assert_eq!(restored.count(), big.count());
+    }
+
+    #[test]
+    fn test_bias_correction_in_mid_range() {
+        use rand::{SeedableRng, rngs::StdRng};
+
+        // The original 2007 estimator: linear counting up to 2.5m, then the
+        // uncorrected raw estimate.
+        fn classic_count(hll: &HyperLogLog) -> f64 {
+            let registers = hll.dense_registers();
+            let m = hll.m as f64;
+            let sum: f64 = registers.iter().map(|&r| 2.0f64.powi(-(r as i32))).sum();
+            let raw = hll.alpha_m * m * m / sum;
+            let zeros = registers.iter().filter(|&&r| r == 0).count();
+            if raw <= 2.5 * m && zeros > 0 {
+                m * (m / zeros as f64).ln()
+            } else {
+                raw
+            }
+        }
+
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut hll = HyperLogLog::new(0.01);
+        let (mut classic_error, mut corrected_error) = (0.0, 0.0);
+        for n in 1..=50_000u64 {
+            hll.add(&rng.random::<u64>());
+            // Just past 2.5m (~41k for m = 16384), where the raw estimate
+            // still overshoots.
+            if n >= 42_000 && n % 1_000 == 0 {
+                classic_error += (classic_count(&hll) - n as f64).abs() / n as f64;
+                corrected_error += (hll.count() as f64 - n as f64).abs() / n as f64;
+            }
+        }
+        println!(
+            "Mean relative error: classic {:.4}, corrected {:.4}",
+            classic_error / 9.0,
+            corrected_error / 9.0
+        );
+        assert!(corrected_error < 0.6 * classic_error);
+    }
+
+    #[test]
+    fn test_clear_reuses_sketch() {
+        let mut hll = HyperLogLog::new(0.02);
+        for i in 0..20_000 {
+            hll.add(&i);
+        }
+        assert!(!hll.is_sparse());
+        let bytes = hll.registers.bytes.len();
+
+        hll.clear();
+        assert_eq!(hll.count(), 0);
+        assert_eq!(hll.registers.bytes.len(), bytes);
+
+        for i in 0..1_000 {
+            hll.add(&("window 2", i));
+        }
+        let error = (hll.count() as f64 - 1_000.0).abs() / 1_000.0;
+        assert!(error < 0.1);
+    }
+
+    #[test]
+    fn test_count_beta_against_count() {
+        // Several independent sketches, told apart by a per-sketch salt.
+        let checkpoints = [100, 10_000, 20_000, 40_000, 1_000_000];
+        let mut beta_error = [0.0; 5];
+        let mut count_error = [0.0; 5];
+        let salts = 4;
+        for salt in 0..salts {
+            let mut hll = HyperLogLog::new(0.01);
+            let mut n = 0;
+            for (k, &target) in checkpoints.iter().enumerate() {
+                while n < target {
+                    hll.add(&(salt, n));
+                    n += 1;
+                }
+                beta_error[k] += (hll.count_beta() as f64 - n as f64).abs() / n as f64;
+                count_error[k] += (hll.count() as f64 - n as f64).abs() / n as f64;
+            }
+        }
+        for k in 0..checkpoints.len() {
+            beta_error[k] /= salts as f64;
+            count_error[k] /= salts as f64;
+            println!(
+                "n = {}: count_beta {:.4}, count {:.4}",
+                checkpoints[k], beta_error[k], count_error[k]
+            );
+            assert!(beta_error[k] < 0.03);
+        }
+        // Mid-range (10k..40k, across the old 2.5m switch).
+        let mid = |errors: &[f64; 5]| errors[1..4].iter().sum::<f64>();
+        assert!(mid(&beta_error) <= mid(&count_error));
+    }
+
     #[test]
     fn test_large_cardinality() {
         let mut hll = HyperLogLog::new(0.01);
@@ -193,6 +1271,36 @@ mod tests {
         assert!(error < 0.05);
     }
 
+    #[test]
+    fn test_atomic_concurrent_inserts() {
+        let hll = AtomicHyperLogLog::new(0.01);
+        std::thread::scope(|scope| {
+            for t in 0..4 {
+                let hll = &hll;
+                scope.spawn(move || {
+                    for i in t * 25_000..(t + 1) * 25_000 {
+                        hll.add(&i);
+                    }
+                });
+            }
+        });
+
+        let estimate = hll.count();
+        let error = (estimate as f64 - 100_000.0).abs() / 100_000.0;
+        println!("Concurrent estimate: {}, Actual: 100000", estimate);
+        assert!(error < 0.05);
+
+        // Same registers as feeding a plain sketch sequentially.
+        let mut plain = HyperLogLog::new(0.01);
+        for i in 0..100_000 {
+            plain.add(&i);
+        }
+        assert_eq!(
+            hll.to_hyperloglog().dense_registers(),
+            plain.dense_registers()
+        );
+    }
+
     #[test]
     fn test_merge() {
         let mut hll1 = HyperLogLog::new(0.05);
@@ -217,4 +1325,263 @@ mod tests {
         );
         assert!(error < 0.10); // Loose bound for small m
     }
+
+    #[test]
+    fn test_custom_hasher_is_order_independent() {
+        /// FNV-1a with a seed, finished with a SplitMix64 mix so the high
+        /// bits used for the register index are well spread.
+        #[derive(Clone, Copy, Debug)]
+        struct SeededFnv(u64);
+        struct FnvHasher(u64);
+
+        impl Hasher for FnvHasher {
+            fn write(&mut self, bytes: &[u8]) {
+                for &b in bytes {
+                    self.0 = (self.0 ^ b as u64).wrapping_mul(0x100_0000_01b3);
+                }
+            }
+            fn finish(&self) -> u64 {
+                let mut z = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+                z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+                z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+                z ^ (z >> 31)
+            }
+        }
+
+        impl BuildHasher for SeededFnv {
+            type Hasher = FnvHasher;
+            fn build_hasher(&self) -> FnvHasher {
+                FnvHasher(0xcbf2_9ce4_8422_2325 ^ self.0)
+            }
+        }
+
+        let mut forward = HyperLogLog::with_hasher(0.02, SeededFnv(42));
+        let mut backward = HyperLogLog::with_hasher(0.02, SeededFnv(42));
+        for i in 0..5_000 {
+            forward.add(&i);
+        }
+        for i in (0..5_000).rev() {
+            backward.add(&i);
+        }
+        assert_eq!(forward.dense_registers(), backward.dense_registers());
+        let error = (forward.count() as f64 - 5_000.0).abs() / 5_000.0;
+        assert!(error < 0.1);
+
+        // The default hasher is deterministic too, so it still merges.
+        let mut a = HyperLogLog::new(0.05);
+        let mut b = HyperLogLog::new(0.05);
+        a.add("x");
+        b.add("x");
+        assert_eq!(a.dense_registers(), b.dense_registers());
+    }
+
+    #[test]
+    fn test_packed_registers_round_trip() {
+        // A hash that puts `rank` into register `j` for precision `b`: index
+        // in the top b bits, then rank - 1 zeros and a one.
+        fn crafted(b: u32, j: u64, rank: u32) -> u64 {
+            (j << (64 - b)) | (1u64 << (64 - b - rank))
+        }
+
+        // b = 4: sixteen registers, each holding a different rank.
+        let mut hll = HyperLogLog::new(0.3).with_packed_registers();
+        assert_eq!(hll.b, 4);
+        let expected: Vec<u8> = (0..16).map(|j| 60 - 3 * j as u8).collect();
+        for (j, &rank) in expected.iter().enumerate() {
+            hll.add_hash(crafted(4, j as u64, rank as u32));
+        }
+        assert!(!hll.is_sparse());
+        assert_eq!(hll.registers.bytes.len(), 12);
+        assert_eq!(hll.dense_registers(), expected);
+
+        // Many registers, every byte-straddling offset, against a plain sketch.
+        let mut packed = HyperLogLog::new(0.05).with_packed_registers();
+        let mut plain = HyperLogLog::new(0.05);
+        let b = packed.b as u32;
+        for j in 0..packed.m as u64 {
+            let rank = 1 + (j * 7 % (64 - b as u64)) as u32;
+            packed.add_hash(crafted(b, j, rank));
+            plain.add_hash(crafted(b, j, rank));
+        }
+        assert_eq!(packed.dense_registers(), plain.dense_registers());
+        assert_eq!(packed.count(), plain.count());
+        assert_eq!(
+            packed.registers.bytes.len() * 4,
+            plain.registers.bytes.len() * 3
+        );
+    }
+
+    #[test]
+    fn test_serialization_round_trip() {
+        let mut hll = HyperLogLog::new(0.01);
+        for i in 0..10_000 {
+            hll.add(&i);
+        }
+
+        let bytes = hll.to_bytes();
+        let restored = HyperLogLog::from_bytes(&bytes).unwrap();
+        assert_eq!(restored.dense_registers(), hll.dense_registers());
+        assert_eq!(restored.count(), hll.count());
+
+        assert!(HyperLogLog::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(HyperLogLog::from_bytes(b"XYZ\x01\x0e").is_err());
+        let mut corrupt = bytes.clone();
+        corrupt[10] = 200;
+        assert!(HyperLogLog::from_bytes(&corrupt).is_err());
+    }
+
+    #[test]
+    fn test_intersect_count() {
+        let mut monday = HyperLogLog::new(0.01);
+        let mut tuesday = HyperLogLog::new(0.01);
+        for user in 0..10_000 {
+            monday.add(&user);
+        }
+        for user in 5_000..15_000 {
+            tuesday.add(&user);
+        }
+
+        let overlap = monday.intersect_count(&tuesday).unwrap();
+        let error = (overlap as f64 - 5_000.0).abs() / 5_000.0;
+        println!("Estimated intersection: {}, Actual: 5000", overlap);
+        assert!(error < 0.2);
+        // Inputs are untouched.
+        assert!((monday.count() as f64 - 10_000.0).abs() < 500.0);
+
+        assert!(monday.intersect_count(&HyperLogLog::new(0.1)).is_err());
+    }
+
+    #[test]
+    fn test_union_matches_repeated_merge() {
+        let sketches: Vec<HyperLogLog> = [0..3_000, 2_000..6_000, 5_000..9_000]
+            .into_iter()
+            .map(|range| {
+                let mut hll = HyperLogLog::new(0.02);
+                for i in range {
+                    hll.add(&i);
+                }
+                hll
+            })
+            .collect();
+        let before = sketches[0].dense_registers();
+
+        let union = HyperLogLog::union(&[&sketches[0], &sketches[1], &sketches[2]]).unwrap();
+        let mut merged = sketches[0].clone();
+        merged.merge(&sketches[1]).unwrap();
+        merged.merge(&sketches[2]).unwrap();
+        assert_eq!(union.dense_registers(), merged.dense_registers());
+        assert_eq!(sketches[0].dense_registers(), before);
+
+        assert!(HyperLogLog::<BuildHasherDefault<DefaultHasher>>::union(&[]).is_err());
+        let coarse = HyperLogLog::new(0.1);
+        assert!(HyperLogLog::union(&[&sketches[0], &coarse]).is_err());
+    }
+
+    #[test]
+    fn test_merge_many() {
+        let shards: Vec<HyperLogLog> = (0..8)
+            .map(|shard| {
+                let mut hll = HyperLogLog::new(0.01);
+                for i in 0..2_000 {
+                    hll.add(&(shard * 1_000_000 + i));
+                }
+                hll
+            })
+            .collect();
+
+        let forward = HyperLogLog::merge_many(shards.iter().cloned()).unwrap();
+        let actual = 16_000.0;
+        let error = (forward.count() as f64 - actual).abs() / actual;
+        assert!(error < 0.05, "error {:.4}", error);
+
+        // Any grouping gives the same registers: merge pairs, then the pairs.
+        let pairs: Vec<HyperLogLog> = shards
+            .chunks(2)
+            .rev()
+            .map(|pair| HyperLogLog::merge_many(pair.iter().cloned()).unwrap())
+            .collect();
+        let tree = HyperLogLog::merge_many(pairs).unwrap();
+        assert_eq!(tree.dense_registers(), forward.dense_registers());
+
+        assert!(HyperLogLog::merge_many(Vec::<HyperLogLog>::new()).is_err());
+        let coarse = HyperLogLog::new(0.2);
+        assert!(HyperLogLog::merge_many([shards[0].clone(), coarse]).is_err());
+    }
+
+    #[test]
+    fn test_count_min_never_undercounts() {
+        let mut cms = CountMinSketch::new(0.01, 0.01);
+        for i in 0..1_000u32 {
+            for _ in 0..(i % 5) {
+                cms.add(&i);
+            }
+        }
+        for i in 0..1_000u32 {
+            assert!(cms.estimate(&i) >= (i % 5) as u64);
+        }
+    }
+
+    #[test]
+    fn test_threshold_distinct_counter() {
+        let mut counter = ThresholdDistinctCounter::new(5, 0.01, 0.0001, 0.001);
+
+        // 500 heavy items seen 4 times each, 5000 light items seen once,
+        // interleaved.
+        for round in 0..4 {
+            for heavy in 0..500u32 {
+                counter.add(&format!("heavy-{}", heavy));
+            }
+            for light in (round * 1_250)..((round + 1) * 1_250) {
+                counter.add(&format!("light-{}", light));
+            }
+        }
+
+        let all = counter.count_at_least(1) as f64;
+        let heavy = counter.count_at_least(3) as f64;
+        assert!((all - 5_500.0).abs() / 5_500.0 < 0.05, "all = {}", all);
+        assert!((heavy - 500.0).abs() / 500.0 < 0.05, "heavy = {}", heavy);
+        assert!(counter.count_at_least(5) < 25);
+    }
+
+    #[test]
+    fn test_topk_heavy_hitters() {
+        // Items 0..5 occur 2000, 1000, 666, 500, 400 times, mixed with 10000
+        // items seen once.
+        let mut stream = Vec::new();
+        for heavy in 0..5u64 {
+            stream.extend(std::iter::repeat_n(heavy, 2000 / (heavy as usize + 1)));
+        }
+        stream.extend(1_000..11_000u64);
+        // Deterministic shuffle so the heavy items are spread out.
+        for i in (1..stream.len()).rev() {
+            stream.swap(i, (i * 7919 + 13) % (i + 1));
+        }
+        let n = stream.len() as u64;
+        let k = 50;
+
+        let check = |topk: &TopK<u64>| {
+            let top = topk.top();
+            for heavy in 0..5u64 {
+                let true_count = 2000 / (heavy + 1);
+                let (_, count) = top.iter().find(|(item, _)| *item == heavy).unwrap();
+                assert!(*count >= true_count && *count <= true_count + n / k as u64);
+            }
+            // The heaviest items lead the ranking.
+            assert_eq!(top[0].0, 0);
+        };
+
+        let mut whole = TopK::new(k);
+        for &item in &stream {
+            whole.add(item);
+        }
+        check(&whole);
+
+        let (left, right) = stream.split_at(stream.len() / 3);
+        let mut a = TopK::new(k);
+        let mut b = TopK::new(k);
+        left.iter().for_each(|&item| a.add(item));
+        right.iter().for_each(|&item| b.add(item));
+        a.merge(&b);
+        check(&a);
+    }
 }
@@ -1,72 +1,316 @@
+use std::cmp::Ordering;
 use std::cmp::max;
 use std::fmt;
-use std::ops::{Add, Mul, Sub};
+use std::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Rem, Sub, SubAssign};
+use std::str::FromStr;
 
-/// A large integer represented by a vector of digits.
-/// Base is 10 for simplicity in string conversion, though 2^32 or 2^64 is better for performance.
-/// We will use base 10 to keep it simple and readable as an algorithmic challenge.
+/// Operand size (in limbs) below which Karatsuba falls back to schoolbook
+/// multiplication, where the recursion overhead isn't worth it.
+const KARATSUBA_THRESHOLD: usize = 32;
+
+/// Operand size (in limbs, ~59k decimal digits) from which `Mul` switches
+/// from Karatsuba to the number-theoretic transform in `mul_fft`. A
+/// conservative cutoff: smaller operands don't repay the three transforms.
+const FFT_THRESHOLD: usize = 6144;
+
+/// NTT-friendly primes `c * 2^k + 1`, all with primitive root 3. Their
+/// product (~2^86) bounds the convolution coefficients we can recover.
+const NTT_PRIMES: [u64; 3] = [998_244_353, 167_772_161, 469_762_049];
+
+/// Longest transform all three primes support: `998_244_353 = 119 * 2^23 + 1`
+/// has roots of unity only up to order 2^23. A product's 16-bit pieces must
+/// fit in this, which allows operands of up to 2^21 limbs each (~20M
+/// decimal digits); `Mul` uses Karatsuba beyond that.
+const NTT_MAX_LEN: usize = 1 << 23;
+
+/// Largest power of ten that fits in a limb, used to convert to and from
+/// decimal nine digits at a time.
+const DECIMAL_CHUNK: u32 = 1_000_000_000;
+const DECIMAL_CHUNK_DIGITS: usize = 9;
+
+/// A large integer stored as a sign and a magnitude in base 2^32.
+/// Strings in base 10 (or any other radix) are converted on the way in and out.
 #[derive(Clone, Debug, PartialEq)]
 pub struct BigInt {
-    digits: Vec<u8>, // Stored in reverse order (little endian), so index 0 is units place.
+    limbs: Vec<u32>, // Stored in reverse order (little endian), so index 0 is the lowest limb.
     is_negative: bool,
 }
 
 impl BigInt {
+    /// Parses a decimal literal, panicking on malformed input; see
+    /// [`BigInt::parse`] for the accepted syntax.
     pub fn new(s: &str) -> Self {
-        if s.is_empty() {
-            return BigInt {
-                digits: vec![0],
-                is_negative: false,
-            };
-        }
+        BigInt::parse(s).unwrap_or_else(|e| panic!("invalid BigInt literal {:?}: {}", s, e))
+    }
 
-        let mut chars = s.chars().peekable();
-        let is_negative = if *chars.peek().unwrap() == '-' {
-            chars.next();
-            true
-        } else {
-            false
+    /// Parses a decimal integer: an optional leading '-' or '+' followed by
+    /// one or more ASCII digits. Anything else is reported together with its
+    /// byte position.
+    pub fn parse(s: &str) -> Result<BigInt, ParseBigIntError> {
+        BigInt::parse_with_options(
+            s,
+            ParseOptions {
+                allow_plus: true,
+                ..ParseOptions::default()
+            },
+        )
+    }
+
+    pub fn from_i64(n: i64) -> Self {
+        let magnitude = n.unsigned_abs();
+        let mut bigint = BigInt {
+            limbs: vec![magnitude as u32, (magnitude >> 32) as u32],
+            is_negative: n < 0,
         };
+        bigint.normalize();
+        bigint
+    }
 
-        let mut digits = Vec::new();
-        for c in chars {
-            if let Some(d) = c.to_digit(10) {
-                digits.push(d as u8);
+    fn normalize(&mut self) {
+        if self.limbs.is_empty() {
+            self.limbs.push(0);
+        }
+        while self.limbs.len() > 1 && self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+        if self.is_zero() {
+            self.is_negative = false;
+        }
+    }
+
+    fn is_zero(&self) -> bool {
+        self.limbs.len() == 1 && self.limbs[0] == 0
+    }
+
+    /// Truncating division: the quotient rounds toward zero and the
+    /// remainder takes the sign of `self`, so `(-7).div_rem(2) == (-3, -1)`.
+    /// Always `q * other + r == self`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `other` is zero.
+    pub fn div_rem(&self, other: &BigInt) -> (BigInt, BigInt) {
+        assert!(!other.is_zero(), "BigInt division by zero");
+
+        let (mut quotient, mut remainder) = self.abs_div_rem(other);
+        quotient.is_negative = self.is_negative ^ other.is_negative;
+        remainder.is_negative = self.is_negative;
+        quotient.normalize();
+        remainder.normalize();
+        (quotient, remainder)
+    }
+
+    /// Euclidean division: the quotient matching `rem_euclid`, so that
+    /// `self == q * other + r` with `0 <= r < |other|`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `other` is zero.
+    pub fn div_euclid(&self, other: &BigInt) -> BigInt {
+        let (quotient, remainder) = self.div_rem(other);
+        if remainder.is_negative {
+            // r was pulled up by |other|, so move q one step the other way.
+            let one = BigInt::from_i64(1);
+            if other.is_negative {
+                &quotient + &one
             } else {
-                // Ignore non-digits or panic? Let's ignore for robustness
+                &quotient - &one
             }
+        } else {
+            quotient
         }
-        digits.reverse();
+    }
 
-        // Normalize (remove trailing zeros in storage -> leading zeros in number)
-        // E.g. "007" -> [7, 0, 0] -> [7]
-        let mut bigint = BigInt {
-            digits,
-            is_negative,
-        };
-        bigint.normalize();
-        bigint
+    /// Euclidean remainder: always in `[0, |other|)`, regardless of the signs
+    /// of the operands, e.g. `(-7).rem_euclid(3) == 2`. This is the one
+    /// modular arithmetic wants.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `other` is zero.
+    pub fn rem_euclid(&self, other: &BigInt) -> BigInt {
+        let (_, remainder) = self.div_rem(other);
+        if remainder.is_negative {
+            &remainder + &other.abs()
+        } else {
+            remainder
+        }
     }
 
-    pub fn from_i64(n: i64) -> Self {
-        BigInt::new(&n.to_string())
+    /// Raises `self` to `exp` by repeated squaring: O(log exp) multiplications
+    /// instead of `exp`. `x.pow(0)` is one, even for zero.
+    pub fn pow(&self, mut exp: u64) -> BigInt {
+        let mut result = BigInt::from_i64(1);
+        let mut base = self.clone();
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = &result * &base;
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = &base * &base;
+            }
+        }
+        result
     }
 
-    fn normalize(&mut self) {
-        while self.digits.len() > 1 && self.digits.last() == Some(&0) {
-            self.digits.pop();
+    /// `self^exp mod modulus`, in `[0, |modulus|)`. Every product is reduced
+    /// right away, so intermediates stay below `modulus^2` instead of growing
+    /// with the full power.
+    ///
+    /// The exponent is scanned from its most significant bit down
+    /// (left-to-right square-and-multiply).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `modulus` is zero or `exp` is negative.
+    pub fn mod_pow(&self, exp: &BigInt, modulus: &BigInt) -> BigInt {
+        assert!(!modulus.is_zero(), "BigInt mod_pow with zero modulus");
+        assert!(!exp.is_negative, "BigInt mod_pow with negative exponent");
+
+        let mul_mod = |a: &BigInt, b: &BigInt| (a * b).rem_euclid(modulus);
+        let base = self.rem_euclid(modulus);
+
+        let mut result = BigInt::from_i64(1).rem_euclid(modulus);
+        for &limb in exp.limbs.iter().rev() {
+            for bit in (0..32).rev() {
+                result = mul_mod(&result, &result);
+                if (limb >> bit) & 1 == 1 {
+                    result = mul_mod(&result, &base);
+                }
+            }
         }
-        if self.digits.len() == 1 && self.digits[0] == 0 {
-            self.is_negative = false;
+        result
+    }
+
+    /// Greatest common divisor by the Euclidean algorithm. Always
+    /// non-negative; `gcd(0, n) == |n|` and `gcd(0, 0) == 0`.
+    pub fn gcd(&self, other: &BigInt) -> BigInt {
+        let mut a = self.abs();
+        let mut b = other.abs();
+        while !b.is_zero() {
+            let (_, r) = a.abs_div_rem(&b);
+            a = b;
+            b = r;
+        }
+        a
+    }
+
+    /// Least common multiple, `|a * b| / gcd(a, b)`. Zero if either operand
+    /// is zero.
+    pub fn lcm(&self, other: &BigInt) -> BigInt {
+        if self.is_zero() || other.is_zero() {
+            return BigInt::from_i64(0);
+        }
+        // Divide first so the intermediate product stays small.
+        let (quotient, _) = self.abs().abs_div_rem(&self.gcd(other));
+        &quotient * &other.abs()
+    }
+
+    /// Absolute value.
+    pub fn abs(&self) -> BigInt {
+        BigInt {
+            limbs: self.limbs.clone(),
+            is_negative: false,
+        }
+    }
+
+    /// -1, 0 or 1 according to the sign of the number.
+    pub fn signum(&self) -> i32 {
+        if self.is_zero() {
+            0
+        } else if self.is_negative {
+            -1
+        } else {
+            1
         }
     }
 
+    /// Long division of the absolute values (Knuth's Algorithm D), one limb
+    /// of the quotient at a time. Both results are non-negative.
+    fn abs_div_rem(&self, divisor: &BigInt) -> (BigInt, BigInt) {
+        if self.abs_cmp(divisor) == Ordering::Less {
+            return (BigInt::from_i64(0), self.abs());
+        }
+        if divisor.limbs.len() == 1 {
+            let mut quotient = self.limbs.clone();
+            let remainder = div_rem_small(&mut quotient, divisor.limbs[0]);
+            let mut quotient = BigInt {
+                limbs: quotient,
+                is_negative: false,
+            };
+            quotient.normalize();
+            return (quotient, BigInt::from_i64(remainder as i64));
+        }
+
+        // Shift both operands so the divisor's top limb has its high bit set;
+        // that keeps each estimated quotient limb at most two too large.
+        let shift = divisor.limbs.last().unwrap().leading_zeros();
+        let v = shl_bits(&divisor.limbs, shift);
+        let mut u = shl_bits(&self.limbs, shift);
+        u.push(0);
+        let n = v.len();
+        let mut quotient = vec![0u32; u.len() - n];
+
+        for j in (0..quotient.len()).rev() {
+            // Estimate from the top two limbs of the current window.
+            let top = ((u[j + n] as u64) << 32) | u[j + n - 1] as u64;
+            let mut q_hat = top / v[n - 1] as u64;
+            let mut r_hat = top % v[n - 1] as u64;
+            while q_hat > u32::MAX as u64
+                || q_hat * v[n - 2] as u64 > ((r_hat << 32) | u[j + n - 2] as u64)
+            {
+                q_hat -= 1;
+                r_hat += v[n - 1] as u64;
+                if r_hat > u32::MAX as u64 {
+                    break;
+                }
+            }
+
+            // u[j..=j+n] -= q_hat * v
+            let mut borrow: i64 = 0;
+            for i in 0..n {
+                let product = q_hat * v[i] as u64;
+                let t = u[i + j] as i64 - borrow - (product & 0xFFFF_FFFF) as i64;
+                u[i + j] = t as u32;
+                borrow = (product >> 32) as i64 - (t >> 32);
+            }
+            let t = u[j + n] as i64 - borrow;
+            u[j + n] = t as u32;
+
+            if t < 0 {
+                // Estimate was one too large: add the divisor back.
+                q_hat -= 1;
+                let mut carry = 0u64;
+                for i in 0..n {
+                    let sum = u[i + j] as u64 + v[i] as u64 + carry;
+                    u[i + j] = sum as u32;
+                    carry = sum >> 32;
+                }
+                u[j + n] = u[j + n].wrapping_add(carry as u32);
+            }
+            quotient[j] = q_hat as u32;
+        }
+
+        let mut quotient = BigInt {
+            limbs: quotient,
+            is_negative: false,
+        };
+        let mut remainder = BigInt {
+            limbs: shr_bits(&u[..n], shift),
+            is_negative: false,
+        };
+        quotient.normalize();
+        remainder.normalize();
+        (quotient, remainder)
+    }
+
     /// Compares absolute values
     fn abs_cmp(&self, other: &Self) -> std::cmp::Ordering {
-        if self.digits.len() != other.digits.len() {
-            return self.digits.len().cmp(&other.digits.len());
+        if self.limbs.len() != other.limbs.len() {
+            return self.limbs.len().cmp(&other.limbs.len());
         }
-        for (d1, d2) in self.digits.iter().rev().zip(other.digits.iter().rev()) {
+        for (d1, d2) in self.limbs.iter().rev().zip(other.limbs.iter().rev()) {
             if d1 != d2 {
                 return d1.cmp(d2);
             }
@@ -75,60 +319,56 @@ impl BigInt {
     }
 
     // helper for adding absolute values
-    fn abs_add(&self, other: &Self) -> Vec<u8> {
+    fn abs_add(&self, other: &Self) -> Vec<u32> {
         let mut result = Vec::new();
-        let mut carry = 0;
-        let n = max(self.digits.len(), other.digits.len());
+        let mut carry = 0u64;
+        let n = max(self.limbs.len(), other.limbs.len());
 
         for i in 0..n {
-            let d1 = *self.digits.get(i).unwrap_or(&0);
-            let d2 = *other.digits.get(i).unwrap_or(&0);
+            let d1 = *self.limbs.get(i).unwrap_or(&0) as u64;
+            let d2 = *other.limbs.get(i).unwrap_or(&0) as u64;
             let sum = d1 + d2 + carry;
-            result.push(sum % 10);
-            carry = sum / 10;
+            result.push(sum as u32);
+            carry = sum >> 32;
         }
         if carry > 0 {
-            result.push(carry);
+            result.push(carry as u32);
         }
         result
     }
 
     // helper for subtracting smaller abs from larger abs
     // Assumes self.abs() >= other.abs()
-    fn abs_sub(&self, other: &Self) -> Vec<u8> {
+    fn abs_sub(&self, other: &Self) -> Vec<u32> {
         let mut result = Vec::new();
-        let mut borrow = 0;
-
-        for i in 0..self.digits.len() {
-            let d1 = *self.digits.get(i).unwrap_or(&0) as i16;
-            let d2 = *other.digits.get(i).unwrap_or(&0) as i16;
-            let mut diff = d1 - d2 - borrow;
-            if diff < 0 {
-                diff += 10;
-                borrow = 1;
-            } else {
-                borrow = 0;
-            }
-            result.push(diff as u8);
+        let mut borrow = false;
+
+        for i in 0..self.limbs.len() {
+            let d1 = self.limbs[i];
+            let d2 = *other.limbs.get(i).unwrap_or(&0);
+            let (diff, b1) = d1.overflowing_sub(d2);
+            let (diff, b2) = diff.overflowing_sub(borrow as u32);
+            result.push(diff);
+            borrow = b1 || b2;
         }
         result
     }
 
     /// Karatsuba Multiplication
     /// x * y = (x1 * B + x0) * (y1 * B + y0)
-    /// where B = 10^m
+    /// where B = 2^(32m)
     /// = z2 * B^2 + z1 * B + z0
     /// z2 = x1 * y1
     /// z0 = x0 * y0
     /// z1 = (x1 + x0) * (y1 + y0) - z2 - z0
     pub fn karatsuba(x: &BigInt, y: &BigInt) -> BigInt {
         // Base case
-        if x.digits.len() < 2 || y.digits.len() < 2 {
+        if x.limbs.len() < KARATSUBA_THRESHOLD || y.limbs.len() < KARATSUBA_THRESHOLD {
             // Fallback to simple multiplication
             return BigInt::simple_mul(x, y);
         }
 
-        let n = max(x.digits.len(), y.digits.len());
+        let n = max(x.limbs.len(), y.limbs.len());
         let m = n / 2;
 
         // Split
@@ -144,31 +384,97 @@ impl BigInt {
         let z1_raw = BigInt::karatsuba(&x_sum, &y_sum);
         let z1 = &(&z1_raw - &z2) - &z0;
 
-        // Reassemble: z2 * 10^(2m) + z1 * 10^m + z0
+        // Reassemble: z2 * B^2 + z1 * B + z0
         let z2_shifted = z2.shift(2 * m);
         let z1_shifted = z1.shift(m);
 
         &(&z2_shifted + &z1_shifted) + &z0
     }
 
+    /// Multiplication by number-theoretic transform, O(n log n).
+    ///
+    /// The magnitudes are split into 16-bit pieces and convolved exactly
+    /// modulo three NTT primes; each coefficient of the product is below
+    /// `n * 2^32`, far under the primes' product, so the Chinese remainder
+    /// theorem recovers it without any rounding.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the transform would be longer than the primes support,
+    /// i.e. `2 * (x.limbs + y.limbs)` exceeds 2^23; see `fits_ntt`.
+    pub fn mul_fft(x: &BigInt, y: &BigInt) -> BigInt {
+        assert!(
+            Self::fits_ntt(x, y),
+            "operands too large for mul_fft ({} and {} limbs)",
+            x.limbs.len(),
+            y.limbs.len()
+        );
+        let a = to_u16_pieces(&x.limbs);
+        let b = to_u16_pieces(&y.limbs);
+        let len = (a.len() + b.len()).next_power_of_two();
+        let residues: Vec<Vec<u64>> = NTT_PRIMES
+            .iter()
+            .map(|&p| ntt_convolve(&a, &b, len, p))
+            .collect();
+
+        let [p0, p1, p2] = NTT_PRIMES;
+        let p0_inv_mod_p1 = pow_mod(p0 % p1, p1 - 2, p1);
+        let p0p1_inv_mod_p2 = pow_mod(p0 * p1 % p2, p2 - 2, p2);
+
+        let mut limbs = Vec::with_capacity(len / 2 + 2);
+        let mut carry: u128 = 0;
+        for (i, &v0) in residues[0].iter().enumerate() {
+            // Garner's algorithm: value = v0 + v1 * p0 + v2 * p0 * p1.
+            let v1 = (residues[1][i] + p1 - v0 % p1) % p1 * p0_inv_mod_p1 % p1;
+            let partial = (v0 + v1 * p0) % p2;
+            let v2 = (residues[2][i] + p2 - partial) % p2 * p0p1_inv_mod_p2 % p2;
+            let value = v0 as u128 + v1 as u128 * p0 as u128 + v2 as u128 * (p0 * p1) as u128;
+
+            carry += value;
+            if i % 2 == 0 {
+                limbs.push((carry & 0xFFFF) as u32);
+            } else {
+                *limbs.last_mut().unwrap() |= ((carry & 0xFFFF) as u32) << 16;
+            }
+            carry >>= 16;
+        }
+        while carry > 0 {
+            limbs.push(carry as u32);
+            carry >>= 32;
+        }
+
+        let mut result = BigInt {
+            limbs,
+            is_negative: x.is_negative ^ y.is_negative,
+        };
+        result.normalize();
+        result
+    }
+
+    /// Whether `mul_fft` can multiply `x` by `y`: the transform length, the
+    /// number of 16-bit pieces of both rounded up to a power of two, must
+    /// not exceed 2^23.
+    pub fn fits_ntt(x: &BigInt, y: &BigInt) -> bool {
+        (2 * (x.limbs.len() + y.limbs.len())).next_power_of_two() <= NTT_MAX_LEN
+    }
+
     fn simple_mul(x: &BigInt, y: &BigInt) -> BigInt {
         // Schoolbook for small numbers
-        let mut result = vec![0; x.digits.len() + y.digits.len()];
-
-        for (i, d1) in x.digits.iter().enumerate() {
-            let mut carry = 0;
-            for (j, d2) in y.digits.iter().enumerate() {
-                let prod = (d1 * d2) as u16 + result[i + j] as u16 + carry;
-                result[i + j] = (prod % 10) as u8;
-                carry = prod / 10;
-            }
-            if carry > 0 {
-                result[i + y.digits.len()] += carry as u8;
+        let mut result = vec![0u32; x.limbs.len() + y.limbs.len()];
+
+        for (i, &d1) in x.limbs.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, &d2) in y.limbs.iter().enumerate() {
+                // At most (2^32 - 1)^2 + 2 * (2^32 - 1) = 2^64 - 1, so no overflow.
+                let prod = d1 as u64 * d2 as u64 + result[i + j] as u64 + carry;
+                result[i + j] = prod as u32;
+                carry = prod >> 32;
             }
+            result[i + y.limbs.len()] = carry as u32;
         }
 
         let mut res = BigInt {
-            digits: result,
+            limbs: result,
             is_negative: x.is_negative ^ y.is_negative,
         };
         res.normalize();
@@ -176,22 +482,22 @@ impl BigInt {
     }
 
     fn split_at(&self, m: usize) -> (BigInt, BigInt) {
-        // Low part: digits[0..m]
-        // High part: digits[m..]
-        let m = std::cmp::min(m, self.digits.len());
-        let low_digits = self.digits[0..m].to_vec();
-        let high_digits = if m < self.digits.len() {
-            self.digits[m..].to_vec()
+        // Low part: limbs[0..m]
+        // High part: limbs[m..]
+        let m = std::cmp::min(m, self.limbs.len());
+        let low_limbs = self.limbs[0..m].to_vec();
+        let high_limbs = if m < self.limbs.len() {
+            self.limbs[m..].to_vec()
         } else {
             vec![0]
         };
 
         let mut low = BigInt {
-            digits: low_digits,
+            limbs: low_limbs,
             is_negative: false,
         }; // Split usually ignores sign logic inside karatsuba
         let mut high = BigInt {
-            digits: high_digits,
+            limbs: high_limbs,
             is_negative: false,
         };
         low.normalize();
@@ -200,25 +506,355 @@ impl BigInt {
     }
 
     fn shift(&self, power: usize) -> BigInt {
-        if self.digits.len() == 1 && self.digits[0] == 0 {
+        if self.is_zero() {
             return self.clone();
         }
-        let mut new_digits = vec![0; power];
-        new_digits.extend(&self.digits);
+        let mut new_limbs = vec![0; power];
+        new_limbs.extend(&self.limbs);
         BigInt {
-            digits: new_digits,
+            limbs: new_limbs,
             is_negative: self.is_negative,
         }
     }
 }
 
+/// `limbs = limbs * mul + add`, growing the magnitude when it carries out.
+fn mul_add_small(limbs: &mut Vec<u32>, mul: u32, add: u32) {
+    let mut carry = add as u64;
+    for limb in limbs.iter_mut() {
+        let t = *limb as u64 * mul as u64 + carry;
+        *limb = t as u32;
+        carry = t >> 32;
+    }
+    if carry > 0 {
+        limbs.push(carry as u32);
+    }
+}
+
+/// Divides the magnitude in place by a single limb and returns the remainder.
+fn div_rem_small(limbs: &mut [u32], divisor: u32) -> u32 {
+    let mut remainder = 0u64;
+    for limb in limbs.iter_mut().rev() {
+        let t = (remainder << 32) | *limb as u64;
+        *limb = (t / divisor as u64) as u32;
+        remainder = t % divisor as u64;
+    }
+    remainder as u32
+}
+
+fn to_u16_pieces(limbs: &[u32]) -> Vec<u64> {
+    limbs
+        .iter()
+        .flat_map(|&l| [(l & 0xFFFF) as u64, (l >> 16) as u64])
+        .collect()
+}
+
+fn pow_mod(mut base: u64, mut exp: u64, p: u64) -> u64 {
+    let mut result = 1;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = result * base % p;
+        }
+        base = base * base % p;
+        exp >>= 1;
+    }
+    result
+}
+
+/// In-place iterative NTT over `Z_p`; `a.len()` must be a power of two
+/// dividing `p - 1`.
+fn ntt(a: &mut [u64], p: u64, invert: bool) {
+    let n = a.len();
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j ^= bit;
+        if i < j {
+            a.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let mut w_len = pow_mod(3, (p - 1) / len as u64, p);
+        if invert {
+            w_len = pow_mod(w_len, p - 2, p);
+        }
+        for chunk in a.chunks_mut(len) {
+            let (lo, hi) = chunk.split_at_mut(len / 2);
+            let mut w = 1;
+            for (u, v) in lo.iter_mut().zip(hi.iter_mut()) {
+                let t = *v * w % p;
+                *v = (*u + p - t) % p;
+                *u = (*u + t) % p;
+                w = w * w_len % p;
+            }
+        }
+        len <<= 1;
+    }
+
+    if invert {
+        let n_inv = pow_mod(n as u64, p - 2, p);
+        for x in a.iter_mut() {
+            *x = *x * n_inv % p;
+        }
+    }
+}
+
+/// Cyclic convolution of `a` and `b` modulo `p`, padded to `len`.
+fn ntt_convolve(a: &[u64], b: &[u64], len: usize, p: u64) -> Vec<u64> {
+    let mut fa = a.to_vec();
+    let mut fb = b.to_vec();
+    fa.resize(len, 0);
+    fb.resize(len, 0);
+    ntt(&mut fa, p, false);
+    ntt(&mut fb, p, false);
+    for (x, y) in fa.iter_mut().zip(&fb) {
+        *x = *x * y % p;
+    }
+    ntt(&mut fa, p, true);
+    fa
+}
+
+/// Shifts a magnitude left by `bits < 32`, keeping the same number of limbs
+/// when nothing carries out of the top.
+fn shl_bits(limbs: &[u32], bits: u32) -> Vec<u32> {
+    if bits == 0 {
+        return limbs.to_vec();
+    }
+    let mut out = Vec::with_capacity(limbs.len() + 1);
+    let mut carry = 0;
+    for &limb in limbs {
+        out.push((limb << bits) | carry);
+        carry = limb >> (32 - bits);
+    }
+    if carry > 0 {
+        out.push(carry);
+    }
+    out
+}
+
+/// Shifts a magnitude right by `bits < 32`.
+fn shr_bits(limbs: &[u32], bits: u32) -> Vec<u32> {
+    if bits == 0 {
+        return limbs.to_vec();
+    }
+    let mut out = vec![0; limbs.len()];
+    for i in 0..limbs.len() {
+        let high = limbs.get(i + 1).map_or(0, |&l| l << (32 - bits));
+        out[i] = (limbs[i] >> bits) | high;
+    }
+    out
+}
+
+/// Error returned when a string is not a valid `BigInt`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseBigIntError {
+    /// No digits were found.
+    Empty,
+    /// A character that is neither a digit nor an allowed sign/separator,
+    /// with its byte offset in the input.
+    InvalidCharacter { found: char, position: usize },
+    /// A separator that does not split the digits into groups of three,
+    /// e.g. "1,,000", "1,00" or a leading/trailing separator.
+    MisplacedSeparator,
+    /// More than one kind of separator in the same number, e.g. "1,000_000".
+    MixedSeparators,
+}
+
+impl fmt::Display for ParseBigIntError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseBigIntError::Empty => write!(f, "cannot parse integer from empty string"),
+            ParseBigIntError::InvalidCharacter { found, position } => {
+                write!(f, "invalid character {:?} at byte {}", found, position)
+            }
+            ParseBigIntError::MisplacedSeparator => {
+                write!(
+                    f,
+                    "digit separators must split the number into groups of three"
+                )
+            }
+            ParseBigIntError::MixedSeparators => write!(f, "mixed digit separators"),
+        }
+    }
+}
+
+impl std::error::Error for ParseBigIntError {}
+
+/// Controls how lenient [`BigInt::parse_with_options`] is. The default is
+/// as strict as `FromStr`: no separators and no leading '+'.
+#[derive(Clone, Debug, Default)]
+pub struct ParseOptions {
+    /// Characters accepted as thousands separators, such as ',', '_' or ' '.
+    pub separators: Vec<char>,
+    /// Whether a leading '+' sign is accepted.
+    pub allow_plus: bool,
+}
+
+impl BigInt {
+    /// Parses a decimal integer, optionally allowing thousands separators and
+    /// a leading '+'. When separators are used they must all be the same and
+    /// group the digits in threes: "1_000_000" is fine, "1,00" is not.
+    pub fn parse_with_options(s: &str, options: ParseOptions) -> Result<BigInt, ParseBigIntError> {
+        let (is_negative, body) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => match s.strip_prefix('+') {
+                Some(rest) if options.allow_plus => (false, rest),
+                Some(_) => {
+                    return Err(ParseBigIntError::InvalidCharacter {
+                        found: '+',
+                        position: 0,
+                    });
+                }
+                None => (false, s),
+            },
+        };
+
+        let offset = s.len() - body.len();
+        let mut digits = Vec::new();
+        let mut separator = None;
+        // Digit count of each separator-delimited group, most significant first.
+        let mut groups = vec![0usize];
+        for (i, c) in body.char_indices() {
+            if let Some(d) = c.to_digit(10) {
+                digits.push(d as u8);
+                *groups.last_mut().unwrap() += 1;
+            } else if options.separators.contains(&c) {
+                if separator.is_some_and(|sep| sep != c) {
+                    return Err(ParseBigIntError::MixedSeparators);
+                }
+                separator = Some(c);
+                groups.push(0);
+            } else {
+                return Err(ParseBigIntError::InvalidCharacter {
+                    found: c,
+                    position: offset + i,
+                });
+            }
+        }
+
+        if digits.is_empty() {
+            return Err(ParseBigIntError::Empty);
+        }
+        if groups.len() > 1
+            && (!(1..=3).contains(&groups[0]) || groups[1..].iter().any(|&g| g != 3))
+        {
+            return Err(ParseBigIntError::MisplacedSeparator);
+        }
+
+        let mut limbs = vec![0];
+        for chunk in digits.chunks(DECIMAL_CHUNK_DIGITS) {
+            let value = chunk.iter().fold(0, |acc, &d| acc * 10 + d as u32);
+            mul_add_small(&mut limbs, 10u32.pow(chunk.len() as u32), value);
+        }
+        let mut bigint = BigInt { limbs, is_negative };
+        bigint.normalize();
+        Ok(bigint)
+    }
+
+    /// Parses an integer written in `radix` (2 to 36), with an optional
+    /// leading '-' or '+'. Letters are case-insensitive, as in
+    /// `i64::from_str_radix`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `radix` is outside `2..=36`.
+    pub fn from_str_radix(s: &str, radix: u32) -> Result<BigInt, ParseBigIntError> {
+        assert!(
+            (2..=36).contains(&radix),
+            "radix must be in 2..=36, got {}",
+            radix
+        );
+        let (is_negative, body) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        if body.is_empty() {
+            return Err(ParseBigIntError::Empty);
+        }
+
+        let offset = s.len() - body.len();
+        let mut limbs = vec![0];
+        for (i, c) in body.char_indices() {
+            let d = c
+                .to_digit(radix)
+                .ok_or(ParseBigIntError::InvalidCharacter {
+                    found: c,
+                    position: offset + i,
+                })?;
+            mul_add_small(&mut limbs, radix, d);
+        }
+        let mut result = BigInt { limbs, is_negative };
+        result.normalize();
+        Ok(result)
+    }
+
+    /// Formats the integer in `radix` (2 to 36) using lowercase letters,
+    /// e.g. `BigInt::from_i64(-255).to_str_radix(16) == "-ff"`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `radix` is outside `2..=36`.
+    pub fn to_str_radix(&self, radix: u32) -> String {
+        assert!(
+            (2..=36).contains(&radix),
+            "radix must be in 2..=36, got {}",
+            radix
+        );
+        let mut rest = self.limbs.clone();
+        let mut out = Vec::new();
+        loop {
+            let d = div_rem_small(&mut rest, radix);
+            out.push(char::from_digit(d, radix).unwrap());
+            while rest.len() > 1 && rest.last() == Some(&0) {
+                rest.pop();
+            }
+            if rest == [0] {
+                break;
+            }
+        }
+        if self.is_negative {
+            out.push('-');
+        }
+        out.iter().rev().collect()
+    }
+}
+
+/// Strict parsing: an optional '-' followed by one or more ASCII digits.
+impl FromStr for BigInt {
+    type Err = ParseBigIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        BigInt::parse_with_options(s, ParseOptions::default())
+    }
+}
+
 impl fmt::Display for BigInt {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         if self.is_negative {
             write!(f, "-")?;
         }
-        for d in self.digits.iter().rev() {
-            write!(f, "{}", d)?;
+        // Peel off nine decimal digits at a time, least significant first.
+        let mut rest = self.limbs.clone();
+        let mut chunks = Vec::new();
+        loop {
+            chunks.push(div_rem_small(&mut rest, DECIMAL_CHUNK));
+            while rest.len() > 1 && rest.last() == Some(&0) {
+                rest.pop();
+            }
+            if rest == [0] {
+                break;
+            }
+        }
+        let mut chunks = chunks.iter().rev();
+        write!(f, "{}", chunks.next().unwrap())?;
+        for chunk in chunks {
+            write!(f, "{:09}", chunk)?;
         }
         Ok(())
     }
@@ -229,9 +865,9 @@ impl Add for &BigInt {
 
     fn add(self, other: Self) -> BigInt {
         if self.is_negative == other.is_negative {
-            let digits = self.abs_add(other);
+            let limbs = self.abs_add(other);
             let mut res = BigInt {
-                digits,
+                limbs,
                 is_negative: self.is_negative,
             };
             res.normalize();
@@ -240,18 +876,18 @@ impl Add for &BigInt {
             // Signs differ: subtract smaller abs from larger abs
             match self.abs_cmp(other) {
                 std::cmp::Ordering::Greater => {
-                    let digits = self.abs_sub(other);
+                    let limbs = self.abs_sub(other);
                     let mut res = BigInt {
-                        digits,
+                        limbs,
                         is_negative: self.is_negative,
                     };
                     res.normalize();
                     res
                 }
                 std::cmp::Ordering::Less => {
-                    let digits = other.abs_sub(self);
+                    let limbs = other.abs_sub(self);
                     let mut res = BigInt {
-                        digits,
+                        limbs,
                         is_negative: other.is_negative,
                     };
                     res.normalize();
@@ -263,15 +899,25 @@ impl Add for &BigInt {
     }
 }
 
+impl Neg for &BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        let mut result = BigInt {
+            limbs: self.limbs.clone(),
+            is_negative: !self.is_negative,
+        };
+        // Zero stays non-negative.
+        result.normalize();
+        result
+    }
+}
+
 impl Sub for &BigInt {
     type Output = BigInt;
 
     fn sub(self, other: Self) -> BigInt {
-        let negated_other = BigInt {
-            digits: other.digits.clone(),
-            is_negative: !other.is_negative,
-        };
-        self + &negated_other
+        self + &(-other)
     }
 }
 
@@ -279,16 +925,73 @@ impl Mul for &BigInt {
     type Output = BigInt;
 
     fn mul(self, other: Self) -> BigInt {
+        if self.limbs.len().min(other.limbs.len()) >= FFT_THRESHOLD && BigInt::fits_ntt(self, other)
+        {
+            return BigInt::mul_fft(self, other);
+        }
         // To handle signs properly in Karatsuba recursion
         let mut result = BigInt::karatsuba(self, other);
         result.is_negative = self.is_negative ^ other.is_negative;
-        if result.digits.len() == 1 && result.digits[0] == 0 {
+        if result.limbs.len() == 1 && result.limbs[0] == 0 {
             result.is_negative = false;
         }
         result
     }
 }
 
+impl AddAssign<&BigInt> for BigInt {
+    fn add_assign(&mut self, other: &BigInt) {
+        if self.is_negative == other.is_negative {
+            self.limbs = self.abs_add(other);
+        } else {
+            match self.abs_cmp(other) {
+                Ordering::Greater => self.limbs = self.abs_sub(other),
+                Ordering::Less => {
+                    self.limbs = other.abs_sub(self);
+                    self.is_negative = other.is_negative;
+                }
+                Ordering::Equal => self.limbs = vec![0],
+            }
+        }
+        self.normalize();
+    }
+}
+
+impl SubAssign<&BigInt> for BigInt {
+    fn sub_assign(&mut self, other: &BigInt) {
+        // a - b == -(-a + b), which avoids cloning `other` to negate it.
+        self.is_negative = !self.is_negative;
+        *self += other;
+        self.is_negative = !self.is_negative;
+        self.normalize();
+    }
+}
+
+impl MulAssign<&BigInt> for BigInt {
+    fn mul_assign(&mut self, other: &BigInt) {
+        *self = &*self * other;
+    }
+}
+
+/// Truncating division, see `BigInt::div_rem`. Panics on a zero divisor.
+impl Div for &BigInt {
+    type Output = BigInt;
+
+    fn div(self, other: Self) -> BigInt {
+        self.div_rem(other).0
+    }
+}
+
+/// Truncating remainder (sign of the dividend), see `BigInt::div_rem`.
+/// Panics on a zero divisor.
+impl Rem for &BigInt {
+    type Output = BigInt;
+
+    fn rem(self, other: Self) -> BigInt {
+        self.div_rem(other).1
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -325,4 +1028,334 @@ mod tests {
         let big_c = &big_a * &big_b;
         assert_eq!(big_c.to_string(), "121932631112635269");
     }
+
+    #[test]
+    fn test_neg_abs_signum() {
+        let five = BigInt::from_i64(5);
+        assert_eq!(-&(-&five), five);
+        assert_eq!((-&five).to_string(), "-5");
+        assert_eq!(-&BigInt::from_i64(0), BigInt::from_i64(0));
+
+        assert_eq!(BigInt::new("-999").abs().to_string(), "999");
+        assert_eq!(BigInt::new("999").abs().to_string(), "999");
+
+        assert_eq!(BigInt::new("0").signum(), 0);
+        assert_eq!(BigInt::new("-0").signum(), 0);
+        assert_eq!(BigInt::new("-12345678901234567890").signum(), -1);
+        assert_eq!(five.signum(), 1);
+    }
+
+    #[test]
+    fn test_assign_operators() {
+        // Sum of k! for k = 1..=100, accumulated in place and by value.
+        let mut acc = BigInt::from_i64(0);
+        let mut by_value = BigInt::from_i64(0);
+        let mut factorial = BigInt::from_i64(1);
+        for k in 1..=100 {
+            factorial *= &BigInt::from_i64(k);
+            acc += &factorial;
+            by_value = &by_value + &factorial;
+        }
+        assert_eq!(acc, by_value);
+
+        // Taking the terms back out, crossing zero on the way.
+        let mut factorial = BigInt::from_i64(1);
+        for k in 1..=100 {
+            factorial *= &BigInt::from_i64(k);
+            acc -= &factorial;
+        }
+        assert_eq!(acc, BigInt::from_i64(0));
+        acc -= &BigInt::from_i64(5);
+        assert_eq!(acc.to_string(), "-5");
+        acc += &BigInt::from_i64(7);
+        assert_eq!(acc.to_string(), "2");
+    }
+
+    #[test]
+    fn test_mul_fft_matches_karatsuba() {
+        let mut seed: u64 = 0x2545_F491_4F6C_DD1D;
+        let mut random_digits = |n: usize| -> String {
+            (0..n)
+                .map(|i| {
+                    seed ^= seed << 13;
+                    seed ^= seed >> 7;
+                    seed ^= seed << 17;
+                    // No leading zero, so the number really has n digits.
+                    let d = (seed % 10) as u8;
+                    (b'0' + if i == 0 { d.max(1) } else { d }) as char
+                })
+                .collect()
+        };
+
+        let x = BigInt::new(&random_digits(20000));
+        let y = BigInt::new(&format!("-{}", random_digits(20000)));
+        // Below FFT_THRESHOLD, so `*` takes the Karatsuba path.
+        assert_eq!(BigInt::mul_fft(&x, &y), &x * &y);
+
+        // All-ones limbs make every convolution coefficient as large as it
+        // can be.
+        let max = &BigInt::from_i64(2).pow(32 * 2500) - &BigInt::from_i64(1);
+        assert_eq!(BigInt::mul_fft(&max, &max), &max * &max);
+    }
+
+    #[test]
+    fn test_ntt_length_limit() {
+        let limbs = |n: usize| BigInt {
+            limbs: vec![u32::MAX; n],
+            is_negative: false,
+        };
+        // 2 * (2^21 + 2^21) pieces is exactly the longest transform...
+        assert!(BigInt::fits_ntt(&limbs(1 << 21), &limbs(1 << 21)));
+        // ...one more limb would need a 2^24 transform, so `*` must go
+        // through Karatsuba instead.
+        assert!(!BigInt::fits_ntt(&limbs((1 << 21) + 1), &limbs(1 << 21)));
+        assert!(!BigInt::fits_ntt(&limbs(1 << 22), &limbs(1)));
+    }
+
+    #[test]
+    #[should_panic(expected = "too large for mul_fft")]
+    fn test_mul_fft_rejects_oversized_operands() {
+        let big = BigInt {
+            limbs: vec![1; 1 << 22],
+            is_negative: false,
+        };
+        BigInt::mul_fft(&big, &BigInt::from_i64(3));
+    }
+
+    #[test]
+    fn test_mul_5000_digits() {
+        // (10^n - 1)^2 = 99..98 00..01, with n - 1 nines and n - 1 zeros.
+        let n = 5000;
+        let nines = BigInt::new(&"9".repeat(n));
+        let expected = format!("{}8{}1", "9".repeat(n - 1), "0".repeat(n - 1));
+        let square = &nines * &nines;
+        assert_eq!(square.to_string(), expected);
+    }
+
+    #[test]
+    fn test_div_rem_truncates_toward_zero() {
+        let (q, r) = BigInt::from_i64(-7).div_rem(&BigInt::from_i64(3));
+        assert_eq!((q.to_string(), r.to_string()), ("-2".into(), "-1".into()));
+
+        let (q, r) = BigInt::from_i64(7).div_rem(&BigInt::from_i64(-3));
+        assert_eq!((q.to_string(), r.to_string()), ("-2".into(), "1".into()));
+
+        let (q, r) = BigInt::new("100000000000000000000").div_rem(&BigInt::from_i64(7));
+        assert_eq!(q.to_string(), "14285714285714285714");
+        assert_eq!(r.to_string(), "2");
+    }
+
+    #[test]
+    fn test_div_and_rem_operators() {
+        let minus_seven = BigInt::from_i64(-7);
+        let two = BigInt::from_i64(2);
+        assert_eq!((&minus_seven / &two).to_string(), "-3");
+        assert_eq!((&minus_seven % &two).to_string(), "-1");
+
+        let n = BigInt::new("100000000000000000000");
+        for d in [
+            "7",
+            "-7",
+            "12345678901",
+            "99999999999999999999",
+            "100000000000000000001",
+        ] {
+            let d = BigInt::new(d);
+            let (q, r) = (&n / &d, &n % &d);
+            assert_eq!(&(&q * &d) + &r, n);
+            assert_eq!(r.abs_cmp(&d), Ordering::Less);
+        }
+    }
+
+    #[test]
+    fn test_pow() {
+        assert_eq!(
+            BigInt::from_i64(3).pow(100).to_string(),
+            "515377520732011331036461129765621272702107522001"
+        );
+        assert_eq!(BigInt::from_i64(0).pow(0).to_string(), "1");
+        assert_eq!(BigInt::from_i64(-2).pow(7).to_string(), "-128");
+        assert_eq!(BigInt::from_i64(-2).pow(8).to_string(), "256");
+
+        let base = BigInt::new("-123456789");
+        let mut naive = BigInt::from_i64(1);
+        for exp in 0..=13 {
+            assert_eq!(base.pow(exp), naive);
+            naive = &naive * &base;
+        }
+    }
+
+    #[test]
+    fn test_mod_pow() {
+        let m = BigInt::from_i64(497);
+        assert_eq!(
+            BigInt::from_i64(4)
+                .mod_pow(&BigInt::from_i64(13), &m)
+                .to_string(),
+            "445"
+        );
+        assert_eq!(
+            BigInt::from_i64(4).mod_pow(&BigInt::from_i64(0), &m),
+            BigInt::from_i64(1)
+        );
+        let one = BigInt::from_i64(1);
+        assert_eq!(
+            BigInt::from_i64(12).mod_pow(&BigInt::from_i64(5), &one),
+            BigInt::from_i64(0)
+        );
+
+        // Fermat: a^(p-1) = 1 mod p for the prime p = 2^61 - 1.
+        let p = &BigInt::from_i64(2).pow(61) - &one;
+        let a = BigInt::new("123456789123456789");
+        assert_eq!(a.mod_pow(&(&p - &one), &p), one);
+
+        // Agrees with the full power for a small case.
+        let base = BigInt::from_i64(-7);
+        let exp = BigInt::from_i64(23);
+        assert_eq!(base.mod_pow(&exp, &m), base.pow(23).rem_euclid(&m));
+    }
+
+    #[test]
+    #[should_panic(expected = "zero modulus")]
+    fn test_mod_pow_zero_modulus_panics() {
+        let two = BigInt::from_i64(2);
+        two.mod_pow(&two, &BigInt::from_i64(0));
+    }
+
+    #[test]
+    fn test_radix_round_trip() {
+        let hex = "DEADBEEFCAFEBABE0123456789ABCDEF0FEDCBA9876543210";
+        let n = BigInt::from_str_radix(hex, 16).unwrap();
+        let decimal = n.to_string();
+        assert_eq!(BigInt::new(&decimal).to_str_radix(16), hex.to_lowercase());
+
+        assert_eq!(
+            BigInt::from_str_radix("-ff", 16).unwrap(),
+            BigInt::from_i64(-255)
+        );
+        assert_eq!(BigInt::from_i64(-255).to_str_radix(2), "-11111111");
+        assert_eq!(BigInt::from_i64(0).to_str_radix(36), "0");
+        assert_eq!(BigInt::from_i64(35).to_str_radix(36), "z");
+
+        assert_eq!(
+            BigInt::from_str_radix("102", 2),
+            Err(ParseBigIntError::InvalidCharacter {
+                found: '2',
+                position: 2
+            })
+        );
+        assert_eq!(BigInt::from_str_radix("-", 8), Err(ParseBigIntError::Empty));
+    }
+
+    #[test]
+    fn test_gcd_and_lcm() {
+        let gcd = |a: &str, b: &str| BigInt::new(a).gcd(&BigInt::new(b)).to_string();
+        let lcm = |a: &str, b: &str| BigInt::new(a).lcm(&BigInt::new(b)).to_string();
+
+        assert_eq!(gcd("1071", "462"), "21");
+        assert_eq!(gcd("-1071", "462"), "21");
+        assert_eq!(gcd("0", "-15"), "15");
+        assert_eq!(gcd("0", "0"), "0");
+        assert_eq!(
+            gcd("1000000000000000000000", "640000000000000000"),
+            "320000000000000000"
+        );
+
+        // Coprime: the LCM is just the product.
+        assert_eq!(lcm("1234567", "7654321"), "9449772114007");
+        assert_eq!(lcm("-4", "6"), "12");
+        assert_eq!(lcm("123456789", "0"), "0");
+    }
+
+    #[test]
+    fn test_euclidean_division() {
+        let three = BigInt::from_i64(3);
+        let minus_seven = BigInt::from_i64(-7);
+
+        // Truncating remainder keeps the dividend's sign...
+        assert_eq!(minus_seven.div_rem(&three).1.to_string(), "-1");
+        // ...while the Euclidean one is always non-negative.
+        assert_eq!(minus_seven.rem_euclid(&three).to_string(), "2");
+        assert_eq!(minus_seven.div_euclid(&three).to_string(), "-3");
+
+        for (a, b) in [(-7, 3), (7, -3), (-7, -3), (7, 3), (-6, 3), (0, -5)] {
+            let (a_big, b_big) = (BigInt::from_i64(a), BigInt::from_i64(b));
+            let q = a_big.div_euclid(&b_big);
+            let r = a_big.rem_euclid(&b_big);
+            assert_eq!(q.to_string(), a.div_euclid(b).to_string());
+            assert_eq!(r.to_string(), a.rem_euclid(b).to_string());
+            assert_eq!(&(&q * &b_big) + &r, a_big);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "division by zero")]
+    fn test_div_rem_by_zero_panics() {
+        BigInt::from_i64(1).div_rem(&BigInt::from_i64(0));
+    }
+
+    #[test]
+    fn test_from_str_is_strict() {
+        assert_eq!("-120".parse::<BigInt>().unwrap().to_string(), "-120");
+        assert_eq!(
+            "+42".parse::<BigInt>(),
+            Err(ParseBigIntError::InvalidCharacter {
+                found: '+',
+                position: 0
+            })
+        );
+        assert_eq!(
+            "1_000".parse::<BigInt>(),
+            Err(ParseBigIntError::InvalidCharacter {
+                found: '_',
+                position: 1
+            })
+        );
+        assert_eq!("".parse::<BigInt>(), Err(ParseBigIntError::Empty));
+    }
+
+    #[test]
+    fn test_parse_reports_errors() {
+        assert_eq!(BigInt::parse("+42").unwrap(), BigInt::from_i64(42));
+        assert_eq!(BigInt::parse("-007").unwrap(), BigInt::from_i64(-7));
+        assert_eq!(BigInt::parse(""), Err(ParseBigIntError::Empty));
+        assert_eq!(BigInt::parse("-"), Err(ParseBigIntError::Empty));
+        assert_eq!(
+            BigInt::parse("12a34"),
+            Err(ParseBigIntError::InvalidCharacter {
+                found: 'a',
+                position: 2
+            })
+        );
+        assert_eq!(
+            BigInt::parse("--5"),
+            Err(ParseBigIntError::InvalidCharacter {
+                found: '-',
+                position: 1
+            })
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid BigInt literal")]
+    fn test_new_panics_on_junk() {
+        BigInt::new("12a34");
+    }
+
+    #[test]
+    fn test_parse_with_grouping_options() {
+        let options = ParseOptions {
+            separators: vec![',', '_', ' '],
+            allow_plus: true,
+        };
+        let parse = |s: &str| BigInt::parse_with_options(s, options.clone());
+
+        assert_eq!(parse("1_000_000").unwrap().to_string(), "1000000");
+        assert_eq!(parse("+42").unwrap().to_string(), "42");
+        assert_eq!(parse("-12 345").unwrap().to_string(), "-12345");
+        assert_eq!(parse("1,00"), Err(ParseBigIntError::MisplacedSeparator));
+        assert_eq!(parse("1,,000"), Err(ParseBigIntError::MisplacedSeparator));
+        assert_eq!(parse(",100"), Err(ParseBigIntError::MisplacedSeparator));
+        assert_eq!(parse("1,000_000"), Err(ParseBigIntError::MixedSeparators));
+    }
 }
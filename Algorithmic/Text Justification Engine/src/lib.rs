@@ -1,41 +1,486 @@
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Number of terminal columns `s` occupies: 2 for wide (e.g. CJK)
+/// characters, 0 for combining marks, so that decomposed text ("e" +
+/// U+0301) measures the same as its precomposed form ("é"), and 1 for
+/// everything else.
+fn display_width(s: &str) -> usize {
+    s.width()
+}
+
+/// Longest prefix of `s` that fits in `max_width` columns. Combining marks
+/// stay attached to the character they follow.
+fn truncate_to_width(s: &str, max_width: usize) -> &str {
+    let mut width = 0;
+    for (i, c) in s.char_indices() {
+        width += c.width().unwrap_or(0);
+        if width > max_width {
+            return &s[..i];
+        }
+    }
+    s
+}
+
+/// What to do with a word wider than the line.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Put the word on a line of its own and let it stick out.
+    #[default]
+    Overflow,
+    /// Cut the word into width-sized pieces, one per line.
+    HardBreak,
+    /// Break the word at the hyphenator's break points, adding a '-' to each
+    /// line but the last. Where no break point yields a piece that fits
+    /// (or no hyphenator is set), that piece is hard-broken instead, so the
+    /// output never exceeds the width.
+    ///
+    /// Words that do fit may also be hyphenated at their break points when
+    /// that makes for less badness overall.
+    Hyphenate,
+}
+
+/// How the words of a line are placed within the width.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Alignment {
+    /// Single spaces between words, padded on the right.
+    Left,
+    /// Single spaces between words, padded on the left.
+    Right,
+    /// Single spaces between words, padding split between both sides (the
+    /// extra space of an odd split goes on the right).
+    Center,
+    /// Spaces spread between words so the line fills the width; the last
+    /// line is left as is.
+    #[default]
+    Justify,
+}
+
+/// A one-word change to already justified text, for `TextJustifier::rejustify`.
+/// Indices count whitespace-separated words.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WordEdit {
+    /// Inserts a word so that it becomes word `index`.
+    Insert(usize, String),
+    Delete(usize),
+    Replace(usize, String),
+}
+
+/// Justified text together with the DP state behind it; see
+/// `TextJustifier::layout`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Layout {
+    words: Vec<String>,
+    // word_starts[w] = index of word w's first token; one extra entry at the end.
+    word_starts: Vec<usize>,
+    // The words, with over-long ones split into pieces and, when
+    // hyphenating, the others into syllables.
+    tokens: Vec<String>,
+    break_after: Vec<bool>,
+    // The next token continues the same word: no space between them, and a
+    // hyphen if the line ends in between.
+    hyphen_after: Vec<bool>,
+    // Columns between each token and the next on the same line: 0 inside a
+    // word, otherwise a space or, after a tab, the tab's expanded width.
+    gap_after: Vec<usize>,
+    // Columns of tab indentation kept on the first line.
+    indent: usize,
+    dp: Vec<f64>,
+    split: Vec<usize>,
+    lines: Vec<String>,
+}
+
+impl Layout {
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    pub fn words(&self) -> &[String] {
+        &self.words
+    }
+}
+
+/// Returns the byte offsets inside a word where it may be hyphenated.
+type Hyphenator = Box<dyn Fn(&str) -> Vec<usize>>;
+
 /// Justifies text into a list of lines with a maximum width.
 /// Uses a dynamic programming approach to minimize "badness" (sum of squares of extra spaces).
 pub struct TextJustifier {
     width: usize,
+    max_lines: Option<usize>,
+    ellipsis: String,
+    overflow: OverflowPolicy,
+    hyphenator: Option<Hyphenator>,
+    alignment: Alignment,
+    justify_last_line: bool,
+    tab_width: usize,
 }
 
 impl TextJustifier {
     pub fn new(width: usize) -> Self {
-        TextJustifier { width }
+        TextJustifier {
+            width,
+            max_lines: None,
+            ellipsis: "...".to_string(),
+            overflow: OverflowPolicy::default(),
+            hyphenator: None,
+            alignment: Alignment::default(),
+            justify_last_line: false,
+            tab_width: 8,
+        }
+    }
+
+    /// Sets how lines are padded to the width. The line breaks are chosen by
+    /// the same DP whatever the alignment. Defaults to `Alignment::Justify`.
+    pub fn with_alignment(mut self, alignment: Alignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+
+    /// Sets how words wider than the line are handled. Defaults to
+    /// `OverflowPolicy::Overflow`.
+    pub fn with_overflow_policy(mut self, policy: OverflowPolicy) -> Self {
+        self.overflow = policy;
+        self
+    }
+
+    /// Sets the tab stop interval used to expand tabs into spaces. Defaults
+    /// to 8.
+    ///
+    /// Tabs are expanded by their column in the input line. Leading
+    /// whitespace that contains a tab is kept as indentation of the first
+    /// line, and a tab between two words on the same input line keeps them
+    /// at least its expanded width apart. Both count towards the line
+    /// width. Other whitespace, including leading spaces, just separates
+    /// words.
+    pub fn with_tab_width(mut self, tab_width: usize) -> Self {
+        self.tab_width = tab_width;
+        self
+    }
+
+    /// Under `Alignment::Justify`, whether the last line is also stretched
+    /// to the full width (a single word is padded on the right) instead of
+    /// left as is. Defaults to `false`. Line breaks are unaffected.
+    pub fn with_justify_last_line(mut self, justify_last_line: bool) -> Self {
+        self.justify_last_line = justify_last_line;
+        self
+    }
+
+    /// Shorthand for the overflow policy: `true` hard-breaks words wider
+    /// than the line across lines (`OverflowPolicy::HardBreak`), `false`
+    /// lets them stick out (`OverflowPolicy::Overflow`). For breaks with a
+    /// hyphen, use `OverflowPolicy::Hyphenate` instead.
+    pub fn with_break_long_words(self, break_long_words: bool) -> Self {
+        self.with_overflow_policy(if break_long_words {
+            OverflowPolicy::HardBreak
+        } else {
+            OverflowPolicy::Overflow
+        })
+    }
+
+    /// Sets the function returning a word's allowed break points (byte
+    /// offsets into the word), used by `OverflowPolicy::Hyphenate`.
+    pub fn with_hyphenator(mut self, hyphenator: impl Fn(&str) -> Vec<usize> + 'static) -> Self {
+        self.hyphenator = Some(Box::new(hyphenator));
+        self
+    }
+
+    /// Caps the number of output lines. When the justified text needs more,
+    /// the output is cut to `max_lines` and the last visible line ends with
+    /// the ellipsis. `None` (the default) means no limit.
+    pub fn with_max_lines(mut self, max_lines: Option<usize>) -> Self {
+        self.max_lines = max_lines;
+        self
+    }
+
+    /// Sets the marker appended to the last visible line on truncation.
+    /// Defaults to `"..."`.
+    pub fn with_ellipsis(mut self, ellipsis: &str) -> Self {
+        self.ellipsis = ellipsis.to_string();
+        self
     }
 
     pub fn justify(&self, text: &str) -> Vec<String> {
-        let words: Vec<&str> = text.split_whitespace().collect();
-        let n = words.len();
-        if n == 0 {
-            return vec![];
+        self.layout(text).lines
+    }
+
+    /// Justifies each paragraph of `text` on its own, with an empty line
+    /// between consecutive paragraphs. Paragraphs are separated by one or
+    /// more blank (empty or all-whitespace) lines; other line breaks are
+    /// ordinary whitespace, as in `justify`. A line limit applies to each
+    /// paragraph separately.
+    pub fn justify_paragraphs(&self, text: &str) -> Vec<String> {
+        let mut paragraphs = Vec::new();
+        let mut current = String::new();
+        for line in text.lines() {
+            if line.trim().is_empty() {
+                paragraphs.push(std::mem::take(&mut current));
+            } else {
+                current.push_str(line);
+                current.push('\n');
+            }
         }
+        paragraphs.push(current);
 
-        // dp[i] = min badness cost for words[i..]
-        let mut dp = vec![f64::MAX; n + 1];
-        // split[i] = index of the first word of the NEXT line
-        let mut split = vec![0; n + 1];
+        let mut lines = Vec::new();
+        for paragraph in paragraphs.iter().filter(|p| !p.is_empty()) {
+            if !lines.is_empty() {
+                lines.push(String::new());
+            }
+            lines.extend(self.justify(paragraph));
+        }
+        lines
+    }
 
-        dp[n] = 0.0;
+    /// Like `justify`, also returning the minimal total badness: the sum of
+    /// `(width - length)^2` over every line but the last.
+    pub fn justify_with_cost(&self, text: &str) -> (Vec<String>, f64) {
+        let layout = self.layout(text);
+        (layout.lines, layout.dp[0])
+    }
 
-        for i in (0..n).rev() {
-            let mut length = 0; // char count
+    /// Justifies `text` and keeps the DP state, so the result can later be
+    /// updated word by word with `rejustify`.
+    pub fn layout(&self, text: &str) -> Layout {
+        let mut layout = Layout {
+            words: Vec::new(),
+            word_starts: vec![0],
+            tokens: Vec::new(),
+            break_after: Vec::new(),
+            hyphen_after: Vec::new(),
+            gap_after: Vec::new(),
+            indent: 0,
+            dp: vec![0.0],
+            split: vec![0],
+            lines: Vec::new(),
+        };
+        let (indent, words) = self.split_words(text);
+        layout.indent = indent;
+        for (word, gap) in words {
+            let (pieces, breaks, hyphens) = self.tokenize(word);
+            layout.words.push(word.to_string());
+            layout.tokens.extend(pieces);
+            layout.break_after.extend(breaks);
+            layout.gap_after.extend(token_gaps(&hyphens, gap));
+            layout.hyphen_after.extend(hyphens);
+            layout.word_starts.push(layout.tokens.len());
+        }
+
+        let n = layout.tokens.len();
+        layout.dp = vec![f64::MAX; n + 1];
+        layout.dp[n] = 0.0;
+        layout.split = vec![0; n + 1];
+        self.fill_dp(&mut layout, n);
+        layout.lines = self.assemble(&layout);
+        layout
+    }
+
+    /// Updates `layout` (made by this justifier) after a one-word edit, with
+    /// the same result as justifying the edited text from scratch.
+    ///
+    /// `dp[i]` only depends on the words from `i` on, so the DP state after
+    /// the edited word is kept (shifted) and only the positions up to the
+    /// edit are recomputed. A change can still move line breaks anywhere
+    /// before it, e.g. a longer word pushing words back onto earlier lines,
+    /// which is why the recomputation has to run all the way back to the
+    /// first line; edits near the end of the text are the cheapest.
+    ///
+    /// A replacing word keeps the gap that followed the old one (wider after
+    /// a tab); an inserted word is followed by a single space. The first
+    /// line's tab indentation stays in place.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of range or the new word contains
+    /// whitespace.
+    pub fn rejustify(&self, layout: &mut Layout, edit: WordEdit) {
+        let (range, word) = match edit {
+            WordEdit::Insert(i, word) => (i..i, Some(word)),
+            WordEdit::Delete(i) => (i..i + 1, None),
+            WordEdit::Replace(i, word) => (i..i + 1, Some(word)),
+        };
+        assert!(range.end <= layout.words.len(), "word index out of range");
+
+        let (pieces, breaks, hyphens) = match &word {
+            Some(word) => {
+                assert!(
+                    !word.is_empty() && !word.contains(char::is_whitespace),
+                    "not a single word: {:?}",
+                    word
+                );
+                self.tokenize(word)
+            }
+            None => (Vec::new(), Vec::new(), Vec::new()),
+        };
+        let old_tokens = layout.word_starts[range.start]..layout.word_starts[range.end];
+        let gap = if word.is_some() && !range.is_empty() {
+            layout.gap_after[old_tokens.end - 1]
+        } else {
+            1
+        };
+        let added = pieces.len();
+        let shift = added as isize - old_tokens.len() as isize;
+        let first = old_tokens.start;
+        let resume = first + added;
+
+        // The new word (if any) ends where the following words now start.
+        let new_ends = word.is_some().then_some(resume);
+        let kept = range.start + 1 + new_ends.iter().len();
+        layout.words.splice(range.clone(), word);
+        layout
+            .word_starts
+            .splice(range.start + 1..range.end + 1, new_ends);
+        for start in &mut layout.word_starts[kept..] {
+            *start = (*start as isize + shift) as usize;
+        }
+        layout.tokens.splice(old_tokens.clone(), pieces);
+        layout.break_after.splice(old_tokens.clone(), breaks);
+        layout
+            .gap_after
+            .splice(old_tokens.clone(), token_gaps(&hyphens, gap));
+        layout.hyphen_after.splice(old_tokens.clone(), hyphens);
+
+        // Positions after the edit keep their DP values; their split
+        // targets move with them.
+        layout
+            .dp
+            .splice(old_tokens.clone(), std::iter::repeat_n(f64::MAX, added));
+        layout
+            .split
+            .splice(old_tokens, std::iter::repeat_n(0, added));
+        let n = layout.tokens.len();
+        for split in &mut layout.split[resume..n] {
+            *split = (*split as isize + shift) as usize;
+        }
+        // The first line's indentation only enters the DP at position 0. An
+        // edit there must refill position 0, and also the old first token
+        // (now at `resume`), whose value included the indentation.
+        let end = if layout.indent > 0 && first == 0 {
+            (resume + 1).min(n)
+        } else {
+            resume
+        };
+        self.fill_dp(layout, end);
+        layout.lines = self.assemble(layout);
+    }
+
+    /// Splits `text` into words, each with the gap that must follow it on
+    /// a line (see `with_tab_width`), and returns the first line's tab
+    /// indentation alongside. Columns count from the start of each input
+    /// line, with tabs advancing to the next multiple of `tab_width`.
+    fn split_words<'a>(&self, text: &'a str) -> (usize, Vec<(&'a str, usize)>) {
+        let tab_width = self.tab_width.max(1);
+        let mut indent = 0;
+        let mut words: Vec<(&str, usize)> = Vec::new();
+        let mut column = 0;
+        // The current whitespace run: its start column, and whether it has
+        // a tab (since its last newline) or a newline.
+        let (mut run_start, mut tab, mut newline) = (0, false, false);
+        let mut word_start = None;
+        for (i, c) in text.char_indices() {
+            if !c.is_whitespace() {
+                if word_start.is_none() {
+                    match words.last_mut() {
+                        Some((_, gap)) if tab && !newline => *gap = column - run_start,
+                        Some(_) => {}
+                        None if tab => indent = column,
+                        None => {}
+                    }
+                    word_start = Some(i);
+                }
+                continue;
+            }
+            if let Some(start) = word_start.take() {
+                words.push((&text[start..i], 1));
+                column += display_width(&text[start..i]);
+                (run_start, tab, newline) = (column, false, false);
+            }
+            match c {
+                '\t' => {
+                    column += tab_width - column % tab_width;
+                    tab = true;
+                }
+                '\n' => {
+                    column = 0;
+                    (tab, newline) = (false, true);
+                }
+                _ => column += c.width().unwrap_or(0),
+            }
+        }
+        if let Some(start) = word_start {
+            words.push((&text[start..], 1));
+        }
+        (indent, words)
+    }
+
+    /// Splits a word into tokens, each flagged with whether a line must end
+    /// after it and whether the line may end after it with a hyphen.
+    ///
+    /// An over-long word becomes line-sized pieces with a break after every
+    /// one but the last. Under `OverflowPolicy::Hyphenate`, any other word is
+    /// cut into syllables at the hyphenator's break points, letting the DP
+    /// hyphenate it wherever that lowers the badness.
+    fn tokenize(&self, word: &str) -> (Vec<String>, Vec<bool>, Vec<bool>) {
+        let pieces = self.split_overlong(word);
+        let last = pieces.len() - 1;
+        if last > 0 {
+            let breaks = (0..pieces.len()).map(|k| k < last).collect();
+            let hyphens = vec![false; pieces.len()];
+            return (pieces, breaks, hyphens);
+        }
+
+        let mut syllables = Vec::new();
+        if let (Some(hyphenator), OverflowPolicy::Hyphenate) = (&self.hyphenator, self.overflow) {
+            let mut points = hyphenator(word);
+            points.retain(|&b| b > 0 && b < word.len() && word.is_char_boundary(b));
+            points.sort_unstable();
+            points.dedup();
+            let mut start = 0;
+            for b in points {
+                syllables.push(word[start..b].to_string());
+                start = b;
+            }
+            syllables.push(word[start..].to_string());
+        } else {
+            syllables.push(word.to_string());
+        }
+        let last = syllables.len() - 1;
+        let hyphens = (0..syllables.len()).map(|k| k < last).collect();
+        let breaks = vec![false; syllables.len()];
+        (syllables, breaks, hyphens)
+    }
+
+    /// Computes `dp[i]`/`split[i]` for every `i < end`, given valid values
+    /// from `end` on.
+    fn fill_dp(&self, layout: &mut Layout, end: usize) {
+        let words = &layout.tokens;
+        let n = words.len();
+        let (dp, split) = (&mut layout.dp, &mut layout.split);
+
+        let hyphen_after = &layout.hyphen_after;
+
+        for i in (0..end).rev() {
+            dp[i] = f64::MAX;
+            // display columns
+            let mut length = if i == 0 { layout.indent } else { 0 };
             for j in i..n {
                 // Add length of words[j]
                 if j > i {
-                    length += 1; // Space between words
+                    length += layout.gap_after[j - 1]; // Space between words
                 }
-                length += words[j].len();
+                length += display_width(&words[j]);
 
-                if length > self.width {
+                // A word still wider than the line (only under
+                // `OverflowPolicy::Overflow`) gets a line to itself.
+                if length > self.width && j > i {
                     break;
                 }
 
+                // Ending the line inside a word costs a column for the hyphen.
+                let line_length = length + usize::from(hyphen_after[j]);
+                if line_length > self.width && j > i {
+                    continue;
+                }
+
                 // Cost calculation
                 // Badness = (width - length)^2
                 // Last line exception: usually we don't penalize the last line if it's short?
@@ -44,7 +489,7 @@ impl TextJustifier {
                 let cost = if j == n - 1 {
                     0.0
                 } else {
-                    let gaps = (self.width - length) as f64;
+                    let gaps = self.width.saturating_sub(line_length) as f64;
                     gaps.powi(2)
                 };
 
@@ -55,51 +500,174 @@ impl TextJustifier {
                         split[i] = j + 1;
                     }
                 }
+
+                if length >= self.width || layout.break_after[j] {
+                    break;
+                }
             }
         }
+    }
+
+    /// Formats the lines chosen by `layout.split`.
+    fn assemble(&self, layout: &Layout) -> Vec<String> {
+        let n = layout.tokens.len();
+        if self.max_lines == Some(0) {
+            return vec![];
+        }
 
         // Reconstruct lines
         let mut lines = Vec::new();
         let mut i = 0;
         while i < n {
-            let next_i = split[i];
-            let line_words = &words[i..next_i];
+            let next_i = layout.split[i];
+            let line_words = &Self::line_words(layout, i..next_i);
+            let indent = if i == 0 { layout.indent } else { 0 };
+            let width = self.width.saturating_sub(indent);
+
+            // Truncation is applied here rather than in the DP, so the visible
+            // lines break exactly as they would in the untruncated output.
+            if let Some(max_lines) = self.max_lines
+                && lines.len() + 1 == max_lines
+                && next_i < n
+            {
+                let line = self.truncate_line(line_words, width);
+                lines.push(" ".repeat(indent) + &line);
+                break;
+            }
 
-            // If it's the last line, left justify
-            if next_i == n {
-                let line = line_words.join(" ");
+            let line = if self.alignment != Alignment::Justify {
+                self.align_line(line_words, width)
+            } else if next_i == n && !self.justify_last_line {
+                // If it's the last line, left justify
                 // Optional: pad with spaces to width? Usually last line is not fully justified.
                 // But "justified" usually means block. However, standard rule is last line left-aligned.
-                lines.push(line);
+                join_words(line_words)
             } else {
                 // Fully justify
-                lines.push(self.format_line(line_words, self.width));
-            }
+                self.format_line(line_words, width)
+            };
+            lines.push(" ".repeat(indent) + &line);
             i = next_i;
         }
 
         lines
     }
 
-    fn format_line(&self, words: &[&str], width: usize) -> String {
+    /// The words on the line made of tokens `range`, each with the gap
+    /// that follows it, with syllables of the same word joined up and a
+    /// hyphen if the line ends inside a word.
+    fn line_words(layout: &Layout, range: std::ops::Range<usize>) -> Vec<(String, usize)> {
+        let mut words: Vec<(String, usize)> = Vec::new();
+        for j in range.clone() {
+            match words.last_mut() {
+                Some((word, gap)) if layout.hyphen_after[j - 1] => {
+                    word.push_str(&layout.tokens[j]);
+                    *gap = layout.gap_after[j];
+                }
+                _ => words.push((layout.tokens[j].clone(), layout.gap_after[j])),
+            }
+        }
+        if layout.hyphen_after[range.end - 1]
+            && let Some((word, _)) = words.last_mut()
+        {
+            word.push('-');
+        }
+        words
+    }
+
+    /// Splits `word` into line-sized pieces according to the overflow
+    /// policy; a word that fits is returned whole.
+    fn split_overlong(&self, word: &str) -> Vec<String> {
+        if display_width(word) <= self.width || self.overflow == OverflowPolicy::Overflow {
+            return vec![word.to_string()];
+        }
+        let width = self.width.max(1);
+        let breaks = match (&self.hyphenator, self.overflow) {
+            (Some(hyphenator), OverflowPolicy::Hyphenate) => hyphenator(word),
+            _ => Vec::new(),
+        };
+
+        let mut pieces = Vec::new();
+        let mut start = 0;
+        while display_width(&word[start..]) > width {
+            let rest = &word[start..];
+            // Latest break point whose piece still fits with its hyphen.
+            let hyphen_at = breaks
+                .iter()
+                .filter(|&&b| b > start && b < word.len() && word.is_char_boundary(b))
+                .filter(|&&b| display_width(&word[start..b]) < width)
+                .max();
+            match hyphen_at {
+                Some(&b) => {
+                    pieces.push(format!("{}-", &word[start..b]));
+                    start = b;
+                }
+                None => {
+                    let mut piece = truncate_to_width(rest, width);
+                    if piece.is_empty() {
+                        // A wide character in a one-column line: give it a
+                        // line anyway rather than loop forever.
+                        let first = rest.chars().next().map_or(0, char::len_utf8);
+                        piece = truncate_to_width(&rest[first..], 0);
+                        piece = &rest[..first + piece.len()];
+                    }
+                    pieces.push(piece.to_string());
+                    start += piece.len();
+                }
+            }
+        }
+        pieces.push(word[start..].to_string());
+        pieces
+    }
+
+    /// Builds the last visible line of truncated output: the line's words,
+    /// cut as needed so that the ellipsis still fits within the width.
+    fn truncate_line(&self, words: &[(String, usize)], width: usize) -> String {
+        let keep = width.saturating_sub(display_width(&self.ellipsis));
+        let content = join_words(words);
+        let mut line = truncate_to_width(&content, keep).trim_end().to_string();
+        line.push_str(&self.ellipsis);
+        line
+    }
+
+    /// Pads a line for the alignments other than `Justify`.
+    fn align_line(&self, words: &[(String, usize)], width: usize) -> String {
+        let content = join_words(words);
+        let padding = width.saturating_sub(display_width(&content));
+        let left = match self.alignment {
+            Alignment::Left | Alignment::Justify => 0,
+            Alignment::Right => padding,
+            Alignment::Center => padding / 2,
+        };
+        format!(
+            "{}{}{}",
+            " ".repeat(left),
+            content,
+            " ".repeat(padding - left)
+        )
+    }
+
+    fn format_line(&self, words: &[(String, usize)], width: usize) -> String {
         if words.len() == 1 {
-            let mut s = words[0].to_string();
-            s.push_str(&" ".repeat(width - s.len()));
+            let mut s = words[0].0.to_string();
+            s.push_str(&" ".repeat(width.saturating_sub(display_width(&s))));
             return s;
         }
 
-        let total_chars: usize = words.iter().map(|w| w.len()).sum();
-        let total_spaces = width - total_chars;
         let gaps = words.len() - 1;
+        let total_chars: usize = words.iter().map(|(w, _)| display_width(w)).sum();
+        let min_spaces: usize = words[..gaps].iter().map(|(_, gap)| gap).sum();
+        // Spread what is left beyond each gap's minimum evenly.
+        let total_spaces = width.saturating_sub(total_chars + min_spaces);
 
         let space_per_gap = total_spaces / gaps;
         let extra_spaces = total_spaces % gaps;
 
         let mut s = String::new();
-        for (i, word) in words.iter().enumerate() {
+        for (i, (word, gap)) in words.iter().enumerate() {
             s.push_str(word);
             if i < gaps {
-                let spaces = space_per_gap + if i < extra_spaces { 1 } else { 0 };
+                let spaces = gap + space_per_gap + if i < extra_spaces { 1 } else { 0 };
                 s.push_str(&" ".repeat(spaces));
             }
         }
@@ -107,6 +675,28 @@ impl TextJustifier {
     }
 }
 
+/// The gaps after a word's tokens: none between syllables, `gap` after
+/// the word itself.
+fn token_gaps(hyphens: &[bool], gap: usize) -> Vec<usize> {
+    let mut gaps: Vec<usize> = hyphens.iter().map(|&h| usize::from(!h)).collect();
+    if let Some(last) = gaps.last_mut() {
+        *last = gap;
+    }
+    gaps
+}
+
+/// Joins a line's words, each followed by its minimum gap.
+fn join_words(words: &[(String, usize)]) -> String {
+    let mut line = String::new();
+    for (i, (word, gap)) in words.iter().enumerate() {
+        line.push_str(word);
+        if i + 1 < words.len() {
+            line.push_str(&" ".repeat(*gap));
+        }
+    }
+    line
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -134,6 +724,82 @@ mod tests {
         assert_eq!(lines[1], "test."); // Last line left aligned
     }
 
+    #[test]
+    fn test_justify_last_line() {
+        let text = "This is a test.";
+        let ragged = TextJustifier::new(10).justify(text);
+        assert_eq!(ragged, vec!["This  is a", "test."]);
+
+        let padded = TextJustifier::new(10)
+            .with_justify_last_line(true)
+            .justify(text);
+        assert_eq!(padded, vec!["This  is a", "test.     "]);
+
+        let lines = TextJustifier::new(10)
+            .with_justify_last_line(true)
+            .justify("a test of it");
+        assert_eq!(lines, vec!["a  test of", "it        "]);
+        // A last line of several words is spread out like the others.
+        let lines = TextJustifier::new(14)
+            .with_justify_last_line(true)
+            .justify("a test of it");
+        assert_eq!(lines, vec!["a  test  of it"]);
+    }
+
+    #[test]
+    fn test_justify_paragraphs() {
+        let text = "This is a test.\n\n  \nAnother\nparagraph here.\n";
+        let justifier = TextJustifier::new(10);
+        let lines = justifier.justify_paragraphs(text);
+        assert_eq!(
+            lines,
+            vec![
+                "This  is a",
+                "test.",
+                "",
+                "Another   ",
+                "paragraph ",
+                "here."
+            ]
+        );
+
+        // A single paragraph comes out exactly as with `justify`.
+        let single = "This is a test.\nStill the same paragraph.";
+        assert_eq!(
+            justifier.justify_paragraphs(single),
+            justifier.justify(single)
+        );
+    }
+
+    #[test]
+    fn test_tab_expansion() {
+        let text = "\tIndented text goes on";
+        let four = TextJustifier::new(12).with_tab_width(4).justify(text);
+        assert_eq!(four, vec!["    Indented", "text goes on"]);
+
+        // The default 8-column tab leaves no room for "text" after it.
+        let eight = TextJustifier::new(16).justify(text);
+        assert_eq!(eight, vec!["        Indented", "text goes on"]);
+        let unindented = TextJustifier::new(16).justify(text.trim_start());
+        assert_eq!(unindented, vec!["Indented    text", "goes on"]);
+        // Leading spaces are not indentation, as before tabs were expanded.
+        let spaces = TextJustifier::new(16).justify("    Indented text goes on");
+        assert_eq!(spaces, unindented);
+
+        // An embedded tab reaches the next stop: "ab\t" spans 4 columns...
+        let j = TextJustifier::new(20).with_tab_width(4);
+        assert_eq!(j.justify("ab\tc"), vec!["ab  c"]);
+        // ...and at least that gap is kept when stretching the line.
+        let j = TextJustifier::new(10).with_tab_width(4);
+        assert_eq!(j.justify("x\ty z w v"), vec!["x    y z w", "v"]);
+        // With 8 columns, "a\tb" no longer fits a line of 8.
+        let j = TextJustifier::new(8);
+        assert_eq!(j.justify("a\tb c"), vec!["a       ", "b c"]);
+        assert_eq!(j.justify("a b c"), vec!["a b c"]);
+        // A tab starting a continuation line is ordinary whitespace.
+        assert_eq!(j.justify("a\n\tb"), vec!["a b"]);
+    }
+
     #[test]
     fn test_greedy_vs_dp() {
         // Example where greedy fails but DP works well?
@@ -165,4 +831,247 @@ mod tests {
         assert_eq!(lines[0].trim(), "aaa");
         assert_eq!(lines[1], "bb  cc");
     }
+
+    #[test]
+    fn test_justify_with_cost() {
+        // "aaa" leaves 3 columns, "bb cc" 1, and the last line is free.
+        let (lines, cost) = TextJustifier::new(6).justify_with_cost("aaa bb cc ddddd");
+        assert_eq!(lines, TextJustifier::new(6).justify("aaa bb cc ddddd"));
+        assert_eq!(cost, 10.0);
+
+        assert_eq!(TextJustifier::new(6).justify_with_cost(""), (vec![], 0.0));
+    }
+
+    #[test]
+    fn test_max_lines_truncates_with_ellipsis() {
+        let text = "The quick brown fox jumps over the lazy dog";
+        let full = TextJustifier::new(16).justify(text);
+        assert_eq!(full.len(), 3);
+
+        let lines = TextJustifier::new(16).with_max_lines(Some(2)).justify(text);
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], full[0]);
+        assert!(lines[1].ends_with("..."));
+        assert!(lines[1].len() <= 16);
+    }
+
+    #[test]
+    fn test_max_lines_custom_ellipsis_and_no_truncation() {
+        let text = "The quick brown fox jumps over the lazy dog";
+        let lines = TextJustifier::new(16)
+            .with_max_lines(Some(1))
+            .with_ellipsis("~")
+            .justify(text);
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].ends_with('~'));
+
+        // A limit the text already fits in leaves the output untouched.
+        let untouched = TextJustifier::new(16).with_max_lines(Some(3)).justify(text);
+        assert_eq!(untouched, TextJustifier::new(16).justify(text));
+    }
+
+    #[test]
+    fn test_combining_marks_are_zero_width() {
+        let decomposed = "Cafe\u{301} cre\u{300}me bru\u{302}le\u{301}e na\u{ef}ve";
+        let precomposed = "Caf\u{e9} cr\u{e8}me br\u{fb}l\u{e9}e na\u{ef}ve";
+        assert_eq!(display_width("e\u{301}"), display_width("\u{e9}"));
+
+        let justifier = TextJustifier::new(12);
+        let a = justifier.justify(decomposed);
+        let b = justifier.justify(precomposed);
+
+        assert_eq!(a.len(), b.len());
+        for (x, y) in a.iter().zip(&b) {
+            assert_eq!(display_width(x), display_width(y));
+            // Same layout: identical spacing between the same words.
+            assert_eq!(x.matches(' ').count(), y.matches(' ').count());
+        }
+        // Justified lines are filled to exactly the visual width.
+        assert_eq!(display_width(&a[0]), 12);
+    }
+
+    #[test]
+    fn test_wide_characters_count_double() {
+        assert_eq!(display_width("日本語"), 6);
+        assert_eq!(display_width("abc"), 3);
+
+        let text = "Learning 日本語 takes time and 日本語 practice every day";
+        let lines = TextJustifier::new(14).justify(text);
+        let (last, full) = lines.split_last().unwrap();
+        for line in full {
+            assert_eq!(display_width(line), 14, "{:?}", line);
+        }
+        assert!(display_width(last) <= 14);
+        let words: Vec<&str> = lines.iter().flat_map(|l| l.split_whitespace()).collect();
+        assert_eq!(words, text.split_whitespace().collect::<Vec<_>>());
+
+        // Hard breaks cut between characters, never through one.
+        let lines = TextJustifier::new(5)
+            .with_overflow_policy(OverflowPolicy::HardBreak)
+            .justify("日本語日本語");
+        assert_eq!(lines, vec!["日本 ", "語日 ", "本語"]);
+    }
+
+    #[test]
+    fn test_alignments() {
+        let text = "aaa bb cc ddddd";
+        let justify = |alignment| {
+            TextJustifier::new(6)
+                .with_alignment(alignment)
+                .justify(text)
+        };
+
+        // Same breaks as test_greedy_vs_dp, padded differently.
+        assert_eq!(
+            justify(Alignment::Justify),
+            vec!["aaa   ", "bb  cc", "ddddd"]
+        );
+        assert_eq!(justify(Alignment::Left), vec!["aaa   ", "bb cc ", "ddddd "]);
+        assert_eq!(
+            justify(Alignment::Right),
+            vec!["   aaa", " bb cc", " ddddd"]
+        );
+        assert_eq!(
+            justify(Alignment::Center),
+            vec![" aaa  ", "bb cc ", "ddddd "]
+        );
+    }
+
+    #[test]
+    fn test_overflow_policies() {
+        let word = "incomprehensibilities";
+        // in-com-pre-hen-si-bil-i-ties
+        let syllables = |w: &str| {
+            assert_eq!(w, "incomprehensibilities");
+            vec![2, 5, 8, 11, 13, 16, 17]
+        };
+        let justify = |policy| {
+            TextJustifier::new(8)
+                .with_overflow_policy(policy)
+                .with_hyphenator(syllables)
+                .justify(word)
+        };
+
+        assert_eq!(justify(OverflowPolicy::Overflow), vec![word]);
+        assert_eq!(
+            justify(OverflowPolicy::HardBreak),
+            vec!["incompre", "hensibil", "ities"]
+        );
+        assert_eq!(
+            justify(OverflowPolicy::Hyphenate),
+            vec!["incom-  ", "prehen- ", "sibili- ", "ties"]
+        );
+
+        // Without usable break points, hyphenation falls back to hard breaks.
+        let no_breaks = TextJustifier::new(8)
+            .with_overflow_policy(OverflowPolicy::Hyphenate)
+            .with_hyphenator(|_| vec![])
+            .justify(word);
+        assert_eq!(no_breaks, justify(OverflowPolicy::HardBreak));
+
+        // Pieces never share a line with neighbouring words past the break.
+        let lines = TextJustifier::new(10)
+            .with_overflow_policy(OverflowPolicy::HardBreak)
+            .justify("a incomprehensibilities b");
+        assert_eq!(lines, vec!["a         ", "incomprehe", "nsibilitie", "s b"]);
+    }
+
+    #[test]
+    fn test_break_long_words() {
+        let token = "abcdefghijklmnopqrstuvwxyz0123";
+        let text = format!("see {} for details", token);
+
+        let lines = TextJustifier::new(10)
+            .with_break_long_words(true)
+            .justify(&text);
+        assert!(lines.iter().all(|line| display_width(line) <= 10));
+        let rejoined: String = lines.iter().flat_map(|l| l.split_whitespace()).collect();
+        assert_eq!(rejoined, text.split_whitespace().collect::<String>());
+
+        // Off, the token gets a line to itself and sticks out.
+        let lines = TextJustifier::new(10)
+            .with_break_long_words(false)
+            .justify(&text);
+        assert!(lines.contains(&token.to_string()));
+    }
+
+    #[test]
+    fn test_hyphenation_lowers_badness() {
+        let text = "hello wonderful world";
+        // won-der-ful
+        let syllables = |w: &str| if w == "wonderful" { vec![3, 6] } else { vec![] };
+
+        let plain = TextJustifier::new(10).layout(text);
+        assert_eq!(plain.lines(), ["hello     ", "wonderful ", "world"]);
+
+        let hyphenating = TextJustifier::new(10)
+            .with_overflow_policy(OverflowPolicy::Hyphenate)
+            .with_hyphenator(syllables);
+        let layout = hyphenating.layout(text);
+        assert_eq!(layout.lines(), ["hello won-", "derful    ", "world"]);
+        // 5^2 + 1^2 = 26 without hyphenation, 0^2 + 4^2 = 16 with it.
+        assert_eq!(plain.dp[0], 26.0);
+        assert_eq!(layout.dp[0], 16.0);
+
+        // Unused break points leave the word whole.
+        assert_eq!(hyphenating.justify("wonderful"), ["wonderful"]);
+    }
+
+    #[test]
+    fn test_rejustify_matches_full_justification() {
+        let text = "The quick brown fox jumps over the lazy dog while the cat \
+                    watches from a sunny windowsill and the birds sing along";
+        let justifier = TextJustifier::new(18).with_overflow_policy(OverflowPolicy::HardBreak);
+
+        let mut words: Vec<String> = text.split_whitespace().map(String::from).collect();
+        let mut layout = justifier.layout(text);
+        let edits = [
+            WordEdit::Replace(9, "hippopotamus".to_string()),
+            WordEdit::Insert(3, "extraordinarily".to_string()),
+            WordEdit::Delete(0),
+            WordEdit::Insert(20, "unquestionably-overlong-word".to_string()),
+            WordEdit::Delete(19),
+        ];
+        for edit in edits {
+            match edit.clone() {
+                WordEdit::Insert(i, w) => words.insert(i, w),
+                WordEdit::Delete(i) => {
+                    words.remove(i);
+                }
+                WordEdit::Replace(i, w) => words[i] = w,
+            }
+            justifier.rejustify(&mut layout, edit);
+
+            let fresh = justifier.layout(&words.join(" "));
+            assert_eq!(layout, fresh);
+            assert_eq!(layout.lines(), justifier.justify(&words.join(" ")));
+        }
+
+        // A tab-indented paragraph, edited at its first word.
+        let justifier = TextJustifier::new(12).with_tab_width(4);
+        let mut words: Vec<String> = ["aa", "bbbbbbb", "cc", "dd", "ee"].map(String::from).into();
+        let mut layout = justifier.layout("\taa bbbbbbb cc dd ee");
+        for edit in [
+            WordEdit::Delete(0),
+            WordEdit::Insert(0, "x".to_string()),
+            WordEdit::Replace(0, "yyyyyy".to_string()),
+        ] {
+            match edit.clone() {
+                WordEdit::Insert(i, w) => words.insert(i, w),
+                WordEdit::Delete(i) => {
+                    words.remove(i);
+                }
+                WordEdit::Replace(i, w) => words[i] = w,
+            }
+            justifier.rejustify(&mut layout, edit.clone());
+            assert_eq!(layout, justifier.layout(&format!("\t{}", words.join(" "))));
+        }
+        assert_eq!(layout.lines(), ["    yyyyyy  ", "bbbbbbb   cc", "dd ee"]);
+
+        // A replaced word keeps the tab gap after it.
+        let mut layout = justifier.layout("a\tb c");
+        justifier.rejustify(&mut layout, WordEdit::Replace(0, "z".to_string()));
+        assert_eq!(layout, justifier.layout("z\tb c"));
+    }
 }